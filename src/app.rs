@@ -1,5 +1,7 @@
 use std::error;
 
+use crate::chess;
+
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
@@ -18,11 +20,13 @@ pub struct App {
 
 impl Default for App {
     fn default() -> Self {
-        Self {
+        let mut app = Self {
             running: true,
             state: 0,
-            games: Box::new(["Chess", "Minesweeper"]),
-        }
+            games: Box::new([]),
+        };
+        app.refresh_games();
+        app
     }
 }
 
@@ -40,6 +44,34 @@ impl App {
         self.running = false;
     }
 
+    /// Rebuilds the menu, offering to resume a chess game only while one is saved.
+    pub fn refresh_games(&mut self) {
+        let mut games = vec![
+            "Chess",
+            "Chess Puzzles",
+            "Chess Tutorial",
+            "Board Editor",
+            "Coordinate Training",
+            "Correspondence Chess",
+            "Game Archive",
+            "Chess Profiles",
+            "Host LAN Game",
+            "Join LAN Game",
+            "Watch LAN Game",
+            "Minesweeper",
+        ];
+        if chess::has_saved_game() {
+            games.insert(0, "Resume Chess");
+        }
+        self.games = games.into_boxed_slice();
+        self.state = self.state.min(self.games.len() as u8 - 1);
+    }
+
+    /// The name of the currently selected game.
+    pub fn selected_game(&self) -> &'static str {
+        self.games[self.state as usize]
+    }
+
     /// Changes the selected item, to the one directly above it, in the menu - without wrapping around.
     pub fn move_up(&mut self) {
         if self.state > 0 {
@@ -0,0 +1,128 @@
+use super::{
+    engine::{search::SearchInfo, tablebase::Verdict, Analysis},
+    game::{board::Board, chess_move::Move, Game},
+};
+
+/// Free exploration of the game with the engine analyzing every position shown, in the variants it plays.
+///
+/// The game itself is put aside while exploring, so that it can be picked up again as it was.
+pub struct AnalysisBoard {
+    /// The game as it was when analysis started.
+    game: Game,
+    /// The position the explored line starts from.
+    start: Board,
+    /// The moves of the explored line, which may run on past the position shown.
+    line: Vec<Move>,
+    /// The number of moves of the line played to reach the position shown.
+    ply: usize,
+    /// The engine analyzing the position shown, unless it does not play the variant.
+    engine: Option<Analysis>,
+}
+
+impl AnalysisBoard {
+    /// Starts exploring from the current position of the game, which is kept aside.
+    pub fn new(game: &mut Game) -> Self {
+        let start = game
+            .history()
+            .first()
+            .map(|ply| ply.board)
+            .unwrap_or(*game.board());
+        let line: Vec<Move> = game.history().iter().map(|ply| ply.mv).collect();
+        let explored = replay(start, &line);
+        Self {
+            game: std::mem::replace(game, explored),
+            start,
+            ply: line.len(),
+            line,
+            engine: analyze(game),
+        }
+    }
+
+    /// The game as it was when analysis started.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Ends the exploration, giving back the game as it was.
+    pub fn into_game(self) -> Game {
+        self.game
+    }
+
+    /// The deepest analysis of the position shown so far, if any.
+    pub fn latest(&self) -> Option<SearchInfo> {
+        self.engine.as_ref()?.latest()
+    }
+
+    /// Whether the engine analyzes the positions shown, which it does in the variants it plays.
+    pub fn has_engine(&self) -> bool {
+        self.engine.is_some()
+    }
+
+    /// What the endgame tables tell about the position shown, once looked at and if they cover it.
+    pub fn tablebase(&self) -> Option<Verdict> {
+        self.engine.as_ref()?.tablebase()
+    }
+
+    /// The best move found for the position shown so far, if any.
+    pub fn best_move(&self) -> Option<Move> {
+        self.engine.as_ref()?.best_move()
+    }
+
+    /// Plays a move in the position shown, following the explored line if it is the next move,
+    /// and branching off into a new line otherwise.
+    pub fn play(&mut self, game: &mut Game, mv: Move) {
+        if self.line.get(self.ply) != Some(&mv) {
+            self.line.truncate(self.ply);
+            self.line.push(mv);
+        }
+        self.ply += 1;
+        game.make_move(mv);
+        self.engine = analyze(game);
+    }
+
+    /// The number of moves played to reach the position shown.
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// Goes back to the moves of the game, showing the position after the given number of them.
+    pub fn jump(&mut self, game: &mut Game, ply: usize) {
+        self.line = self.game.history().iter().map(|ply| ply.mv).collect();
+        self.ply = ply.min(self.line.len());
+        *game = replay(self.start, &self.line[..self.ply]);
+        self.engine = analyze(game);
+    }
+
+    /// Steps one move back through the explored line.
+    pub fn back(&mut self, game: &mut Game) {
+        if self.ply > 0 {
+            self.ply -= 1;
+            *game = replay(self.start, &self.line[..self.ply]);
+            self.engine = analyze(game);
+        }
+    }
+
+    /// Steps one move forward through the explored line.
+    pub fn forward(&mut self, game: &mut Game) {
+        if let Some(mv) = self.line.get(self.ply).copied() {
+            self.play(game, mv);
+        }
+    }
+}
+
+/// Starts analyzing the current position of the game, if the engine plays its variant.
+fn analyze(game: &Game) -> Option<Analysis> {
+    game.board()
+        .variant()
+        .has_engine_support()
+        .then(|| Analysis::start(game))
+}
+
+/// Plays the moves from the position, rebuilding everything the game keeps track of along the way.
+fn replay(start: Board, moves: &[Move]) -> Game {
+    let mut game = Game::with_board(start);
+    for mv in moves {
+        game.make_move(*mv);
+    }
+    game
+}
@@ -0,0 +1,43 @@
+use crate::app::AppResult;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::prelude::Backend;
+
+use super::Archive;
+
+pub fn handle_key_events<B: Backend>(
+    key_event: KeyEvent,
+    archive: &mut Archive<'_, B>,
+) -> AppResult<()> {
+    if key_event.code == KeyCode::Char('c') && key_event.modifiers == KeyModifiers::CONTROL {
+        archive.quit();
+        return Ok(());
+    }
+    if archive.is_filtering() {
+        match key_event.code {
+            KeyCode::Esc => archive.clear_filter(),
+            KeyCode::Enter => archive.stop_filtering(),
+            KeyCode::Backspace => archive.erase_filter(),
+            KeyCode::Up => archive.move_up(),
+            KeyCode::Down => archive.move_down(),
+            KeyCode::Char(symbol) => archive.type_filter(symbol),
+            _ => {}
+        }
+        return Ok(());
+    }
+    if archive.is_deleting() && !matches!(key_event.code, KeyCode::Char('d') | KeyCode::Delete) {
+        archive.keep();
+        return Ok(());
+    }
+    match key_event.code {
+        KeyCode::Esc | KeyCode::Char('q') => archive.quit(),
+        KeyCode::Up => archive.move_up(),
+        KeyCode::Down => archive.move_down(),
+        KeyCode::Enter => archive.open()?,
+        KeyCode::Char('/') | KeyCode::Char('f') => archive.start_filtering(),
+        KeyCode::Char('s') => archive.cycle_sort(),
+        KeyCode::Char('r') => archive.reverse(),
+        KeyCode::Char('d') | KeyCode::Delete => archive.delete(),
+        _ => {}
+    }
+    Ok(())
+}
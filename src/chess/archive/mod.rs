@@ -0,0 +1,381 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use crate::{app::AppResult, event::Event, tui::Tui};
+use tui::prelude::Backend;
+
+use self::handler::handle_key_events;
+use super::{game::pgn::pgn_tags, storage, Chess};
+
+mod handler;
+mod ui;
+
+/// The name of the PGN database finished games are kept in, in the data directory.
+const ARCHIVE_FILE: &str = "archive.pgn";
+
+/// A finished game as found in the archive.
+pub struct ArchivedGame {
+    /// The game in Portable Game Notation.
+    pgn: String,
+    tags: Vec<(String, String)>,
+}
+
+impl ArchivedGame {
+    fn new(pgn: String) -> Self {
+        Self {
+            tags: pgn_tags(&pgn),
+            pgn,
+        }
+    }
+
+    /// The value of the tag, or nothing if the game does not have it.
+    pub fn tag(&self, name: &str) -> &str {
+        self.tags
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default()
+    }
+
+    /// The opening of the game with its ECO code, e.g. `C50 Italian Game`.
+    pub fn opening(&self) -> String {
+        format!("{} {}", self.tag("ECO"), self.tag("Opening"))
+            .trim()
+            .to_owned()
+    }
+
+    /// The number of moves the game lasted, counting a move of each side as one.
+    pub fn length(&self) -> usize {
+        let plies: usize = self.tag("PlyCount").parse().unwrap_or_default();
+        plies.div_ceil(2)
+    }
+
+    /// Whether the filter is found in the date, the players, the result or the opening, ignoring case.
+    fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+        ["Date", "White", "Black", "Result"]
+            .into_iter()
+            .map(|name| self.tag(name).to_owned())
+            .chain([self.opening()])
+            .any(|text| text.to_lowercase().contains(&filter))
+    }
+}
+
+/// Adds a finished game, written in Portable Game Notation, to the end of the archive.
+pub fn record(pgn: &str) -> io::Result<()> {
+    let path = archive_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", pgn.trim_end())?;
+    writeln!(file)
+}
+
+/// Reads every game of the archive, in the order they were played.
+fn load() -> Vec<ArchivedGame> {
+    let Ok(text) = archive_file().and_then(fs::read_to_string) else {
+        return Vec::new();
+    };
+    let mut games = Vec::new();
+    let mut pgn = String::new();
+    let mut has_movetext = false;
+    for line in text.lines() {
+        let is_tag = line.trim_start().starts_with('[');
+        // the tags after the movetext of a game belong to the next one
+        if is_tag && has_movetext {
+            games.push(ArchivedGame::new(std::mem::take(&mut pgn)));
+            has_movetext = false;
+        }
+        has_movetext |= !is_tag && !line.trim().is_empty();
+        pgn.push_str(line);
+        pgn.push('\n');
+    }
+    if has_movetext {
+        games.push(ArchivedGame::new(pgn));
+    }
+    games
+}
+
+/// Writes the archive anew with only the given games.
+fn store(games: &[ArchivedGame]) -> io::Result<()> {
+    let text: String = games
+        .iter()
+        .map(|game| format!("{}\n\n", game.pgn.trim_end()))
+        .collect();
+    fs::write(archive_file()?, text)
+}
+
+fn archive_file() -> io::Result<PathBuf> {
+    storage::data_dir()
+        .map(|dir| dir.join(ARCHIVE_FILE))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))
+}
+
+/// What the games of the archive are listed by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Date,
+    White,
+    Black,
+    Result,
+    Opening,
+    Length,
+}
+
+impl SortKey {
+    const ALL: [SortKey; 6] = [
+        SortKey::Date,
+        SortKey::White,
+        SortKey::Black,
+        SortKey::Result,
+        SortKey::Opening,
+        SortKey::Length,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SortKey::Date => "date",
+            SortKey::White => "white",
+            SortKey::Black => "black",
+            SortKey::Result => "result",
+            SortKey::Opening => "opening",
+            SortKey::Length => "length",
+        }
+    }
+
+    /// The key after this one, wrapping around to the first.
+    fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|key| *key == self);
+        Self::ALL[(index.unwrap_or(0) + 1) % Self::ALL.len()]
+    }
+}
+
+/// The screen listing the finished games of the archive, from which they can be replayed or deleted.
+pub struct Archive<'a, B: Backend> {
+    running: bool,
+    tui: Option<&'a mut Tui<B>>,
+    games: Vec<ArchivedGame>,
+    /// The index into the listed games of the highlighted one.
+    selected: usize,
+    /// The text the listed games have to contain.
+    filter: String,
+    /// Signifies whether the keys typed go into the filter.
+    is_filtering: bool,
+    sort: SortKey,
+    /// Signifies whether the games are listed from the last to the first, as they are at first to put the newest on top.
+    is_descending: bool,
+    /// Signifies whether the player was asked to confirm deleting the highlighted game.
+    is_deleting: bool,
+    /// A message for the player about the last thing they did.
+    notice: Option<String>,
+}
+
+impl<'a, B: Backend> Archive<'a, B> {
+    pub fn new(tui: &'a mut Tui<B>) -> Self {
+        Self {
+            running: true,
+            tui: Some(tui),
+            games: load(),
+            selected: 0,
+            filter: String::new(),
+            is_filtering: false,
+            sort: SortKey::Date,
+            is_descending: true,
+            is_deleting: false,
+            notice: None,
+        }
+    }
+
+    pub fn launch_archive(&mut self) -> AppResult<()> {
+        while self.running {
+            let tui = self.tui.take().expect("should always contain a tui");
+            tui.terminal.draw(|frame| ui::render(self, frame))?;
+            let event = tui.events.next()?;
+            // the tui is put back before handling the keys, as replaying a game needs it
+            self.tui = Some(tui);
+            match event {
+                Event::Tick => {}
+                Event::Key(key_event) => handle_key_events(key_event, self)?,
+                Event::Mouse(_) => {}
+                Event::Resize(_, _) => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn quit(&mut self) {
+        self.running = false;
+    }
+
+    /// The games matching the filter, in the order they are listed.
+    pub fn listed(&self) -> Vec<&ArchivedGame> {
+        let mut listed: Vec<&ArchivedGame> = self
+            .games
+            .iter()
+            .filter(|game| game.matches(&self.filter))
+            .collect();
+        // the sort is stable, so games which compare equal stay in the order they were played
+        match self.sort {
+            SortKey::Date => listed.sort_by(|a, b| a.tag("Date").cmp(b.tag("Date"))),
+            SortKey::White => listed.sort_by_key(|game| game.tag("White").to_lowercase()),
+            SortKey::Black => listed.sort_by_key(|game| game.tag("Black").to_lowercase()),
+            SortKey::Result => listed.sort_by(|a, b| a.tag("Result").cmp(b.tag("Result"))),
+            SortKey::Opening => listed.sort_by_key(|game| game.opening()),
+            SortKey::Length => listed.sort_by_key(|game| game.length()),
+        }
+        if self.is_descending {
+            listed.reverse();
+        }
+        listed
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    pub fn is_filtering(&self) -> bool {
+        self.is_filtering
+    }
+
+    pub fn sort(&self) -> SortKey {
+        self.sort
+    }
+
+    pub fn is_descending(&self) -> bool {
+        self.is_descending
+    }
+
+    pub fn is_deleting(&self) -> bool {
+        self.is_deleting
+    }
+
+    pub fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    /// The number of games in the archive, whether they are listed or not.
+    pub fn total(&self) -> usize {
+        self.games.len()
+    }
+
+    /// Forgets about the last notice and a pending deletion, as the player went on to something else.
+    fn settle(&mut self) {
+        self.notice = None;
+        self.is_deleting = false;
+    }
+
+    /// Highlights the game directly above the highlighted one - without wrapping around.
+    pub fn move_up(&mut self) {
+        self.settle();
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Highlights the game directly below the highlighted one - without wrapping around.
+    pub fn move_down(&mut self) {
+        self.settle();
+        if self.selected + 1 < self.listed().len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Lists the games by the next key, in the same direction.
+    pub fn cycle_sort(&mut self) {
+        self.settle();
+        self.sort = self.sort.next();
+        self.selected = 0;
+    }
+
+    /// Lists the games in the opposite direction.
+    pub fn reverse(&mut self) {
+        self.settle();
+        self.is_descending = !self.is_descending;
+        self.selected = 0;
+    }
+
+    /// Starts typing into the filter.
+    pub fn start_filtering(&mut self) {
+        self.settle();
+        self.is_filtering = true;
+    }
+
+    /// Stops typing into the filter, keeping what was typed.
+    pub fn stop_filtering(&mut self) {
+        self.is_filtering = false;
+    }
+
+    /// Stops typing into the filter and clears it, listing every game again.
+    pub fn clear_filter(&mut self) {
+        self.is_filtering = false;
+        self.filter.clear();
+        self.selected = 0;
+    }
+
+    pub fn type_filter(&mut self, symbol: char) {
+        self.filter.push(symbol);
+        self.selected = 0;
+    }
+
+    pub fn erase_filter(&mut self) {
+        self.filter.pop();
+        self.selected = 0;
+    }
+
+    /// The index into the archive of the highlighted game, if any game is listed.
+    fn selected_index(&self) -> Option<usize> {
+        let selected = *self.listed().get(self.selected)?;
+        self.games
+            .iter()
+            .position(|game| std::ptr::eq(game, selected))
+    }
+
+    /// Opens the highlighted game in the replay viewer, coming back to the archive once it is closed.
+    pub fn open(&mut self) -> AppResult<()> {
+        self.settle();
+        let Some(index) = self.selected_index() else {
+            return Ok(());
+        };
+        let tui = self.tui.take().expect("should always contain a tui");
+        let replay = Chess::replay(&mut *tui, &self.games[index].pgn);
+        match replay {
+            Ok(mut chess) => chess.launch_chess()?,
+            Err(error) => self.notice = Some(format!("Could not replay the game: {error}")),
+        }
+        self.tui = Some(tui);
+        Ok(())
+    }
+
+    /// Keeps the highlighted game after all, rather than deleting it.
+    pub fn keep(&mut self) {
+        self.is_deleting = false;
+    }
+
+    /// Asks the player to confirm deleting the highlighted game, and deletes it once they have.
+    pub fn delete(&mut self) {
+        let Some(index) = self.selected_index() else {
+            return;
+        };
+        if !self.is_deleting {
+            self.notice = None;
+            self.is_deleting = true;
+            return;
+        }
+        self.is_deleting = false;
+        let game = self.games.remove(index);
+        self.notice = Some(match store(&self.games) {
+            Ok(()) => String::from("Game deleted"),
+            Err(error) => {
+                self.games.insert(index, game);
+                format!("Could not delete the game: {error}")
+            }
+        });
+        self.selected = self.selected.min(self.listed().len().saturating_sub(1));
+    }
+}
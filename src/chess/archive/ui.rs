@@ -0,0 +1,132 @@
+use super::Archive;
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint},
+    prelude::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+/// The widths of the columns of the list of games, the opening taking up whatever is left.
+const COLUMN_WIDTHS: [Constraint; 6] = [
+    Constraint::Length(10),
+    Constraint::Length(12),
+    Constraint::Length(12),
+    Constraint::Length(7),
+    Constraint::Min(20),
+    Constraint::Length(5),
+];
+
+/// Renders the user interface widgets.
+pub fn render<B: Backend>(archive: &mut Archive<B>, frame: &mut Frame<'_, B>) {
+    outer_block(frame);
+    status(archive, frame);
+    games(archive, frame);
+    key_hints(archive, frame);
+}
+
+#[inline(always)]
+fn outer_block<B: Backend>(frame: &mut Frame<'_, B>) {
+    let outer_block = Block::default()
+        .title("Game Archive")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    frame.render_widget(outer_block, frame.size());
+}
+
+/// The filter and the order of the games, or the last notice.
+#[inline(always)]
+fn status<B: Backend>(archive: &mut Archive<B>, frame: &mut Frame<'_, B>) {
+    let direction = match archive.is_descending() {
+        true => "↓",
+        false => "↑",
+    };
+    let cursor = match archive.is_filtering() {
+        true => "_",
+        false => "",
+    };
+    let text = match (archive.notice(), archive.is_deleting()) {
+        (_, true) => String::from("Press d again to delete the game, or any other key to keep it"),
+        (Some(notice), _) => notice.to_owned(),
+        (None, _) => format!(
+            "Filter: {}{cursor} · {} of {} games · sorted by {} {direction}",
+            archive.filter(),
+            archive.listed().len(),
+            archive.total(),
+            archive.sort().name(),
+        ),
+    };
+    let style = match archive.is_deleting() {
+        true => Style::default().fg(Color::Red),
+        false => Style::default().fg(Color::White),
+    };
+    let area = Rect::new(2, 1, frame.size().width.saturating_sub(4), 1);
+    frame.render_widget(Paragraph::new(text).style(style), area);
+}
+
+#[inline(always)]
+fn games<B: Backend>(archive: &mut Archive<B>, frame: &mut Frame<'_, B>) {
+    let listed = archive.listed();
+    let area = Rect::new(
+        2,
+        3,
+        frame.size().width.saturating_sub(4),
+        frame.size().height.saturating_sub(6),
+    );
+    if listed.is_empty() {
+        let text = match archive.total() {
+            0 => "Finished games are kept here",
+            _ => "No game matches the filter",
+        };
+        let empty = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let header = Row::new(["Date", "White", "Black", "Result", "Opening", "Moves"]).style(
+        Style::default()
+            .add_modifier(Modifier::BOLD)
+            .fg(Color::White),
+    );
+    let rows: Vec<Row> = listed
+        .iter()
+        .map(|game| {
+            Row::new([
+                game.tag("Date").to_owned(),
+                game.tag("White").to_owned(),
+                game.tag("Black").to_owned(),
+                game.tag("Result").to_owned(),
+                game.opening(),
+                game.length().to_string(),
+            ])
+        })
+        .collect();
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&COLUMN_WIDTHS)
+        .column_spacing(2)
+        .style(Style::default().fg(Color::DarkGray))
+        .highlight_style(Style::default().fg(Color::Cyan));
+
+    let mut state = TableState::default();
+    state.select(Some(archive.selected()));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+#[inline(always)]
+fn key_hints<B: Backend>(archive: &mut Archive<B>, frame: &mut Frame<'_, B>) {
+    let text = match archive.is_filtering() {
+        true => "type to filter · Enter done · Esc clear",
+        false => "Enter replay · / filter · s sort · r reverse · d delete · Esc back",
+    };
+    let y_axis = frame.size().height.saturating_sub(2);
+    let area = Rect::new(1, y_axis, frame.size().width.saturating_sub(2), 1);
+    let hints = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(hints, area);
+}
@@ -0,0 +1,102 @@
+use std::{fs, io, path::Path};
+
+use super::{
+    game::{board::Board, board::Position, chess_move::Move, piece::PieceType},
+    storage,
+};
+
+/// The name of the opening book in the data directory.
+const BOOK_FILE: &str = "book.bin";
+
+/// The size of a single entry in a Polyglot book: the key, the move, the weight and the learning data.
+const ENTRY_SIZE: usize = 16;
+
+/// An opening book in the Polyglot `.bin` format, holding the moves known for positions by their Zobrist key.
+pub struct OpeningBook {
+    /// The entries of the book, sorted by key as the format requires.
+    entries: Vec<BookEntry>,
+}
+
+struct BookEntry {
+    key: u64,
+    /// The move packed as the destination, the origin and the promotion, three bits per file, rank and piece.
+    mv: u16,
+    weight: u16,
+}
+
+impl OpeningBook {
+    /// Reads the whole book into memory, as books are rarely larger than a few megabytes.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let entries = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|entry| BookEntry {
+                key: u64::from_be_bytes(entry[0..8].try_into().expect("should be eight bytes")),
+                mv: u16::from_be_bytes([entry[8], entry[9]]),
+                weight: u16::from_be_bytes([entry[10], entry[11]]),
+            })
+            .collect();
+        Ok(Self { entries })
+    }
+
+    /// Reads the opening book in the data directory, if there is one.
+    pub fn load() -> Option<Self> {
+        let path = storage::data_dir()?.join(BOOK_FILE);
+        Self::open(&path).ok()
+    }
+
+    /// The moves the book knows for the position, along with their weights.
+    pub fn moves(&self, board: &Board) -> Vec<(Move, u16)> {
+        let key = board.polyglot_key();
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let legal_moves = board.legal_moves();
+
+        self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.key == key)
+            .filter_map(|entry| {
+                decode_move(&legal_moves, board, entry.mv).map(|mv| (mv, entry.weight))
+            })
+            .collect()
+    }
+
+    /// Picks one of the moves the book knows for the position at random, favoring the moves with more weight.
+    pub fn pick_move(&self, board: &Board) -> Option<Move> {
+        let moves = self.moves(board);
+        let total: u32 = moves.iter().map(|(_, weight)| *weight as u32).sum();
+        if total == 0 {
+            return moves.first().map(|(mv, _)| *mv);
+        }
+
+        let mut choice = fastrand::u32(0..total);
+        for (mv, weight) in moves {
+            if choice < weight as u32 {
+                return Some(mv);
+            }
+            choice -= weight as u32;
+        }
+        None
+    }
+}
+
+/// Finds the legal move a packed Polyglot move stands for.
+///
+/// Castling is stored as the king capturing its own rook, which matches how castling is entered on the board.
+fn decode_move(legal_moves: &[Move], board: &Board, packed: u16) -> Option<Move> {
+    let square = |bits: u16| Position::new((bits & 0b111) as u8, ((bits >> 3) & 0b111) as u8);
+    let to = square(packed);
+    let from = square(packed >> 6);
+    let promotion = match (packed >> 12) & 0b111 {
+        1 => Some(PieceType::Knight),
+        2 => Some(PieceType::Bishop),
+        3 => Some(PieceType::Rook),
+        4 => Some(PieceType::Queen),
+        _ => None,
+    };
+
+    legal_moves.iter().copied().find(|mv| {
+        mv.from == from
+            && (mv.to == to || board.castling_rook(mv) == Some(to))
+            && mv.promotion == promotion
+    })
+}
@@ -0,0 +1,179 @@
+use std::time::{Duration, Instant};
+
+use super::game::piece::PieceColor;
+
+/// The time each player starts with, and the time added after each of their moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    pub initial: Duration,
+    pub increment: Duration,
+}
+
+impl TimeControl {
+    /// The time controls offered on the setup screen, from bullet to rapid.
+    pub const PRESETS: [TimeControl; 7] = [
+        TimeControl::new(1, 0),
+        TimeControl::new(2, 1),
+        TimeControl::new(3, 0),
+        TimeControl::new(3, 2),
+        TimeControl::new(5, 0),
+        TimeControl::new(10, 0),
+        TimeControl::new(15, 10),
+    ];
+
+    const fn new(minutes: u64, increment: u64) -> Self {
+        Self {
+            initial: Duration::from_secs(minutes * 60),
+            increment: Duration::from_secs(increment),
+        }
+    }
+
+    /// The time control in the usual minutes plus increment form, e.g. `3+2`.
+    pub fn name(self) -> String {
+        format!(
+            "{}+{}",
+            self.initial.as_secs() / 60,
+            self.increment.as_secs()
+        )
+    }
+
+    /// The time control after this one, or none after the last one.
+    pub fn next(time_control: Option<Self>) -> Option<Self> {
+        match time_control {
+            None => Self::PRESETS.first().copied(),
+            Some(time_control) => Self::PRESETS
+                .iter()
+                .skip_while(|preset| **preset != time_control)
+                .nth(1)
+                .copied(),
+        }
+    }
+
+    /// The time control before this one, or none before the first one.
+    pub fn previous(time_control: Option<Self>) -> Option<Self> {
+        match time_control {
+            None => Self::PRESETS.last().copied(),
+            Some(time_control) => Self::PRESETS
+                .iter()
+                .rev()
+                .skip_while(|preset| **preset != time_control)
+                .nth(1)
+                .copied(),
+        }
+    }
+}
+
+/// A chess clock counting down the time of the side to move.
+///
+/// The clock only starts running with the first move, so that white is not rushed into it.
+pub struct Clock {
+    time_control: TimeControl,
+    white: Duration,
+    black: Duration,
+    /// The side whose time is running.
+    turn: PieceColor,
+    /// When the time of the side to move started running, if the clock is running.
+    since: Option<Instant>,
+}
+
+impl Clock {
+    pub fn new(time_control: TimeControl, turn: PieceColor) -> Self {
+        Self {
+            time_control,
+            white: time_control.initial,
+            black: time_control.initial,
+            turn,
+            since: None,
+        }
+    }
+
+    /// Sets the clock back to the times the players had left, running for the side to move if the game is under way.
+    pub fn restore(
+        time_control: TimeControl,
+        white: Duration,
+        black: Duration,
+        turn: PieceColor,
+        is_running: bool,
+    ) -> Self {
+        Self {
+            time_control,
+            white,
+            black,
+            turn,
+            since: is_running.then(Instant::now),
+        }
+    }
+
+    pub fn time_control(&self) -> TimeControl {
+        self.time_control
+    }
+
+    /// Whether the time of the side to move is running out.
+    pub fn is_running(&self) -> bool {
+        self.since.is_some()
+    }
+
+    /// The time the player has left, including the time running out right now.
+    pub fn remaining(&self, color: PieceColor) -> Duration {
+        let stored = match color {
+            PieceColor::White => self.white,
+            PieceColor::Black => self.black,
+        };
+        match self.since {
+            Some(since) if color == self.turn => stored.saturating_sub(since.elapsed()),
+            _ => stored,
+        }
+    }
+
+    /// Stops the time of the player who moved, adding the increment, and starts the time of their opponent.
+    pub fn press(&mut self, color: PieceColor) {
+        let remaining = self.remaining(color);
+        let time = match color {
+            PieceColor::White => &mut self.white,
+            PieceColor::Black => &mut self.black,
+        };
+        *time = remaining + self.time_control.increment;
+        self.turn = color.opposite();
+        self.since = Some(Instant::now());
+    }
+
+    /// Stops the clock, keeping the times as they are.
+    pub fn stop(&mut self) {
+        let remaining = self.remaining(self.turn);
+        match self.turn {
+            PieceColor::White => self.white = remaining,
+            PieceColor::Black => self.black = remaining,
+        }
+        self.since = None;
+    }
+
+    /// Starts the time of the side to move running again after the clock was stopped.
+    pub fn resume(&mut self) {
+        if self.since.is_none() {
+            self.since = Some(Instant::now());
+        }
+    }
+
+    /// Sets the times of the players to the ones the other side of a network game has, keeping the clock running.
+    pub fn sync(&mut self, white: Duration, black: Duration) {
+        self.white = white;
+        self.black = black;
+        if self.since.is_some() {
+            self.since = Some(Instant::now());
+        }
+    }
+
+    /// The player whose time has run out, if any.
+    pub fn flagged(&self) -> Option<PieceColor> {
+        (self.since.is_some() && self.remaining(self.turn).is_zero()).then_some(self.turn)
+    }
+}
+
+/// Shows the time as minutes and seconds, or as seconds and tenths once there are less than ten seconds left.
+pub fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    match seconds < 10 {
+        true => format!("{}.{}", seconds, time.subsec_millis() / 100),
+        false => format!("{}:{:02}", seconds / 60, seconds % 60),
+    }
+}
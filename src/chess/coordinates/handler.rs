@@ -0,0 +1,40 @@
+use crate::app::AppResult;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use tui::prelude::Backend;
+
+use super::Coordinates;
+
+pub fn handle_key_events<B: Backend>(
+    key_event: KeyEvent,
+    coordinates: &mut Coordinates<'_, B>,
+) -> AppResult<()> {
+    if key_event.code == KeyCode::Char('c') && key_event.modifiers == KeyModifiers::CONTROL {
+        coordinates.quit();
+        return Ok(());
+    }
+    match key_event.code {
+        KeyCode::Up => coordinates.move_cursor(0, 1),
+        KeyCode::Down => coordinates.move_cursor(0, -1),
+        KeyCode::Left => coordinates.move_cursor(-1, 0),
+        KeyCode::Right => coordinates.move_cursor(1, 0),
+        KeyCode::Enter | KeyCode::Char(' ') if coordinates.is_playing() => {
+            coordinates.pick_cursor();
+        }
+        KeyCode::Esc if coordinates.is_playing() => coordinates.abandon(),
+        KeyCode::Enter | KeyCode::Char(' ') => coordinates.start(),
+        KeyCode::Char('f') => coordinates.flip(),
+        KeyCode::Esc | KeyCode::Char('q') => coordinates.quit(),
+        _ => {}
+    }
+    Ok(())
+}
+
+pub fn handle_mouse_events<B: Backend>(
+    mouse_event: MouseEvent,
+    coordinates: &mut Coordinates<'_, B>,
+) -> AppResult<()> {
+    if mouse_event.kind == MouseEventKind::Down(MouseButton::Left) {
+        coordinates.click(mouse_event.column, mouse_event.row);
+    }
+    Ok(())
+}
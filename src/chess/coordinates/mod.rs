@@ -0,0 +1,340 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use crate::{app::AppResult, event::Event, tui::Tui};
+use tui::prelude::{Backend, Rect};
+
+use self::handler::{handle_key_events, handle_mouse_events};
+use super::{
+    game::{
+        board::{Position, CHESS_BOARD_SIZE},
+        board_widget::{BoardScale, BoardWidget},
+        pgn::today,
+        piece::PieceColor,
+    },
+    storage,
+};
+
+mod handler;
+mod ui;
+
+/// The name of the file the scores of the rounds are kept in, in the data directory.
+const SCORES_FILE: &str = "coordinates.tsv";
+
+/// How long a round lasts, in which as many squares as possible are to be found.
+pub const ROUND_TIME: Duration = Duration::from_secs(30);
+
+/// A finished round of the drill.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round {
+    /// The day the round was played, in the `YYYY.MM.DD` form of PGN.
+    pub date: String,
+    /// The side the board was seen from.
+    pub side: PieceColor,
+    /// The number of squares found.
+    pub score: u32,
+    /// The number of squares picked that were not the one asked for.
+    pub mistakes: u32,
+}
+
+/// Reads the scores of every round played so far from the data directory, the oldest first.
+///
+/// The scores file has a line for each round, with the fields separated by tabs:
+///
+/// ```text
+/// 2024.05.17  white   21  2
+/// ```
+pub fn load() -> Vec<Round> {
+    let Some(text) =
+        storage::data_dir().and_then(|dir| fs::read_to_string(dir.join(SCORES_FILE)).ok())
+    else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [date, side, score, mistakes] = fields.as_slice() else {
+                return None;
+            };
+            let side = match *side {
+                "white" => PieceColor::White,
+                "black" => PieceColor::Black,
+                _ => return None,
+            };
+            Some(Round {
+                date: date.to_string(),
+                side,
+                score: score.parse().ok()?,
+                mistakes: mistakes.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Adds the round to the end of the scores file.
+fn record(round: &Round) -> io::Result<()> {
+    let dir = storage::data_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    fs::create_dir_all(&dir)?;
+    let side = match round.side {
+        PieceColor::White => "white",
+        PieceColor::Black => "black",
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(SCORES_FILE))?;
+    writeln!(
+        file,
+        "{}\t{side}\t{}\t{}",
+        round.date, round.score, round.mistakes
+    )
+}
+
+/// A drill where squares are named one after the other, and are to be found on an empty board
+/// before the time of the round runs out.
+pub struct Coordinates<'a, B: Backend> {
+    running: bool,
+    tui: Option<&'a mut Tui<B>>,
+    /// The square currently highlighted for keyboard input.
+    cursor: Position,
+    /// Signifies whether the board is seen from the side of black.
+    flipped: bool,
+    /// The square to find, while a round is being played.
+    target: Option<Position>,
+    /// When the round being played started.
+    started: Option<Instant>,
+    /// The squares found in the round being played, or the last one.
+    score: u32,
+    /// The wrong squares picked in the round being played, or the last one.
+    mistakes: u32,
+    /// The square that was asked for when the last wrong square was picked, shown so it is learned.
+    missed: Option<Position>,
+    /// The rounds played so far, the oldest first.
+    rounds: Vec<Round>,
+    /// A message for the player about the last thing they did.
+    notice: Option<String>,
+    /// Where the board was last rendered, to tell which square was clicked.
+    board_area: Rect,
+    board_scale: BoardScale,
+}
+
+impl<'a, B: Backend> Coordinates<'a, B> {
+    pub fn new(tui: &'a mut Tui<B>) -> Self {
+        Self {
+            running: true,
+            tui: Some(tui),
+            cursor: Position::new(4, 3),
+            flipped: false,
+            target: None,
+            started: None,
+            score: 0,
+            mistakes: 0,
+            missed: None,
+            rounds: load(),
+            notice: None,
+            board_area: Rect::default(),
+            board_scale: BoardScale::default(),
+        }
+    }
+
+    pub fn launch_coordinates(&mut self) -> AppResult<()> {
+        while self.running {
+            let tui = self.tui.take().expect("should always contain a tui");
+            tui.terminal.draw(|frame| ui::render(self, frame))?;
+            match tui.events.next()? {
+                Event::Tick => self.tick(),
+                Event::Key(key_event) => handle_key_events(key_event, self)?,
+                Event::Mouse(mouse_event) => handle_mouse_events(mouse_event, self)?,
+                Event::Resize(_, _) => {}
+            }
+            self.tui = Some(tui);
+        }
+        Ok(())
+    }
+
+    pub fn quit(&mut self) {
+        self.running = false;
+    }
+
+    pub fn cursor(&self) -> Position {
+        self.cursor
+    }
+
+    pub fn is_flipped(&self) -> bool {
+        self.flipped
+    }
+
+    pub fn target(&self) -> Option<Position> {
+        self.target
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// The time left in the round being played.
+    pub fn time_left(&self) -> Duration {
+        match self.started {
+            Some(started) => ROUND_TIME.saturating_sub(started.elapsed()),
+            None => ROUND_TIME,
+        }
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn mistakes(&self) -> u32 {
+        self.mistakes
+    }
+
+    pub fn missed(&self) -> Option<Position> {
+        self.missed
+    }
+
+    pub fn rounds(&self) -> &[Round] {
+        &self.rounds
+    }
+
+    pub fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    /// The side the board is seen from.
+    pub fn side(&self) -> PieceColor {
+        match self.flipped {
+            true => PieceColor::Black,
+            false => PieceColor::White,
+        }
+    }
+
+    /// The highest score of the rounds played with the board seen from the side.
+    pub fn best(&self, side: PieceColor) -> Option<u32> {
+        self.rounds
+            .iter()
+            .filter(|round| round.side == side)
+            .map(|round| round.score)
+            .max()
+    }
+
+    /// Moves the cursor by the given number of files and ranks as seen on the screen, staying on the board.
+    pub fn move_cursor(&mut self, file: i8, rank: i8) {
+        let (file, rank) = match self.flipped {
+            true => (-file, -rank),
+            false => (file, rank),
+        };
+        if let Some(position) = self.cursor.offset(file, rank) {
+            self.cursor = position;
+        }
+    }
+
+    /// Turns the board around between rounds.
+    pub fn flip(&mut self) {
+        if !self.is_playing() {
+            self.flipped = !self.flipped;
+        }
+    }
+
+    /// Starts a new round, naming the first square to find.
+    pub fn start(&mut self) {
+        self.notice = None;
+        self.score = 0;
+        self.mistakes = 0;
+        self.missed = None;
+        self.started = Some(Instant::now());
+        self.target = Some(random_square(None));
+    }
+
+    /// Abandons the round being played, without keeping its score.
+    pub fn abandon(&mut self) {
+        self.started = None;
+        self.target = None;
+        self.missed = None;
+        self.notice = Some(String::from("Round abandoned"));
+    }
+
+    /// Picks the square under the cursor as the answer.
+    pub fn pick_cursor(&mut self) {
+        self.pick(self.cursor);
+    }
+
+    /// Picks the square as the answer, naming the next square whether it was the right one or not.
+    pub fn pick(&mut self, position: Position) {
+        self.cursor = position;
+        let Some(target) = self.target else {
+            return;
+        };
+        if position == target {
+            self.score += 1;
+            self.missed = None;
+        } else {
+            self.mistakes += 1;
+            self.missed = Some(target);
+        }
+        self.target = Some(random_square(Some(target)));
+    }
+
+    /// Picks the square clicked on the board, if any.
+    pub fn click(&mut self, x: u16, y: u16) {
+        if let Some(position) =
+            BoardWidget::square_at(self.board_area, self.board_scale, self.flipped, x, y)
+        {
+            self.pick(position);
+        }
+    }
+
+    /// Ends the round once its time has run out, and keeps its score.
+    fn tick(&mut self) {
+        if !self.is_playing() || !self.time_left().is_zero() {
+            return;
+        }
+        self.started = None;
+        self.target = None;
+        self.missed = None;
+        let round = Round {
+            date: today(),
+            side: self.side(),
+            score: self.score,
+            mistakes: self.mistakes,
+        };
+        let is_best = self.best(round.side).is_none_or(|best| round.score > best);
+        let mut notice = format!(
+            "Time is up: {} found, {}",
+            count(round.score, "square"),
+            count(round.mistakes, "mistake")
+        );
+        if is_best && round.score > 0 {
+            notice.push_str(" · a new best!");
+        }
+        if let Err(error) = record(&round) {
+            notice = format!("{notice} · Could not keep the score: {error}");
+        }
+        self.notice = Some(notice);
+        self.rounds.push(round);
+    }
+}
+
+/// The number of things, followed by the word for them, which is made plural unless there is one.
+fn count(number: u32, word: &str) -> String {
+    match number {
+        1 => format!("1 {word}"),
+        _ => format!("{number} {word}s"),
+    }
+}
+
+/// A square picked at random, other than the previous one so that each square asked for is a new one.
+fn random_square(previous: Option<Position>) -> Position {
+    loop {
+        let position = Position::new(
+            fastrand::u8(0..CHESS_BOARD_SIZE as u8),
+            fastrand::u8(0..CHESS_BOARD_SIZE as u8),
+        );
+        if Some(position) != previous {
+            return position;
+        }
+    }
+}
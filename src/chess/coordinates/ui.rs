@@ -0,0 +1,195 @@
+use super::{Coordinates, ROUND_TIME};
+use crate::chess::game::{
+    board::Board,
+    board_widget::{BoardScale, BoardWidget},
+    piece::PieceColor,
+};
+use tui::{
+    backend::Backend,
+    layout::Alignment,
+    prelude::Rect,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+/// The width of the panel beside the board, showing the square to find or the scores of the last rounds.
+const PANEL_WIDTH: u16 = 34;
+
+/// The gap between the board and the panel.
+const PANEL_GAP: u16 = 4;
+
+/// The rows below the board: a gap and the key hints.
+const ROWS_BELOW_BOARD: u16 = 3;
+
+/// The number of rounds shown below the best scores.
+const LATEST_ROUNDS: usize = 8;
+
+/// Renders the user interface widgets.
+pub fn render<B: Backend>(coordinates: &mut Coordinates<B>, frame: &mut Frame<'_, B>) {
+    outer_block(frame);
+    let size = frame.size();
+    // the largest board that fits beside the panel, or the smallest one if none does
+    let scale = BoardScale::LARGEST_FIRST
+        .into_iter()
+        .find(|scale| {
+            scale.width() + PANEL_GAP + PANEL_WIDTH + 2 <= size.width
+                && scale.height() + ROWS_BELOW_BOARD + 2 <= size.height
+        })
+        .unwrap_or(BoardScale::Compact);
+    let total_width = scale.width() + PANEL_GAP + PANEL_WIDTH;
+    let x = size.width.saturating_sub(total_width) / 2;
+    let y = size
+        .height
+        .saturating_sub(scale.height() + ROWS_BELOW_BOARD)
+        / 2;
+    let board_area = Rect::new(x, y, scale.width(), scale.height()).intersection(size);
+    let panel_area = Rect::new(
+        x + scale.width() + PANEL_GAP,
+        y,
+        PANEL_WIDTH,
+        scale.height(),
+    )
+    .intersection(size);
+    coordinates.board_area = board_area;
+    coordinates.board_scale = scale;
+
+    board(coordinates, frame, board_area, scale);
+    match coordinates.is_playing() {
+        true => round(coordinates, frame, panel_area),
+        false => scores(coordinates, frame, panel_area),
+    }
+    key_hints(coordinates, frame);
+}
+
+#[inline(always)]
+fn outer_block<B: Backend>(frame: &mut Frame<'_, B>) {
+    let outer_block = Block::default()
+        .title("Coordinate Training")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    frame.render_widget(outer_block, frame.size());
+}
+
+/// An empty board, with the square that was missed last shown so it is learned.
+#[inline(always)]
+fn board<B: Backend>(
+    coordinates: &mut Coordinates<B>,
+    frame: &mut Frame<'_, B>,
+    area: Rect,
+    scale: BoardScale,
+) {
+    let empty = Board::empty();
+    let board = BoardWidget::new(&empty)
+        .cursor(coordinates.cursor())
+        .hint(coordinates.missed().into_iter().collect())
+        .flipped(coordinates.is_flipped())
+        .scale(scale);
+    frame.render_widget(board, area);
+}
+
+/// The square to find, the time left and the score of the round being played.
+#[inline(always)]
+fn round<B: Backend>(coordinates: &mut Coordinates<B>, frame: &mut Frame<'_, B>, area: Rect) {
+    let heading = Style::default()
+        .add_modifier(Modifier::BOLD)
+        .fg(Color::White);
+    let target = coordinates
+        .target()
+        .map(|target| target.to_string())
+        .unwrap_or_default();
+    let time_left = coordinates.time_left();
+    // the time is rounded up, so that the round ends as the clock shows 0
+    let seconds = (time_left.as_millis() as u64).div_ceil(1000);
+    let time_style = match time_left < ROUND_TIME / 6 {
+        true => Style::default().fg(Color::Red),
+        false => Style::default().fg(Color::White),
+    };
+    let mut lines = vec![
+        Line::styled("Find", heading),
+        Line::styled(target, heading.fg(Color::Cyan)),
+        Line::from(""),
+        Line::styled(format!("{seconds}s left"), time_style),
+        Line::from(""),
+        Line::from(format!("Found {}", coordinates.score())),
+        Line::from(format!("Mistakes {}", coordinates.mistakes())),
+    ];
+    if let Some(missed) = coordinates.missed() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            format!("That was not {missed}"),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+/// The best scores from either side and the latest rounds, along with the outcome of the last one.
+#[inline(always)]
+fn scores<B: Backend>(coordinates: &mut Coordinates<B>, frame: &mut Frame<'_, B>, area: Rect) {
+    let heading = Style::default()
+        .add_modifier(Modifier::BOLD)
+        .fg(Color::White);
+    let side = match coordinates.side() {
+        PieceColor::White => "white",
+        PieceColor::Black => "black",
+    };
+    let mut lines = vec![
+        Line::styled(format!("Seen from {side}"), heading),
+        Line::from(format!(
+            "Find as many squares as you can in {} seconds",
+            ROUND_TIME.as_secs()
+        )),
+        Line::from(""),
+    ];
+    if let Some(notice) = coordinates.notice() {
+        lines.push(Line::styled(
+            notice.to_owned(),
+            Style::default().fg(Color::Yellow),
+        ));
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::styled("Best", heading));
+    for (name, color) in [("White", PieceColor::White), ("Black", PieceColor::Black)] {
+        let best = coordinates
+            .best(color)
+            .map(|best| best.to_string())
+            .unwrap_or_else(|| String::from("-"));
+        lines.push(Line::from(format!("{name:<7}{best}")));
+    }
+    if !coordinates.rounds().is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled("Latest rounds", heading));
+        for round in coordinates.rounds().iter().rev().take(LATEST_ROUNDS) {
+            let side = match round.side {
+                PieceColor::White => "White",
+                PieceColor::Black => "Black",
+            };
+            lines.push(Line::styled(
+                format!(
+                    "{}  {side:<7}{:>3}  ({} missed)",
+                    round.date, round.score, round.mistakes
+                ),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+    let panel = Paragraph::new(lines).wrap(Wrap { trim: true });
+    frame.render_widget(panel, area);
+}
+
+#[inline(always)]
+fn key_hints<B: Backend>(coordinates: &mut Coordinates<B>, frame: &mut Frame<'_, B>) {
+    let text = match coordinates.is_playing() {
+        true => "click the square, or move with the arrows and press Enter · Esc abandon",
+        false => "Enter start · f flip the board · Esc back",
+    };
+    let y_axis = frame.size().height.saturating_sub(2);
+    let area = Rect::new(1, y_axis, frame.size().width.saturating_sub(2), 1);
+    let hints = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(hints, area);
+}
@@ -0,0 +1,41 @@
+use crate::app::AppResult;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::prelude::Backend;
+
+use super::Correspondence;
+
+pub fn handle_key_events<B: Backend>(
+    key_event: KeyEvent,
+    correspondence: &mut Correspondence<'_, B>,
+) -> AppResult<()> {
+    if key_event.code == KeyCode::Char('c') && key_event.modifiers == KeyModifiers::CONTROL {
+        correspondence.quit();
+        return Ok(());
+    }
+    if correspondence.input().is_some() {
+        match key_event.code {
+            KeyCode::Esc => correspondence.cancel_input(),
+            KeyCode::Enter => correspondence.confirm_input()?,
+            KeyCode::Backspace => correspondence.erase_symbol(),
+            KeyCode::Tab => correspondence.switch_side(),
+            KeyCode::Char(symbol) => correspondence.type_symbol(symbol),
+            _ => {}
+        }
+        return Ok(());
+    }
+    if correspondence.is_forgetting() && key_event.code != KeyCode::Char('d') {
+        correspondence.keep();
+        return Ok(());
+    }
+    match key_event.code {
+        KeyCode::Esc | KeyCode::Char('q') => correspondence.quit(),
+        KeyCode::Up => correspondence.move_up(),
+        KeyCode::Down => correspondence.move_down(),
+        KeyCode::Enter => correspondence.open_selected()?,
+        KeyCode::Char('n') => correspondence.start_creating()?,
+        KeyCode::Char('o') => correspondence.start_opening(),
+        KeyCode::Char('d') => correspondence.forget(),
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,526 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{app::AppResult, event::Event, tui::Tui};
+use tui::prelude::Backend;
+
+use self::handler::handle_key_events;
+use super::{
+    game::{piece::PieceColor, Game},
+    setup::{GameOptions, Opponent, Setup},
+    storage, Chess,
+};
+
+mod handler;
+mod ui;
+
+/// The name of the file listing the correspondence games played here and the side played in each,
+/// in the data directory.
+const GAMES_FILE: &str = "correspondence.txt";
+
+/// How often the game file is checked for a move of the opponent.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The name of a side no player has claimed yet.
+const UNCLAIMED: &str = "?";
+
+/// The tags of the game file kept as they are when a move is written, the others following from the game.
+const KEPT_TAGS: [&str; 5] = ["Event", "Date", "White", "Black", "Variant"];
+
+/// A correspondence game kept in a file both players can reach, such as on a network drive or in a synced folder,
+/// which each of them writes their moves to in turn.
+pub struct GameFile {
+    path: PathBuf,
+    /// The side played here.
+    color: PieceColor,
+    /// When the file was last changed, as far as this side knows.
+    modified: Option<SystemTime>,
+    /// When the file was last checked for changes.
+    last_check: Instant,
+}
+
+impl GameFile {
+    /// Starts a new game with the options in a file at the path, playing the side and leaving the other one
+    /// for the opponent to claim.
+    pub fn create(path: &Path, color: PieceColor, options: GameOptions) -> Result<Self, String> {
+        if path.exists() {
+            return Err(format!("{} already exists", path.display()));
+        }
+        let mut tags = options.variant_tags();
+        tags.push(("Event", String::from("Correspondence game")));
+        for side in [PieceColor::White, PieceColor::Black] {
+            let name = match side == color {
+                true => player_name(),
+                false => String::from(UNCLAIMED),
+            };
+            tags.push((side_tag(side), name));
+        }
+        let game = Game::with_board(options.resolve().board());
+        write_atomically(path, &game.to_pgn(&tags, &[])).map_err(|error| error.to_string())?;
+        Self::remember(path, color)
+    }
+
+    /// Opens the game in the file at the path, on the side played here before, or on the side nobody claimed yet.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(color) = known_color(path) {
+            return Ok(Self::new(path, color));
+        }
+        let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let (game, tags) = Game::from_pgn(&text)?;
+        let tag = |name: &str| {
+            tags.iter()
+                .find(|(other, _)| other == name)
+                .map(|(_, value)| value.as_str())
+                .unwrap_or(UNCLAIMED)
+        };
+        let color = [PieceColor::White, PieceColor::Black]
+            .into_iter()
+            .find(|side| tag(side_tag(*side)) == UNCLAIMED)
+            .ok_or_else(|| String::from("both sides of the game are already claimed"))?;
+        let mut file = Self::new(path, color);
+        file.write_with(&game, &tags, &[(side_tag(color), player_name())])
+            .map_err(|error| error.to_string())?;
+        Self::remember(path, color)
+    }
+
+    fn new(path: &Path, color: PieceColor) -> Self {
+        Self {
+            path: path.to_owned(),
+            color,
+            modified: modified(path),
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Adds the game to the games played here, so that it is listed and opened on the same side again.
+    fn remember(path: &Path, color: PieceColor) -> Result<Self, String> {
+        let mut games = known_games();
+        games.retain(|(other, _)| other != path);
+        games.push((path.to_owned(), color));
+        store_games(&games).map_err(|error| error.to_string())?;
+        Ok(Self::new(path, color))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn color(&self) -> PieceColor {
+        self.color
+    }
+
+    /// Reads the game and its tags from the file.
+    pub fn read(&mut self) -> Result<(Game, Vec<(String, String)>), String> {
+        self.modified = modified(&self.path);
+        let text = fs::read_to_string(&self.path).map_err(|error| error.to_string())?;
+        Game::from_pgn(&text)
+    }
+
+    /// Whether the file changed since it was last read or written, checking only every so often.
+    pub fn has_changed(&mut self) -> bool {
+        if self.last_check.elapsed() < CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+        modified(&self.path) != self.modified
+    }
+
+    /// Writes the game to the file, keeping the tags naming the players and the game.
+    pub fn write(&mut self, game: &Game) -> io::Result<()> {
+        let text = fs::read_to_string(&self.path)?;
+        let tags = Game::from_pgn(&text)
+            .map(|(_, tags)| tags)
+            .unwrap_or_default();
+        self.write_with(game, &tags, &[])
+    }
+
+    fn write_with(
+        &mut self,
+        game: &Game,
+        tags: &[(String, String)],
+        changes: &[(&'static str, String)],
+    ) -> io::Result<()> {
+        let mut kept: Vec<(&str, String)> = tags
+            .iter()
+            .filter(|(name, _)| KEPT_TAGS.contains(&name.as_str()))
+            .map(|(name, value)| (name.as_str(), value.clone()))
+            .collect();
+        kept.extend(changes.iter().cloned());
+        write_atomically(&self.path, &game.to_pgn(&kept, &[]))?;
+        self.modified = modified(&self.path);
+        Ok(())
+    }
+}
+
+/// The tag naming the player of the side.
+fn side_tag(color: PieceColor) -> &'static str {
+    match color {
+        PieceColor::White => "White",
+        PieceColor::Black => "Black",
+    }
+}
+
+/// The name the player is known by in their games, which is their user name.
+fn player_name() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| String::from("Player"))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Writes the text to a file next to the path before moving it into place, so that the opponent
+/// never reads a move half written.
+fn write_atomically(path: &Path, text: &str) -> io::Result<()> {
+    let mut name = path.file_name().unwrap_or_default().to_owned();
+    name.push(".tmp");
+    let temporary = path.with_file_name(name);
+    let mut file = fs::File::create(&temporary)?;
+    file.write_all(text.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(temporary, path)
+}
+
+/// The correspondence games played here, with the side played in each.
+fn known_games() -> Vec<(PathBuf, PieceColor)> {
+    let Ok(text) = games_file().and_then(fs::read_to_string) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| {
+            let (color, path) = line.split_once(' ')?;
+            let color = match color {
+                "white" => PieceColor::White,
+                "black" => PieceColor::Black,
+                _ => return None,
+            };
+            Some((PathBuf::from(path), color))
+        })
+        .collect()
+}
+
+fn known_color(path: &Path) -> Option<PieceColor> {
+    known_games()
+        .into_iter()
+        .find(|(other, _)| other == path)
+        .map(|(_, color)| color)
+}
+
+fn store_games(games: &[(PathBuf, PieceColor)]) -> io::Result<()> {
+    let path = games_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text: String = games
+        .iter()
+        .map(|(path, color)| {
+            let color = match color {
+                PieceColor::White => "white",
+                PieceColor::Black => "black",
+            };
+            format!("{color} {}\n", path.display())
+        })
+        .collect();
+    fs::write(path, text)
+}
+
+fn games_file() -> io::Result<PathBuf> {
+    storage::data_dir()
+        .map(|dir| dir.join(GAMES_FILE))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))
+}
+
+/// Turns a typed path into one the file can be found at from anywhere, expanding a leading `~` to the home directory.
+fn expand_path(typed: &str) -> PathBuf {
+    let path = match (typed.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(typed),
+    };
+    match std::env::current_dir() {
+        Ok(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    }
+}
+
+/// A correspondence game as listed on the screen.
+pub struct Listing {
+    pub path: PathBuf,
+    pub color: PieceColor,
+    /// The name of the opponent, or `?` while nobody has claimed their side.
+    pub opponent: String,
+    /// Whose move it is, or how the game ended.
+    pub state: String,
+}
+
+impl Listing {
+    fn new(path: PathBuf, color: PieceColor) -> Self {
+        let (opponent, state) = match fs::read_to_string(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|text| Game::from_pgn(&text))
+        {
+            Ok((game, tags)) => {
+                let opponent = tags
+                    .iter()
+                    .find(|(name, _)| name == side_tag(color.opposite()))
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or_else(|| String::from(UNCLAIMED));
+                let state = match game.status().is_over() {
+                    true => game.result().to_owned(),
+                    false if game.board().turn() == color => String::from("Your move"),
+                    false => String::from("Their move"),
+                };
+                (opponent, state)
+            }
+            Err(_) => (String::from(UNCLAIMED), String::from("Unreadable")),
+        };
+        Self {
+            path,
+            color,
+            opponent,
+            state,
+        }
+    }
+}
+
+/// What the typed path is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathPurpose {
+    /// Starting a game with the options in a new file, playing the side.
+    Create(GameOptions, PieceColor),
+    /// Opening a game someone else started.
+    Open,
+}
+
+/// The screen listing the correspondence games played here, from which they are played, started or opened.
+pub struct Correspondence<'a, B: Backend> {
+    running: bool,
+    tui: Option<&'a mut Tui<B>>,
+    games: Vec<Listing>,
+    /// The index of the highlighted game.
+    selected: usize,
+    /// The path being typed, and what it is for.
+    input: Option<(PathPurpose, String)>,
+    /// Signifies whether the player was asked to confirm forgetting the highlighted game.
+    is_forgetting: bool,
+    /// A message for the player about the last thing they did.
+    notice: Option<String>,
+}
+
+impl<'a, B: Backend> Correspondence<'a, B> {
+    pub fn new(tui: &'a mut Tui<B>) -> Self {
+        let mut correspondence = Self {
+            running: true,
+            tui: Some(tui),
+            games: Vec::new(),
+            selected: 0,
+            input: None,
+            is_forgetting: false,
+            notice: None,
+        };
+        correspondence.refresh();
+        correspondence
+    }
+
+    pub fn launch_correspondence(&mut self) -> AppResult<()> {
+        while self.running {
+            let tui = self.tui.take().expect("should always contain a tui");
+            tui.terminal.draw(|frame| ui::render(self, frame))?;
+            let event = tui.events.next()?;
+            // the tui is put back before handling the keys, as playing a game needs it
+            self.tui = Some(tui);
+            match event {
+                Event::Tick => {}
+                Event::Key(key_event) => handle_key_events(key_event, self)?,
+                Event::Mouse(_) => {}
+                Event::Resize(_, _) => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn quit(&mut self) {
+        self.running = false;
+    }
+
+    pub fn games(&self) -> &[Listing] {
+        &self.games
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn input(&self) -> Option<(PathPurpose, &str)> {
+        self.input
+            .as_ref()
+            .map(|(purpose, text)| (*purpose, text.as_str()))
+    }
+
+    pub fn is_forgetting(&self) -> bool {
+        self.is_forgetting
+    }
+
+    pub fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    /// Reads the games played here anew, to show the latest state of each.
+    fn refresh(&mut self) {
+        self.games = known_games()
+            .into_iter()
+            .map(|(path, color)| Listing::new(path, color))
+            .collect();
+        self.selected = self.selected.min(self.games.len().saturating_sub(1));
+    }
+
+    /// Forgets about the last notice and a pending forget, as the player went on to something else.
+    fn settle(&mut self) {
+        self.notice = None;
+        self.is_forgetting = false;
+    }
+
+    /// Highlights the game directly above the highlighted one - without wrapping around.
+    pub fn move_up(&mut self) {
+        self.settle();
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Highlights the game directly below the highlighted one - without wrapping around.
+    pub fn move_down(&mut self) {
+        self.settle();
+        if self.selected + 1 < self.games.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Picks the options of a new game, then asks for the path of the file to keep it in.
+    pub fn start_creating(&mut self) -> AppResult<()> {
+        self.settle();
+        let tui = self.tui.take().expect("should always contain a tui");
+        let options = Setup::without_players(&mut *tui).launch_setup()?;
+        self.tui = Some(tui);
+        if let Some(options) = options {
+            // there is neither a clock nor a computer in a game played over days
+            let options = GameOptions {
+                time_control: None,
+                opponent: Opponent::Human,
+                ..options
+            };
+            self.input = Some((
+                PathPurpose::Create(options, PieceColor::White),
+                String::new(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Asks for the path of the file of a game someone else started.
+    pub fn start_opening(&mut self) {
+        self.settle();
+        self.input = Some((PathPurpose::Open, String::new()));
+    }
+
+    pub fn type_symbol(&mut self, symbol: char) {
+        if let Some((_, text)) = self.input.as_mut() {
+            text.push(symbol);
+        }
+    }
+
+    pub fn erase_symbol(&mut self) {
+        if let Some((_, text)) = self.input.as_mut() {
+            text.pop();
+        }
+    }
+
+    /// Switches the side played in the new game.
+    pub fn switch_side(&mut self) {
+        if let Some((PathPurpose::Create(_, color), _)) = self.input.as_mut() {
+            *color = color.opposite();
+        }
+    }
+
+    pub fn cancel_input(&mut self) {
+        self.input = None;
+    }
+
+    /// Creates or opens the game at the typed path, and plays it right away.
+    pub fn confirm_input(&mut self) -> AppResult<()> {
+        let Some((purpose, text)) = self.input.take() else {
+            return Ok(());
+        };
+        let path = expand_path(text.trim());
+        let file = match purpose {
+            PathPurpose::Create(options, color) => GameFile::create(&path, color, options),
+            PathPurpose::Open => GameFile::open(&path),
+        };
+        match file {
+            Ok(file) => self.play(file)?,
+            Err(error) => self.notice = Some(format!("Could not open the game: {error}")),
+        }
+        self.refresh();
+        Ok(())
+    }
+
+    /// Plays the highlighted game.
+    pub fn open_selected(&mut self) -> AppResult<()> {
+        self.settle();
+        let Some(listing) = self.games.get(self.selected) else {
+            return Ok(());
+        };
+        let file = GameFile::new(&listing.path, listing.color);
+        self.play(file)?;
+        self.refresh();
+        Ok(())
+    }
+
+    fn play(&mut self, file: GameFile) -> AppResult<()> {
+        let tui = self.tui.take().expect("should always contain a tui");
+        match Chess::correspondence(&mut *tui, file) {
+            Ok(mut chess) => chess.launch_chess()?,
+            Err(error) => self.notice = Some(format!("Could not read the game: {error}")),
+        }
+        self.tui = Some(tui);
+        Ok(())
+    }
+
+    /// Keeps the highlighted game listed after all.
+    pub fn keep(&mut self) {
+        self.is_forgetting = false;
+    }
+
+    /// Asks the player to confirm forgetting the highlighted game, and stops listing it once they have.
+    /// The game file itself is left alone, as the opponent may still need it.
+    pub fn forget(&mut self) {
+        if self.games.get(self.selected).is_none() {
+            return;
+        }
+        if !self.is_forgetting {
+            self.notice = None;
+            self.is_forgetting = true;
+            return;
+        }
+        self.is_forgetting = false;
+        let listing = self.games.remove(self.selected);
+        let games: Vec<(PathBuf, PieceColor)> = self
+            .games
+            .iter()
+            .map(|listing| (listing.path.clone(), listing.color))
+            .collect();
+        self.notice = Some(match store_games(&games) {
+            Ok(()) => String::from("Game forgotten, its file is left as it is"),
+            Err(error) => {
+                self.games.insert(self.selected, listing);
+                format!("Could not forget the game: {error}")
+            }
+        });
+        self.selected = self.selected.min(self.games.len().saturating_sub(1));
+    }
+}
@@ -0,0 +1,131 @@
+use super::{Correspondence, PathPurpose};
+use crate::chess::game::piece::PieceColor;
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint},
+    prelude::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+/// The widths of the columns of the list of games, the file taking up whatever is left.
+const COLUMN_WIDTHS: [Constraint; 4] = [
+    Constraint::Length(6),
+    Constraint::Length(14),
+    Constraint::Length(10),
+    Constraint::Min(20),
+];
+
+/// Renders the user interface widgets.
+pub fn render<B: Backend>(correspondence: &mut Correspondence<B>, frame: &mut Frame<'_, B>) {
+    outer_block(frame);
+    status(correspondence, frame);
+    games(correspondence, frame);
+    key_hints(correspondence, frame);
+}
+
+#[inline(always)]
+fn outer_block<B: Backend>(frame: &mut Frame<'_, B>) {
+    let outer_block = Block::default()
+        .title("Correspondence Chess")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    frame.render_widget(outer_block, frame.size());
+}
+
+/// The path being typed, or the last notice.
+#[inline(always)]
+fn status<B: Backend>(correspondence: &mut Correspondence<B>, frame: &mut Frame<'_, B>) {
+    let text = match (correspondence.input(), correspondence.notice()) {
+        (Some((PathPurpose::Create(options, color), path)), _) => format!(
+            "New {} game as {} in file: {path}_",
+            options.name(),
+            side(color)
+        ),
+        (Some((PathPurpose::Open, path)), _) => format!("Open the game in file: {path}_"),
+        (None, _) if correspondence.is_forgetting() => {
+            String::from("Press d again to stop listing the game, or any other key to keep it")
+        }
+        (None, Some(notice)) => notice.to_owned(),
+        (None, None) => String::from(
+            "Each move is written to the game file, which your opponent reads from a shared folder",
+        ),
+    };
+    let style = match correspondence.is_forgetting() {
+        true => Style::default().fg(Color::Red),
+        false => Style::default().fg(Color::White),
+    };
+    let area = Rect::new(2, 1, frame.size().width.saturating_sub(4), 1);
+    frame.render_widget(Paragraph::new(text).style(style), area);
+}
+
+#[inline(always)]
+fn games<B: Backend>(correspondence: &mut Correspondence<B>, frame: &mut Frame<'_, B>) {
+    let area = Rect::new(
+        2,
+        3,
+        frame.size().width.saturating_sub(4),
+        frame.size().height.saturating_sub(6),
+    );
+    if correspondence.games().is_empty() {
+        let empty = Paragraph::new("Start a game with n, or open one someone else started with o")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let header = Row::new(["Side", "Opponent", "State", "File"]).style(
+        Style::default()
+            .add_modifier(Modifier::BOLD)
+            .fg(Color::White),
+    );
+    let rows: Vec<Row> = correspondence
+        .games()
+        .iter()
+        .map(|game| {
+            Row::new([
+                side(game.color).to_owned(),
+                game.opponent.clone(),
+                game.state.clone(),
+                game.path.display().to_string(),
+            ])
+        })
+        .collect();
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&COLUMN_WIDTHS)
+        .column_spacing(2)
+        .style(Style::default().fg(Color::DarkGray))
+        .highlight_style(Style::default().fg(Color::Cyan));
+
+    let mut state = TableState::default();
+    state.select(Some(correspondence.selected()));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+#[inline(always)]
+fn key_hints<B: Backend>(correspondence: &mut Correspondence<B>, frame: &mut Frame<'_, B>) {
+    let text = match correspondence.input() {
+        Some((PathPurpose::Create(..), _)) => {
+            "type the path · Tab switch side · Enter start · Esc cancel"
+        }
+        Some((PathPurpose::Open, _)) => "type the path · Enter open · Esc cancel",
+        None => "Enter play · n new game · o open a game file · d forget · Esc back",
+    };
+    let y_axis = frame.size().height.saturating_sub(2);
+    let area = Rect::new(1, y_axis, frame.size().width.saturating_sub(2), 1);
+    let hints = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(hints, area);
+}
+
+fn side(color: PieceColor) -> &'static str {
+    match color {
+        PieceColor::White => "White",
+        PieceColor::Black => "Black",
+    }
+}
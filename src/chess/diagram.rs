@@ -0,0 +1,285 @@
+use std::{
+    fs, io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tui::style::Color;
+
+use super::{
+    game::{
+        board::{Board, Position, CHESS_BOARD_SIZE},
+        board_widget::{DARK_LAST_MOVE, DARK_SQUARE, LIGHT_LAST_MOVE, LIGHT_SQUARE},
+        chess_move::Move,
+        piece::{PieceColor, PieceSet},
+    },
+    storage,
+};
+
+/// The name of the directory the diagrams are exported to, in the data directory.
+const EXPORT_DIRECTORY: &str = "diagrams";
+
+/// The extensions of the SVG, plain text and ANSI text files a diagram is exported as.
+const EXTENSIONS: [&str; 3] = ["svg", "txt", "ans"];
+
+/// The width and height of a square of the SVG diagram, in pixels.
+const SQUARE_SIZE: usize = 45;
+
+/// The space around the squares of the SVG diagram, holding the rank and file labels, in pixels.
+const MARGIN: usize = 20;
+
+/// The color of the arrows of the SVG diagram.
+const ARROW_COLOR: &str = "#15781b";
+
+/// The width of the shaft of an arrow of the SVG diagram, in pixels.
+const ARROW_WIDTH: usize = 9;
+
+/// The length of the head of an arrow, in multiples of its width, so that its tip lands in the middle of the square.
+const ARROW_HEAD_LENGTH: usize = 3;
+
+/// A position drawn for a document rather than the terminal, seen from the side of white,
+/// with the squares of the last move marked and arrows drawn between squares.
+pub struct Diagram<'a> {
+    board: &'a Board,
+    last_move: Option<Move>,
+    /// The arrows, from one square to the other, such as for the best move.
+    arrows: Vec<(Position, Position)>,
+}
+
+impl<'a> Diagram<'a> {
+    pub fn new(
+        board: &'a Board,
+        last_move: Option<Move>,
+        arrows: Vec<(Position, Position)>,
+    ) -> Self {
+        Self {
+            board,
+            last_move,
+            arrows,
+        }
+    }
+
+    /// Whether the square is one the last move was played from or to.
+    fn is_last_move(&self, position: Position) -> bool {
+        self.last_move
+            .is_some_and(|mv| mv.from == position || mv.to == position)
+    }
+
+    /// The color of the square, as on the board in the terminal.
+    fn square_color(&self, position: Position) -> Color {
+        match (position.is_light(), self.is_last_move(position)) {
+            (true, true) => LIGHT_LAST_MOVE,
+            (false, true) => DARK_LAST_MOVE,
+            (true, false) => LIGHT_SQUARE,
+            (false, false) => DARK_SQUARE,
+        }
+    }
+
+    /// The ranks from the top of the diagram to the bottom, each with its squares from left to right.
+    fn rows() -> impl Iterator<Item = (u8, impl Iterator<Item = Position>)> {
+        (0..CHESS_BOARD_SIZE as u8).rev().map(|rank| {
+            (
+                rank,
+                (0..CHESS_BOARD_SIZE as u8).map(move |file| Position::new(file, rank)),
+            )
+        })
+    }
+
+    /// The side to move, the last move, the arrows and the FEN, written below the text diagrams.
+    fn caption(&self) -> String {
+        let mut lines = vec![match self.board.turn() {
+            PieceColor::White => String::from("White to move"),
+            PieceColor::Black => String::from("Black to move"),
+        }];
+        if let Some(mv) = self.last_move {
+            lines.push(format!("Last move: {}-{}", mv.from, mv.to));
+        }
+        if !self.arrows.is_empty() {
+            let arrows: Vec<String> = self
+                .arrows
+                .iter()
+                .map(|(from, to)| format!("{from}-{to}"))
+                .collect();
+            lines.push(format!("Arrows: {}", arrows.join(", ")));
+        }
+        lines.push(format!("FEN: {}", self.board.to_fen()));
+        lines.join("\n")
+    }
+
+    /// The diagram as a scalable vector graphic, drawing the pieces with their Unicode symbols.
+    pub fn to_svg(&self) -> String {
+        let size = CHESS_BOARD_SIZE * SQUARE_SIZE + 2 * MARGIN;
+        let mut svg = vec![
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}" font-family="sans-serif">"#
+            ),
+            format!(
+                r#"<defs><marker id="arrowhead" markerWidth="{ARROW_HEAD_LENGTH}" markerHeight="{ARROW_HEAD_LENGTH}" refX="0" refY="{}" orient="auto"><path d="M0,0 L{ARROW_HEAD_LENGTH},{} L0,{ARROW_HEAD_LENGTH} z" fill="{ARROW_COLOR}"/></marker></defs>"#,
+                ARROW_HEAD_LENGTH as f64 / 2.0,
+                ARROW_HEAD_LENGTH as f64 / 2.0
+            ),
+            format!(r##"<rect width="{size}" height="{size}" fill="#ffffff"/>"##),
+        ];
+        for (row, (rank, squares)) in Self::rows().enumerate() {
+            let y = MARGIN + row * SQUARE_SIZE;
+            svg.push(format!(
+                r#"<text x="{}" y="{}" font-size="12" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                MARGIN / 2,
+                y + SQUARE_SIZE / 2,
+                rank + 1
+            ));
+            for position in squares {
+                let x = MARGIN + position.file() as usize * SQUARE_SIZE;
+                svg.push(format!(
+                    r#"<rect x="{x}" y="{y}" width="{SQUARE_SIZE}" height="{SQUARE_SIZE}" fill="{}"/>"#,
+                    hex(self.square_color(position))
+                ));
+                if let Some(piece) = self.board.piece_at(position) {
+                    // both sides use the filled symbols, the white ones outlined in black so they stand out
+                    let paint = match piece.color() {
+                        PieceColor::White => {
+                            r##"fill="#ffffff" stroke="#000000" stroke-width="1""##
+                        }
+                        PieceColor::Black => r##"fill="#000000""##,
+                    };
+                    svg.push(format!(
+                        r#"<text x="{}" y="{}" font-size="{}" text-anchor="middle" dominant-baseline="central" {paint}>{}</text>"#,
+                        x + SQUARE_SIZE / 2,
+                        y + SQUARE_SIZE / 2,
+                        SQUARE_SIZE * 4 / 5,
+                        PieceSet::UnicodeFilled.board_symbol(piece)
+                    ));
+                }
+            }
+        }
+        for (file, letter) in ('a'..='h').enumerate() {
+            svg.push(format!(
+                r#"<text x="{}" y="{}" font-size="12" text-anchor="middle" dominant-baseline="central">{letter}</text>"#,
+                MARGIN + file * SQUARE_SIZE + SQUARE_SIZE / 2,
+                size - MARGIN / 2
+            ));
+        }
+        for (from, to) in &self.arrows {
+            let center = |position: &Position| {
+                let x = MARGIN + position.file() as usize * SQUARE_SIZE + SQUARE_SIZE / 2;
+                let y = MARGIN
+                    + (CHESS_BOARD_SIZE - 1 - position.rank() as usize) * SQUARE_SIZE
+                    + SQUARE_SIZE / 2;
+                (x as f64, y as f64)
+            };
+            let ((x1, y1), (x2, y2)) = (center(from), center(to));
+            // the shaft stops short of the middle of the square, where the tip of the head lands
+            let length = (x2 - x1).hypot(y2 - y1);
+            let head = (ARROW_WIDTH * ARROW_HEAD_LENGTH) as f64;
+            if length <= head {
+                continue;
+            }
+            let (x2, y2) = (
+                x2 - (x2 - x1) * head / length,
+                y2 - (y2 - y1) * head / length,
+            );
+            svg.push(format!(
+                r#"<line x1="{x1:.1}" y1="{y1:.1}" x2="{x2:.1}" y2="{y2:.1}" stroke="{ARROW_COLOR}" stroke-width="{ARROW_WIDTH}" stroke-opacity="0.8" marker-end="url(#arrowhead)"/>"#
+            ));
+        }
+        svg.push(String::from("</svg>"));
+        svg.join("\n") + "\n"
+    }
+
+    /// The diagram in plain text, with the pieces as the letters of FEN, the empty squares as dots,
+    /// and the squares of the last move in brackets.
+    pub fn to_text(&self) -> String {
+        let border = format!("  +{}+", "-".repeat(3 * CHESS_BOARD_SIZE));
+        let mut lines = vec![border.clone()];
+        for (rank, squares) in Self::rows() {
+            let squares: String = squares
+                .map(|position| {
+                    let symbol = self
+                        .board
+                        .piece_at(position)
+                        .map(|piece| PieceSet::Ascii.text_symbol(piece))
+                        .unwrap_or(".");
+                    match self.is_last_move(position) {
+                        true => format!("[{symbol}]"),
+                        false => format!(" {symbol} "),
+                    }
+                })
+                .collect();
+            lines.push(format!("{} |{squares}|", rank + 1));
+        }
+        lines.push(border);
+        let letters: String = ('a'..='h').map(|letter| format!(" {letter} ")).collect();
+        lines.push(format!("   {}", letters.trim_end()));
+        lines.push(String::new());
+        lines.push(self.caption());
+        lines.join("\n") + "\n"
+    }
+
+    /// The diagram as text colored with ANSI escape codes, looking as the board does in the terminal.
+    pub fn to_ansi(&self) -> String {
+        let mut lines = Vec::new();
+        for (rank, squares) in Self::rows() {
+            let squares: String = squares
+                .map(|position| {
+                    let (symbol, foreground) = match self.board.piece_at(position) {
+                        Some(piece) => (
+                            PieceSet::UnicodeFilled.board_symbol(piece),
+                            match piece.color() {
+                                PieceColor::White => "97",
+                                PieceColor::Black => "30",
+                            },
+                        ),
+                        None => (" ", "30"),
+                    };
+                    let background = match self.square_color(position) {
+                        Color::Rgb(red, green, blue) => format!("48;2;{red};{green};{blue}"),
+                        _ => String::from("49"),
+                    };
+                    format!("\x1b[1;{foreground};{background}m {symbol} \x1b[0m")
+                })
+                .collect();
+            lines.push(format!("{} {squares}", rank + 1));
+        }
+        let letters: String = ('a'..='h').map(|letter| format!(" {letter} ")).collect();
+        lines.push(format!("  {}", letters.trim_end()));
+        lines.push(String::new());
+        lines.push(self.caption());
+        lines.join("\n") + "\n"
+    }
+
+    /// Writes the diagram to the data directory as an SVG, a plain text and an ANSI text file of the same name,
+    /// returning the path they share, without the extension.
+    pub fn export(&self) -> io::Result<PathBuf> {
+        let dir = storage::data_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?
+            .join(EXPORT_DIRECTORY);
+        fs::create_dir_all(&dir)?;
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis())
+            .unwrap_or_default();
+        // a diagram exported within the same millisecond as another one is numbered rather than overwriting it
+        let mut path = dir.join(format!("diagram-{millis}"));
+        let mut number = 1;
+        while EXTENSIONS
+            .iter()
+            .any(|extension| path.with_extension(extension).exists())
+        {
+            number += 1;
+            path = dir.join(format!("diagram-{millis}-{number}"));
+        }
+        let contents = [self.to_svg(), self.to_text(), self.to_ansi()];
+        for (extension, contents) in EXTENSIONS.into_iter().zip(contents) {
+            fs::write(path.with_extension(extension), contents)?;
+        }
+        Ok(path)
+    }
+}
+
+/// The color in the hexadecimal notation of SVG.
+fn hex(color: Color) -> String {
+    match color {
+        Color::Rgb(red, green, blue) => format!("#{red:02x}{green:02x}{blue:02x}"),
+        _ => String::from("#000000"),
+    }
+}
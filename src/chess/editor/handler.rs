@@ -0,0 +1,49 @@
+use crate::app::AppResult;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::prelude::Backend;
+
+use super::Editor;
+use crate::chess::game::{
+    chess_move::CastleSide,
+    piece::{Piece, PieceColor, PieceType},
+};
+
+pub fn handle_key_events<B: Backend>(
+    key_event: KeyEvent,
+    editor: &mut Editor<'_, B>,
+) -> AppResult<()> {
+    // pieces are typed as in FEN, white in uppercase and black in lowercase
+    if let KeyCode::Char(letter) = key_event.code {
+        if let Some(variant) = PieceType::from_letter(letter) {
+            let color = match letter.is_ascii_uppercase() {
+                true => PieceColor::White,
+                false => PieceColor::Black,
+            };
+            editor.place(Piece::new(color, variant));
+            return Ok(());
+        }
+    }
+    match key_event.code {
+        // the letters of the pieces are taken, so only escape goes back
+        KeyCode::Esc => editor.quit(),
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            editor.quit();
+        }
+        KeyCode::Up => editor.move_cursor(0, 1),
+        KeyCode::Down => editor.move_cursor(0, -1),
+        KeyCode::Left => editor.move_cursor(-1, 0),
+        KeyCode::Right => editor.move_cursor(1, 0),
+        KeyCode::Char(' ') | KeyCode::Backspace | KeyCode::Delete => editor.remove(),
+        KeyCode::Tab => editor.switch_turn(),
+        KeyCode::Char('1') => editor.toggle_castling(PieceColor::White, CastleSide::King),
+        KeyCode::Char('2') => editor.toggle_castling(PieceColor::White, CastleSide::Queen),
+        KeyCode::Char('3') => editor.toggle_castling(PieceColor::Black, CastleSide::King),
+        KeyCode::Char('4') => editor.toggle_castling(PieceColor::Black, CastleSide::Queen),
+        KeyCode::Char('c') => editor.clear(),
+        KeyCode::Char('s') => editor.reset(),
+        KeyCode::Enter => editor.play()?,
+        KeyCode::Char('a') => editor.analyze()?,
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use crate::{app::AppResult, event::Event, tui::Tui};
+use tui::prelude::Backend;
+
+use self::handler::handle_key_events;
+use super::{
+    game::{
+        board::{Board, Position},
+        chess_move::CastleSide,
+        piece::{Piece, PieceColor, PieceType},
+    },
+    setup::{GameOptions, Setup, StartingPosition},
+    Chess,
+};
+
+mod handler;
+mod ui;
+
+/// The screen where a position is set up piece by piece, to start a game or an analysis from.
+pub struct Editor<'a, B: Backend> {
+    running: bool,
+    tui: Option<&'a mut Tui<B>>,
+    board: Board,
+    /// The square currently highlighted for keyboard input.
+    cursor: Position,
+    /// A message for the player about the last thing they did.
+    notice: Option<String>,
+}
+
+impl<'a, B: Backend> Editor<'a, B> {
+    /// Constructs an editor starting out from the standard starting position.
+    pub fn new(tui: &'a mut Tui<B>) -> Self {
+        Self {
+            running: true,
+            tui: Some(tui),
+            board: Board::new(),
+            cursor: Position::new(4, 1),
+            notice: None,
+        }
+    }
+
+    pub fn launch_editor(&mut self) -> AppResult<()> {
+        while self.running {
+            let tui = self.tui.take().expect("should always contain a tui");
+            tui.terminal.draw(|frame| ui::render(self, frame))?;
+            let event = tui.events.next()?;
+            // the tui is put back before handling the keys, as starting the game needs it
+            self.tui = Some(tui);
+            match event {
+                Event::Tick => {}
+                Event::Key(key_event) => handle_key_events(key_event, self)?,
+                Event::Mouse(_) => {}
+                Event::Resize(_, _) => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn quit(&mut self) {
+        self.running = false;
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn cursor(&self) -> Position {
+        self.cursor
+    }
+
+    pub fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    /// Moves the cursor by the given number of files and ranks, staying on the board.
+    pub fn move_cursor(&mut self, file: i8, rank: i8) {
+        if let Some(position) = self.cursor.offset(file, rank) {
+            self.cursor = position;
+        }
+    }
+
+    /// Puts the piece on the square under the cursor, replacing whatever stood there.
+    pub fn place(&mut self, piece: Piece) {
+        self.edit(|board, cursor| board.set_piece(cursor, Some(piece)));
+    }
+
+    /// Takes the piece off the square under the cursor.
+    pub fn remove(&mut self) {
+        self.edit(|board, cursor| board.set_piece(cursor, None));
+    }
+
+    pub fn switch_turn(&mut self) {
+        self.edit(|board, _| board.set_turn(board.turn().opposite()));
+    }
+
+    /// Takes every piece off the board.
+    pub fn clear(&mut self) {
+        self.edit(|board, _| {
+            *board = Board::empty();
+        });
+    }
+
+    /// Sets the pieces up as at the start of a standard game.
+    pub fn reset(&mut self) {
+        self.edit(|board, _| *board = Board::new());
+    }
+
+    /// Gives or takes away the right of the side to castle to the given side, which it can only have
+    /// while its king and a rook on that side of it are on the back rank.
+    pub fn toggle_castling(&mut self, color: PieceColor, side: CastleSide) {
+        self.notice = None;
+        let mut castling = self.board.castling();
+        let rook_file = match castling.get(color, side) {
+            Some(_) => None,
+            None => match self.board.outermost_rook(color, side) {
+                Some(file) => Some(file),
+                None => {
+                    self.notice = Some(format!(
+                        "{} can only castle there with its king and a rook on its back rank",
+                        color_name(color)
+                    ));
+                    return;
+                }
+            },
+        };
+        castling.set(color, side, rook_file);
+        self.board.set_castling(castling);
+    }
+
+    /// Changes the board, then takes away the castling rights the king or the rook is no longer there for.
+    /// An edited position has no en passant square and starts at the first move.
+    fn edit(&mut self, change: impl FnOnce(&mut Board, Position)) {
+        self.notice = None;
+        change(&mut self.board, self.cursor);
+        let mut castling = self.board.castling();
+        for color in [PieceColor::White, PieceColor::Black] {
+            for side in [CastleSide::King, CastleSide::Queen] {
+                if castling.get(color, side).is_some()
+                    && castling.get(color, side) != self.board.outermost_rook(color, side)
+                {
+                    castling.set(color, side, None);
+                }
+            }
+        }
+        self.board.set_castling(castling);
+        self.board.set_en_passant(None);
+        self.board.set_move_counters(0, 1);
+    }
+
+    /// Sets up a game from the position, and plays it, as long as the position is legal.
+    pub fn play(&mut self) -> AppResult<()> {
+        if !self.check() {
+            return Ok(());
+        }
+        let tui = self.tui.take().expect("should always contain a tui");
+        let options = Setup::with_position(&mut *tui, self.board).launch_setup()?;
+        if let Some(options) = options {
+            Chess::new(&mut *tui, options).launch_chess()?;
+        }
+        self.tui = Some(tui);
+        Ok(())
+    }
+
+    /// Explores the position with the engine in analysis mode, as long as the position is legal.
+    pub fn analyze(&mut self) -> AppResult<()> {
+        if !self.check() {
+            return Ok(());
+        }
+        let options = GameOptions {
+            start: StartingPosition::Custom(self.board),
+            ..GameOptions::default()
+        };
+        let tui = self.tui.take().expect("should always contain a tui");
+        let mut chess = Chess::new(&mut *tui, options);
+        chess.toggle_analysis();
+        chess.launch_chess()?;
+        self.tui = Some(tui);
+        Ok(())
+    }
+
+    /// Whether the position is legal, telling the player what to fix otherwise.
+    fn check(&mut self) -> bool {
+        match problem(&self.board) {
+            Some(problem) => {
+                self.notice = Some(problem);
+                false
+            }
+            None => true,
+        }
+    }
+}
+
+/// What makes the position illegal, if anything: each side needs exactly one king, the side not to move
+/// cannot be in check, and pawns cannot stand on the first or the last rank.
+pub fn problem(board: &Board) -> Option<String> {
+    for color in [PieceColor::White, PieceColor::Black] {
+        let king = Piece::new(color, PieceType::King);
+        match board.pieces().filter(|(_, piece)| *piece == king).count() {
+            0 => return Some(format!("{} has no king", color_name(color))),
+            1 => {}
+            _ => return Some(format!("{} has more than one king", color_name(color))),
+        }
+    }
+    let waiting = board.turn().opposite();
+    if board
+        .king_position(waiting)
+        .is_some_and(|king| board.is_attacked(king, board.turn()))
+    {
+        return Some(format!(
+            "{} is in check, but it is {} to move",
+            color_name(waiting),
+            color_name(board.turn()).to_lowercase()
+        ));
+    }
+    let has_stray_pawn = board.pieces().any(|(position, piece)| {
+        piece.variant() == PieceType::Pawn && (position.rank() == 0 || position.rank() == 7)
+    });
+    if has_stray_pawn {
+        return Some(String::from(
+            "Pawns cannot stand on the first or the last rank",
+        ));
+    }
+    None
+}
+
+fn color_name(color: PieceColor) -> &'static str {
+    match color {
+        PieceColor::White => "White",
+        PieceColor::Black => "Black",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem_of(fen: &str) -> Option<String> {
+        problem(&Board::from_fen(fen).unwrap())
+    }
+
+    #[test]
+    fn accepts_a_position_that_can_be_played() {
+        assert_eq!(problem(&Board::new()), None);
+        assert_eq!(problem_of("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"), None);
+        // the side to move may well be in check
+        assert_eq!(problem_of("4k3/8/8/8/8/8/8/R3K2r w - - 0 1"), None);
+    }
+
+    #[test]
+    fn tells_what_makes_the_position_illegal() {
+        assert_eq!(
+            problem_of("8/8/8/8/8/8/4P3/4K3 w - - 0 1").as_deref(),
+            Some("Black has no king")
+        );
+        assert_eq!(
+            problem_of("4k3/8/8/8/8/8/8/2K1K3 w - - 0 1").as_deref(),
+            Some("White has more than one king")
+        );
+        assert_eq!(
+            problem_of("4k3/8/8/8/8/8/8/R3K2r b - - 0 1").as_deref(),
+            Some("White is in check, but it is black to move")
+        );
+        assert_eq!(
+            problem_of("4k2P/8/8/8/8/8/8/4K3 w - - 0 1").as_deref(),
+            Some("Pawns cannot stand on the first or the last rank")
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use super::{color_name, problem, Editor};
+use crate::chess::game::{
+    board_widget::{BoardScale, BoardWidget},
+    chess_move::CastleSide,
+    piece::PieceColor,
+};
+use tui::{
+    backend::Backend,
+    layout::Alignment,
+    prelude::Rect,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+/// The width of the panel beside the board, showing the side to move, the castling rights and whether the position is legal.
+const PANEL_WIDTH: u16 = 36;
+
+/// The gap between the board and the panel.
+const PANEL_GAP: u16 = 4;
+
+/// The rows below the board: a gap, the FEN of the position and a gap, as well as two rows of key hints.
+const ROWS_BELOW_BOARD: u16 = 6;
+
+/// The castling rights in the order they are toggled with the number keys.
+const CASTLING: [(PieceColor, CastleSide, &str); 4] = [
+    (PieceColor::White, CastleSide::King, "O-O"),
+    (PieceColor::White, CastleSide::Queen, "O-O-O"),
+    (PieceColor::Black, CastleSide::King, "O-O"),
+    (PieceColor::Black, CastleSide::Queen, "O-O-O"),
+];
+
+/// Renders the user interface widgets.
+pub fn render<B: Backend>(editor: &mut Editor<B>, frame: &mut Frame<'_, B>) {
+    outer_block(frame);
+    let size = frame.size();
+    // the largest board that fits beside the panel, or the smallest one if none does
+    let scale = BoardScale::LARGEST_FIRST
+        .into_iter()
+        .find(|scale| {
+            scale.width() + PANEL_GAP + PANEL_WIDTH + 2 <= size.width
+                && scale.height() + ROWS_BELOW_BOARD + 2 <= size.height
+        })
+        .unwrap_or(BoardScale::Compact);
+    let total_width = scale.width() + PANEL_GAP + PANEL_WIDTH;
+    let x = size.width.saturating_sub(total_width) / 2;
+    let y = size
+        .height
+        .saturating_sub(scale.height() + ROWS_BELOW_BOARD)
+        / 2;
+    let board_area = Rect::new(x, y, scale.width(), scale.height()).intersection(size);
+    let panel_area = Rect::new(
+        x + scale.width() + PANEL_GAP,
+        y,
+        PANEL_WIDTH,
+        scale.height(),
+    )
+    .intersection(size);
+
+    board(editor, frame, board_area, scale);
+    panel(editor, frame, panel_area);
+    fen(editor, frame, board_area.bottom() + 1);
+    key_hints(frame);
+}
+
+#[inline(always)]
+fn outer_block<B: Backend>(frame: &mut Frame<'_, B>) {
+    let outer_block = Block::default()
+        .title("Board Editor")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    frame.render_widget(outer_block, frame.size());
+}
+
+#[inline(always)]
+fn board<B: Backend>(
+    editor: &mut Editor<B>,
+    frame: &mut Frame<'_, B>,
+    area: Rect,
+    scale: BoardScale,
+) {
+    let board = BoardWidget::new(editor.board())
+        .cursor(editor.cursor())
+        .scale(scale);
+    frame.render_widget(board, area);
+}
+
+/// The side to move, the castling rights, and whether the position can be played.
+#[inline(always)]
+fn panel<B: Backend>(editor: &mut Editor<B>, frame: &mut Frame<'_, B>, area: Rect) {
+    let board = editor.board();
+    let heading = Style::default()
+        .add_modifier(Modifier::BOLD)
+        .fg(Color::White);
+    let mut lines = vec![
+        Line::styled(format!("{} to move", color_name(board.turn())), heading),
+        Line::from(""),
+        Line::styled("Castling", heading),
+    ];
+    for (index, (color, side, name)) in CASTLING.into_iter().enumerate() {
+        let (mark, style) = match board.castling().get(color, side) {
+            Some(_) => ("✓", Style::default().fg(Color::Cyan)),
+            None => ("·", Style::default().fg(Color::DarkGray)),
+        };
+        let text = format!("{} {mark} {} {name}", index + 1, color_name(color));
+        lines.push(Line::styled(text, style));
+    }
+    lines.push(Line::from(""));
+    lines.push(match problem(board) {
+        Some(problem) => Line::styled(problem, Style::default().fg(Color::Red)),
+        None => Line::styled("Legal position", Style::default().fg(Color::Green)),
+    });
+    if let Some(notice) = editor.notice() {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            notice.to_owned(),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    let panel = Paragraph::new(lines).wrap(Wrap { trim: true });
+    frame.render_widget(panel, area);
+}
+
+#[inline(always)]
+fn fen<B: Backend>(editor: &mut Editor<B>, frame: &mut Frame<'_, B>, y_axis: u16) {
+    let area =
+        Rect::new(1, y_axis, frame.size().width.saturating_sub(2), 1).intersection(frame.size());
+    let fen = Paragraph::new(editor.board().to_fen())
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(fen, area);
+}
+
+#[inline(always)]
+fn key_hints<B: Backend>(frame: &mut Frame<'_, B>) {
+    let lines = vec![
+        Line::from("KQRBNP white · kqrbnp black · Space remove · Tab side to move · 1-4 castling"),
+        Line::from("c clear · s start position · Enter play · a analyze · Esc back"),
+    ];
+    let y_axis = frame.size().height.saturating_sub(3);
+    let area = Rect::new(1, y_axis, frame.size().width.saturating_sub(2), 2);
+    let hints = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(hints, area);
+}
@@ -0,0 +1,110 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use self::{
+    search::{search, SearchInfo, SearchLimits},
+    tablebase::Verdict,
+};
+use super::game::{chess_move::Move, Game};
+
+pub mod eval;
+pub mod search;
+pub mod tablebase;
+
+/// A search of a position running on a background thread until it is dropped, keeping the latest completed depth.
+///
+/// A position the endgame tables cover is looked up in them instead, on the same thread, as they know better.
+pub struct Analysis {
+    stop: Arc<AtomicBool>,
+    latest: Arc<Mutex<Option<SearchInfo>>>,
+    tablebase: Arc<Mutex<Option<Verdict>>>,
+    /// Whether the search has come to an end by itself, having reached its limits.
+    finished: Arc<AtomicBool>,
+}
+
+impl Analysis {
+    /// Starts analyzing the current position of the game, until stopped.
+    pub fn start(game: &Game) -> Self {
+        Self::with_limits(game, SearchLimits::infinite())
+    }
+
+    /// Starts searching the current position of the game within the limits.
+    pub fn with_limits(game: &Game, limits: SearchLimits) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let latest = Arc::new(Mutex::new(None));
+        let tablebase = Arc::new(Mutex::new(None));
+        let finished = Arc::new(AtomicBool::new(false));
+        let board = *game.board();
+        let history = history_keys(game);
+
+        let (thread_stop, thread_latest, thread_tablebase, thread_finished) = (
+            Arc::clone(&stop),
+            Arc::clone(&latest),
+            Arc::clone(&tablebase),
+            Arc::clone(&finished),
+        );
+        thread::spawn(move || {
+            let verdict = tablebase::shared().and_then(|tablebase| tablebase.verdict(&board));
+            if verdict.is_some() {
+                if let Ok(mut tablebase) = thread_tablebase.lock() {
+                    *tablebase = verdict;
+                }
+                thread_finished.store(true, Ordering::Relaxed);
+                return;
+            }
+            search(&board, &history, limits, &thread_stop, |info| {
+                if let Ok(mut latest) = thread_latest.lock() {
+                    *latest = Some(info.clone());
+                }
+            });
+            thread_finished.store(true, Ordering::Relaxed);
+        });
+        Self {
+            stop,
+            latest,
+            tablebase,
+            finished,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    /// The result of the deepest depth completed so far, if any.
+    pub fn latest(&self) -> Option<SearchInfo> {
+        self.latest.lock().ok().and_then(|latest| latest.clone())
+    }
+
+    /// What the endgame tables tell about the position, once they have been looked at and if they cover it.
+    pub fn tablebase(&self) -> Option<Verdict> {
+        self.tablebase.lock().ok().and_then(|tablebase| *tablebase)
+    }
+
+    /// The best move found so far, from the endgame tables or else from the deepest depth searched.
+    pub fn best_move(&self) -> Option<Move> {
+        self.tablebase()
+            .map(|verdict| verdict.best_move)
+            .or_else(|| self.latest()?.pv.first().copied())
+    }
+}
+
+impl Drop for Analysis {
+    fn drop(&mut self) {
+        // the thread notices the signal within a few thousand nodes, so it is left to finish by itself
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The keys of the positions played before the current one, as the search expects them.
+pub fn history_keys(game: &Game) -> Vec<u64> {
+    game.history()
+        .iter()
+        .map(|ply| ply.board.polyglot_key())
+        .collect()
+}
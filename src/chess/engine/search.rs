@@ -0,0 +1,458 @@
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::chess::game::{
+    board::Board,
+    chess_move::Move,
+    piece::{PieceColor, PieceType},
+    variant::Variant,
+};
+
+use super::eval::{evaluate, piece_value};
+
+/// The score of checkmating right away, from which the plies taken to get there are subtracted.
+const MATE: i32 = 30_000;
+
+/// The deepest the search goes, which also bounds how far a mate can be seen.
+pub const MAX_DEPTH: u8 = 64;
+
+/// The rating the engine is estimated to play at when searching for a second a move,
+/// and the rating it gains every time the time it searches for doubles.
+const RATING_BY_TIME: (f64, f64) = (1500.0, 80.0);
+
+/// The rating the engine is estimated to play at when searching no deeper than a ply,
+/// and the rating it gains with every ply it may search deeper.
+const RATING_BY_DEPTH: (f64, f64) = (550.0, 150.0);
+
+/// The lowest and the highest rating the engine is estimated to play at, whatever its limits.
+const RATING_RANGE: (f64, f64) = (800.0, 2400.0);
+
+/// How many nodes are searched between checks of the stop signal and the limits.
+const CHECK_INTERVAL: u64 = 1024;
+
+/// The most positions remembered between searches at different depths, to keep the memory used in check.
+const TABLE_SIZE: usize = 1 << 20;
+
+/// How good a position is, as found by the search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    /// The advantage of white in hundredths of a pawn; negative when black is better.
+    Centipawns(i32),
+    /// A forced checkmate by the winner in the given number of moves, none if the position is already checkmate.
+    Mate { winner: PieceColor, moves: u16 },
+}
+
+impl Score {
+    /// Converts a score from the point of view of the side to move.
+    fn new(value: i32, turn: PieceColor) -> Self {
+        if value.abs() >= MATE - MAX_DEPTH as i32 * 2 {
+            let plies = MATE - value.abs();
+            let (winner, moves) = match value > 0 {
+                true => (turn, (plies + 1) / 2),
+                false => (turn.opposite(), plies / 2),
+            };
+            return Score::Mate {
+                winner,
+                moves: moves as u16,
+            };
+        }
+        match turn {
+            PieceColor::White => Score::Centipawns(value),
+            PieceColor::Black => Score::Centipawns(-value),
+        }
+    }
+
+    /// The chance of white winning from the score, between zero and one, as used for the evaluation bar.
+    pub fn white_share(self) -> f64 {
+        match self {
+            Score::Centipawns(centipawns) => 1.0 / (1.0 + 10f64.powf(-centipawns as f64 / 400.0)),
+            Score::Mate {
+                winner: PieceColor::White,
+                ..
+            } => 1.0,
+            Score::Mate {
+                winner: PieceColor::Black,
+                ..
+            } => 0.0,
+        }
+    }
+}
+
+impl std::fmt::Display for Score {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Score::Centipawns(centipawns) => write!(f, "{:+.2}", *centipawns as f64 / 100.0),
+            Score::Mate {
+                winner: PieceColor::White,
+                moves,
+            } => write!(f, "#{moves}"),
+            Score::Mate {
+                winner: PieceColor::Black,
+                moves,
+            } => write!(f, "#-{moves}"),
+        }
+    }
+}
+
+/// How long a search may run; it always finishes at least the first depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub depth: u8,
+    pub time: Option<Duration>,
+    pub nodes: Option<u64>,
+}
+
+impl SearchLimits {
+    /// Searches until stopped, or until the deepest depth is reached.
+    pub fn infinite() -> Self {
+        Self {
+            depth: MAX_DEPTH,
+            time: None,
+            nodes: None,
+        }
+    }
+
+    /// Searches for the given time, or until the deepest depth is reached.
+    pub fn time(time: Duration) -> Self {
+        Self {
+            time: Some(time),
+            ..Self::infinite()
+        }
+    }
+
+    /// A rough estimate of the Elo rating the engine plays at within the limits, going by whichever
+    /// of the depth and the time holds it back the most.
+    pub fn estimated_rating(&self) -> i32 {
+        let (rating_at_one_ply, per_ply) = RATING_BY_DEPTH;
+        let by_depth = rating_at_one_ply + per_ply * f64::from(self.depth.saturating_sub(1));
+        let (rating_at_one_second, per_doubling) = RATING_BY_TIME;
+        let by_time = self.time.map_or(f64::INFINITY, |time| {
+            rating_at_one_second + per_doubling * time.as_secs_f64().log2()
+        });
+        let (lowest, highest) = RATING_RANGE;
+        by_depth.min(by_time).clamp(lowest, highest).round() as i32
+    }
+}
+
+/// What the search found after completing a depth.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchInfo {
+    pub depth: u8,
+    pub score: Score,
+    /// The principal variation: the line of best play expected from the position, starting with the best move.
+    pub pv: Vec<Move>,
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The position is at least as good as the stored value, as the search was cut off.
+    Lower,
+    /// The position is at most as good as the stored value, as no move raised the lower bound.
+    Upper,
+}
+
+/// A position remembered by the search, so it is not searched again and its best move can be tried first.
+#[derive(Debug, Clone, Copy)]
+struct TableEntry {
+    depth: u8,
+    value: i32,
+    bound: Bound,
+    best_move: Option<Move>,
+}
+
+/// An alpha-beta search with iterative deepening over the positions reachable from a board.
+///
+/// The search knows the rules kept by the board itself, which leaves out the pockets of Crazyhouse
+/// and the checks counted in Three-Check.
+struct Search<'a> {
+    limits: SearchLimits,
+    stop: &'a AtomicBool,
+    start: Instant,
+    nodes: u64,
+    /// The node count at which the stop signal and the limits are checked next.
+    next_check: u64,
+    /// Whether the search was interrupted, leaving the depth being searched unfinished.
+    aborted: bool,
+    /// Whether a depth has been completed; until then the search cannot be interrupted, so that there is a move to play.
+    has_result: bool,
+    table: HashMap<u64, TableEntry>,
+    /// The keys of the positions leading up to the one being searched, to recognize repetitions.
+    path: Vec<u64>,
+}
+
+/// Searches the position within the limits, reporting the result of every completed depth as it goes.
+///
+/// The keys of the positions played before the board, in order, let the search steer into or away from repetitions.
+/// Returns the result of the deepest completed depth, which is only missing if the game is already over.
+pub fn search(
+    board: &Board,
+    history: &[u64],
+    limits: SearchLimits,
+    stop: &AtomicBool,
+    mut report: impl FnMut(&SearchInfo),
+) -> Option<SearchInfo> {
+    let mut search = Search {
+        limits,
+        stop,
+        start: Instant::now(),
+        nodes: 0,
+        next_check: 0,
+        aborted: false,
+        has_result: false,
+        table: HashMap::new(),
+        path: history.to_vec(),
+    };
+
+    if let Some(value) = terminal_value(board, 0).or_else(|| {
+        board
+            .legal_moves()
+            .is_empty()
+            .then(|| if board.in_check() { -MATE } else { 0 })
+    }) {
+        let info = SearchInfo {
+            depth: 0,
+            score: Score::new(value, board.turn()),
+            pv: Vec::new(),
+            nodes: 1,
+            elapsed: search.start.elapsed(),
+        };
+        report(&info);
+        return None;
+    }
+
+    let mut result = None;
+    for depth in 1..=limits.depth {
+        let mut pv = Vec::new();
+        let value = search.negamax(board, depth, 0, -MATE - 1, MATE + 1, &mut pv);
+        // an unfinished depth is thrown away
+        if search.aborted {
+            break;
+        }
+        let info = SearchInfo {
+            depth,
+            score: Score::new(value, board.turn()),
+            pv,
+            nodes: search.nodes,
+            elapsed: search.start.elapsed(),
+        };
+        report(&info);
+        let is_mate =
+            matches!(info.score, Score::Mate { moves, .. } if (moves as u32) * 2 <= depth as u32);
+        result = Some(info);
+        search.has_result = true;
+        if is_mate {
+            break;
+        }
+    }
+    result
+}
+
+/// The value of a position the variant has already decided, from the point of view of the side to move.
+fn terminal_value(board: &Board, ply: u8) -> Option<i32> {
+    let turn = board.turn();
+    let lost = match board.variant() {
+        Variant::Atomic => board.king_position(turn).is_none(),
+        Variant::KingOfTheHill => board.is_king_on_hill(turn.opposite()),
+        _ => false,
+    };
+    lost.then_some(-MATE + ply as i32)
+}
+
+impl Search<'_> {
+    fn should_stop(&mut self) -> bool {
+        if self.aborted || !self.has_result {
+            return self.aborted;
+        }
+        if self.nodes >= self.next_check {
+            self.next_check = self.nodes + CHECK_INTERVAL;
+            self.aborted = self.stop.load(Ordering::Relaxed)
+                || self
+                    .limits
+                    .time
+                    .map(|time| self.start.elapsed() >= time)
+                    .unwrap_or(false)
+                || self
+                    .limits
+                    .nodes
+                    .map(|nodes| self.nodes >= nodes)
+                    .unwrap_or(false);
+        }
+        self.aborted
+    }
+
+    fn negamax(
+        &mut self,
+        board: &Board,
+        depth: u8,
+        ply: u8,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        pv.clear();
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        if let Some(value) = terminal_value(board, ply) {
+            return value;
+        }
+        let key = board.polyglot_key();
+        if ply > 0 && (board.halfmove_clock() >= 100 || self.path.contains(&key)) {
+            return 0;
+        }
+
+        let in_check = board.in_check();
+        // checks are searched a ply deeper, so that forced lines are not cut short
+        let depth = match in_check && ply < MAX_DEPTH {
+            true => depth + 1,
+            false => depth,
+        };
+        if depth == 0 || ply >= MAX_DEPTH {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        let entry = self.table.get(&key).copied();
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth >= depth) {
+            let value = from_table(entry.value, ply);
+            match entry.bound {
+                Bound::Exact => return value,
+                Bound::Lower if value >= beta => return value,
+                Bound::Upper if value <= alpha => return value,
+                _ => {}
+            }
+        }
+
+        let mut moves = board.legal_moves();
+        if moves.is_empty() {
+            return match in_check {
+                true => -MATE + ply as i32,
+                false => 0,
+            };
+        }
+        order_moves(&mut moves, entry.and_then(|entry| entry.best_move));
+
+        let original_alpha = alpha;
+        let mut best_value = -MATE - 1;
+        let mut best_move = None;
+        let mut line = Vec::new();
+        self.path.push(key);
+        for mv in moves {
+            let mut after = *board;
+            after.apply(&mv);
+            let value = -self.negamax(&after, depth - 1, ply + 1, -beta, -alpha, &mut line);
+            if self.aborted {
+                break;
+            }
+            if value > best_value {
+                best_value = value;
+                best_move = Some(mv);
+            }
+            if value > alpha {
+                alpha = value;
+                pv.clear();
+                pv.push(mv);
+                pv.extend_from_slice(&line);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        self.path.pop();
+        if self.aborted {
+            return best_value.max(alpha);
+        }
+
+        let bound = if best_value <= original_alpha {
+            Bound::Upper
+        } else if best_value >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        if self.table.len() < TABLE_SIZE || self.table.contains_key(&key) {
+            self.table.insert(
+                key,
+                TableEntry {
+                    depth,
+                    value: to_table(best_value, ply),
+                    bound,
+                    best_move,
+                },
+            );
+        }
+        best_value
+    }
+
+    /// Searches captures and promotions until the position is quiet, so it is not judged in the middle of an exchange.
+    fn quiescence(&mut self, board: &Board, ply: u8, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        let stand_pat = evaluate(board);
+        if stand_pat >= beta || ply >= MAX_DEPTH * 2 {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut moves: Vec<Move> = board
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| mv.captured.is_some() || mv.promotion == Some(PieceType::Queen))
+            .collect();
+        order_moves(&mut moves, None);
+        for mv in moves {
+            let mut after = *board;
+            after.apply(&mv);
+            let value = match terminal_value(&after, ply + 1) {
+                Some(value) => -value,
+                None => -self.quiescence(&after, ply + 1, -beta, -alpha),
+            };
+            if value >= beta {
+                return value;
+            }
+            alpha = alpha.max(value);
+        }
+        alpha
+    }
+}
+
+/// Sorts the moves most likely to be best to the front: the remembered best move, then the captures of
+/// the most valuable pieces by the least valuable ones, then promotions.
+fn order_moves(moves: &mut [Move], best_move: Option<Move>) {
+    moves.sort_by_key(|mv| {
+        if Some(*mv) == best_move {
+            return i32::MIN;
+        }
+        let capture = mv
+            .captured
+            .map(|captured| {
+                10 * piece_value(captured.variant()) - piece_value(mv.piece.variant()) + 10_000
+            })
+            .unwrap_or(0);
+        let promotion = mv.promotion.map(piece_value).unwrap_or(0);
+        -(capture + promotion)
+    });
+}
+
+/// Mate values are stored relative to the position rather than the root, as the position can be reached at any ply.
+fn to_table(value: i32, ply: u8) -> i32 {
+    match value {
+        value if value >= MATE - MAX_DEPTH as i32 * 2 => value + ply as i32,
+        value if value <= -MATE + MAX_DEPTH as i32 * 2 => value - ply as i32,
+        value => value,
+    }
+}
+
+fn from_table(value: i32, ply: u8) -> i32 {
+    match value {
+        value if value >= MATE - MAX_DEPTH as i32 * 2 => value - ply as i32,
+        value if value <= -MATE + MAX_DEPTH as i32 * 2 => value + ply as i32,
+        value => value,
+    }
+}
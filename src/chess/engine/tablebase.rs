@@ -0,0 +1,1427 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use crate::chess::{
+    game::{
+        board::{Board, CastlingRights},
+        chess_move::Move,
+        piece::{PieceColor, PieceType},
+        variant::Variant,
+    },
+    storage,
+};
+
+/// The name of the directory the Syzygy tables are looked for in, in the data directory, unless another one
+/// is set. It may as well be a link to wherever the tables are kept.
+const DIRECTORY: &str = "syzygy";
+
+/// The directory set for the tables to be read from instead of the one in the data directory.
+static CONFIGURED_DIRECTORY: OnceLock<PathBuf> = OnceLock::new();
+
+/// The tablebase, looked for the first time it is needed and kept for as long as the app runs,
+/// or what kept it from being read.
+static SHARED: OnceLock<Result<Option<Tablebase>, String>> = OnceLock::new();
+
+/// The extension of the tables telling whether a position is won, drawn or lost.
+const WDL_EXTENSION: &str = "rtbw";
+
+/// The extension of the tables telling how far a position is from the next capture or pawn move.
+const DTZ_EXTENSION: &str = "rtbz";
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+/// The most pieces of any Syzygy table, kings included.
+const MAX_PIECES: usize = 7;
+
+/// The pieces in the order they are named in the file names of the tables, after the king.
+const NAME_ORDER: [PieceType; 5] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+];
+
+/// The flags of the compressed data of a table, for one side to move and one file of the leading pawn.
+const STM_FLAG: u8 = 1;
+const MAPPED_FLAG: u8 = 2;
+const WIN_PLIES_FLAG: u8 = 4;
+const LOSS_PLIES_FLAG: u8 = 8;
+const WIDE_FLAG: u8 = 16;
+const SINGLE_VALUE_FLAG: u8 = 128;
+
+/// The number of placements of the two kings, up to symmetry, when they are the only unique pieces.
+const KINGS_PLACEMENTS: u64 = 462;
+
+/// The number of placements of three unique pieces, up to symmetry.
+const TRIPLE_PLACEMENTS: u64 = 31_332;
+
+/// The outcome of a position with perfect play, from the point of view of the side to move.
+///
+/// A cursed win is a win the fifty-move rule turns into a draw, and a blessed loss is a loss it saves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    /// The outcome stored in a table, from -2 for a loss to 2 for a win.
+    fn from_value(value: i32) -> Self {
+        match value {
+            ..=-2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            _ => Wdl::Win,
+        }
+    }
+
+    fn value(self) -> i32 {
+        self as i32 - 2
+    }
+
+    /// The same outcome, seen from the other side.
+    fn opposite(self) -> Self {
+        Self::from_value(-self.value())
+    }
+
+    /// The distance to zeroing the position just before a capture or a pawn move leading to the outcome.
+    fn before_zeroing(self) -> i32 {
+        match self {
+            Wdl::Loss => -1,
+            Wdl::BlessedLoss => -101,
+            Wdl::Draw => 0,
+            Wdl::CursedWin => 101,
+            Wdl::Win => 1,
+        }
+    }
+}
+
+/// What the tables tell about a position: who wins with perfect play, the move to play for it,
+/// and the distance to zeroing once it is played.
+#[derive(Debug, Clone, Copy)]
+pub struct Verdict {
+    /// The side winning, or nothing for a draw, the fifty-move rule included.
+    pub winner: Option<PieceColor>,
+    pub best_move: Move,
+    /// The distance to zeroing in plies, from the point of view of the side to move. It counts the plies
+    /// until the next capture or pawn move, not until mate.
+    pub dtz: i32,
+}
+
+impl std::fmt::Display for Verdict {
+    /// Tells the outcome from the point of view of the side to move, counting the distance to zeroing in moves
+    /// rather than plies. It is no distance to mate, which the tables do not know.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (outcome, moves) = match (self.winner, self.dtz > 0) {
+            (None, _) => return write!(f, "draw"),
+            (Some(_), true) => ("win", (self.dtz + 1) / 2),
+            (Some(_), false) => ("loss", -self.dtz / 2),
+        };
+        match moves {
+            1 => write!(f, "{outcome} in 1 move (DTZ)"),
+            moves => write!(f, "{outcome} in {moves} moves (DTZ)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Wdl,
+    Dtz,
+}
+
+impl Kind {
+    fn extension(self) -> &'static str {
+        match self {
+            Kind::Wdl => WDL_EXTENSION,
+            Kind::Dtz => DTZ_EXTENSION,
+        }
+    }
+
+    fn magic(self) -> [u8; 4] {
+        match self {
+            Kind::Wdl => WDL_MAGIC,
+            Kind::Dtz => DTZ_MAGIC,
+        }
+    }
+}
+
+/// What a table holds for a position.
+enum Probe {
+    Value(i32),
+    /// The table only holds the positions with the other side to move, as distance tables store one side.
+    OtherSide,
+}
+
+/// The Syzygy endgame tablebases found in a directory, telling the outcome of positions with few pieces
+/// and how to get there with perfect play.
+///
+/// The tables are only read the first time a position needs them, and then kept in memory.
+pub struct Tablebase {
+    dir: PathBuf,
+    /// The most pieces of the positions the outcome tables in the directory cover.
+    max_pieces: usize,
+    indices: Indices,
+    /// The tables read so far by file name, or nothing for those that are missing or broken.
+    tables: Mutex<HashMap<String, Option<Arc<Table>>>>,
+    /// The last table found broken, which the other tables are still used without.
+    problem: Mutex<Option<String>>,
+}
+
+/// Reads the tables from the directory instead of the one in the data directory.
+/// Only the first call counts, and only if it comes before the tables are first needed.
+pub fn set_directory(dir: PathBuf) {
+    let _ = CONFIGURED_DIRECTORY.set(dir);
+}
+
+/// The tablebase, if there is one and it could be read.
+///
+/// The directory is read the first time, so this is best left to the threads of the engine.
+pub fn shared() -> Option<&'static Tablebase> {
+    SHARED.get_or_init(Tablebase::load).as_ref().ok()?.as_ref()
+}
+
+/// What went wrong reading the tablebase or one of its tables, if anything did.
+///
+/// Nothing is read here, so that it can be asked while drawing: the tablebase is only looked at once needed.
+pub fn problem() -> Option<String> {
+    match SHARED.get()? {
+        Err(problem) => Some(problem.clone()),
+        Ok(tablebase) => tablebase.as_ref()?.problem.lock().ok()?.clone(),
+    }
+}
+
+impl Tablebase {
+    /// Finds the tables in the directory, without reading them past the first bytes telling they are tables.
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        let mut max_pieces = 0;
+        let mut problem = None;
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let kind = match path.extension().and_then(|extension| extension.to_str()) {
+                Some(WDL_EXTENSION) => Kind::Wdl,
+                Some(DTZ_EXTENSION) => Kind::Dtz,
+                _ => continue,
+            };
+            if !has_magic(&path, kind) {
+                problem = Some(format!("{} is not a Syzygy table", path.display()));
+                continue;
+            }
+            if kind != Kind::Wdl {
+                continue;
+            }
+            if let Some(pieces) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(Material::from_name)
+                .map(|material| material.piece_count())
+            {
+                max_pieces = max_pieces.max(pieces);
+            }
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            max_pieces,
+            indices: Indices::new(),
+            tables: Mutex::new(HashMap::new()),
+            problem: Mutex::new(problem),
+        })
+    }
+
+    /// Opens the tables in the directory set for them, or else in the data directory.
+    ///
+    /// The data directory having no tables is no problem, as they are optional, but the directory set
+    /// for them not holding any is.
+    pub fn load() -> Result<Option<Self>, String> {
+        let (dir, configured) = match CONFIGURED_DIRECTORY.get() {
+            Some(dir) => (dir.clone(), true),
+            None => match storage::data_dir() {
+                Some(dir) => (dir.join(DIRECTORY), false),
+                None => return Ok(None),
+            },
+        };
+        match Self::open(&dir) {
+            Ok(tablebase) if tablebase.max_pieces > 0 => Ok(Some(tablebase)),
+            Ok(tablebase) => match tablebase.problem.into_inner().ok().flatten() {
+                Some(problem) => Err(problem),
+                None if configured => Err(format!("no tables in {}", dir.display())),
+                None => Ok(None),
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound && !configured => Ok(None),
+            Err(error) => Err(format!("cannot read {}: {error}", dir.display())),
+        }
+    }
+
+    /// Whether the position is one the tables could know: one of standard chess, with no castling rights left
+    /// and few enough pieces.
+    fn covers(&self, board: &Board) -> bool {
+        board.variant() == Variant::Standard
+            && board.castling() == CastlingRights::none()
+            && board.pieces().count() <= self.max_pieces
+    }
+
+    /// The move to play with perfect play, along with the distance to zeroing of the position once it is played,
+    /// counted from the position before it.
+    ///
+    /// Wins are converted as fast as the fifty-move rule allows, draws are held, and losses are dragged out.
+    pub fn best_move(&self, board: &Board) -> Option<(Move, i32)> {
+        if !self.covers(board) {
+            return None;
+        }
+        let clock = board.halfmove_clock() as i32;
+        let rank = |dtz: i32| match dtz {
+            0 => 0,
+            dtz if dtz > 0 && dtz + clock <= 100 => 3000 - dtz,
+            dtz if dtz > 0 => 1000 - dtz,
+            dtz if -dtz + clock <= 100 => -3000 - dtz,
+            dtz => -1000 - dtz,
+        };
+        let mut best: Option<(Move, i32)> = None;
+        for mv in board.legal_moves() {
+            let mut after = *board;
+            after.apply(&mv);
+            let mut dtz = match is_zeroing(&mv) {
+                true => self.search(&after, false)?.0.opposite().before_zeroing(),
+                false => match -self.dtz(&after)? {
+                    0 => 0,
+                    dtz => dtz + dtz.signum(),
+                },
+            };
+            if dtz == 2 && after.in_check() && after.legal_moves().is_empty() {
+                dtz = 1;
+            }
+            if best.is_none_or(|(_, best)| rank(dtz) > rank(best)) {
+                best = Some((mv, dtz));
+            }
+        }
+        best
+    }
+
+    /// Who wins the position with perfect play and how, if the tables needed for it are in the directory.
+    pub fn verdict(&self, board: &Board) -> Option<Verdict> {
+        let (best_move, dtz) = self.best_move(board)?;
+        let clock = board.halfmove_clock() as i32;
+        // a win the fifty-move rule would come first for is a draw
+        let winner = match dtz {
+            dtz if dtz > 0 && dtz + clock <= 100 => Some(board.turn()),
+            dtz if dtz < 0 && -dtz + clock <= 100 => Some(board.turn().opposite()),
+            _ => None,
+        };
+        Some(Verdict {
+            winner,
+            best_move,
+            dtz,
+        })
+    }
+
+    /// The outcome of the position, searching the captures and, if asked, the pawn moves before looking it up,
+    /// as the tables store no matter what for positions where one of them is best. Also tells whether the
+    /// outcome comes from such a move, for which the distance tables are not to be trusted either.
+    fn search(&self, board: &Board, pawn_moves: bool) -> Option<(Wdl, bool)> {
+        // bare kings are a draw, and have no table of their own
+        if board.pieces().count() == 2 {
+            return Some((Wdl::Draw, false));
+        }
+        let moves = board.legal_moves();
+        let mut best = Wdl::Loss;
+        let mut searched = 0;
+        for mv in &moves {
+            if mv.captured.is_none() && !(pawn_moves && mv.piece.variant() == PieceType::Pawn) {
+                continue;
+            }
+            searched += 1;
+            let mut after = *board;
+            after.apply(mv);
+            let value = self.search(&after, false)?.0.opposite();
+            if value > best {
+                best = value;
+                if value == Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+        // with every move searched the table is not needed, and could be wrong about en passant
+        let all_searched = searched > 0 && searched == moves.len();
+        let value = match all_searched {
+            true => best,
+            false => match self.probe_table(board, Kind::Wdl, Wdl::Draw)? {
+                Probe::Value(value) => Wdl::from_value(value),
+                Probe::OtherSide => return None,
+            },
+        };
+        match best >= value {
+            true => Some((best, best > Wdl::Draw || all_searched)),
+            false => Some((value, false)),
+        }
+    }
+
+    /// The distance to zeroing of the position, in plies: how long until the winner captures or moves a pawn,
+    /// which is the mate itself when there is no such move left. It is positive when the side to move wins,
+    /// negative when it loses, and zero for a draw; wins and losses the fifty-move rule saves add 100.
+    fn dtz(&self, board: &Board) -> Option<i32> {
+        let (wdl, zeroing) = self.search(board, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if zeroing {
+            return Some(wdl.before_zeroing());
+        }
+        let sign = wdl.value().signum();
+        match self.probe_table(board, Kind::Dtz, wdl)? {
+            Probe::Value(dtz) => {
+                let cursed = matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss);
+                Some((dtz + if cursed { 100 } else { 0 }) * sign)
+            }
+            Probe::OtherSide => {
+                // the table holds the positions after every move, so the best of them is looked for
+                let mut best = None;
+                for mv in board.legal_moves() {
+                    let mut after = *board;
+                    after.apply(&mv);
+                    let zeroing = is_zeroing(&mv);
+                    let mut dtz = match zeroing {
+                        true => -self.search(&after, false)?.0.before_zeroing(),
+                        false => -self.dtz(&after)?,
+                    };
+                    if dtz == 1 && after.in_check() && after.legal_moves().is_empty() {
+                        best = Some(1);
+                    }
+                    if !zeroing {
+                        dtz += dtz.signum();
+                    }
+                    if dtz.signum() == sign && best.is_none_or(|best| dtz < best) {
+                        best = Some(dtz);
+                    }
+                }
+                // without a legal move the position is mate
+                Some(best.unwrap_or(-1))
+            }
+        }
+    }
+
+    /// The table of the given kind for the material, reading it the first time it is asked for.
+    fn table(&self, name: &str, kind: Kind) -> Option<Arc<Table>> {
+        let file_name = format!("{name}.{}", kind.extension());
+        let mut tables = self.tables.lock().ok()?;
+        if let Some(table) = tables.get(&file_name) {
+            return table.clone();
+        }
+        let table = Material::from_name(name).and_then(|material| {
+            let read = fs::read(self.dir.join(&file_name))
+                .and_then(|data| Table::parse(data, kind, &material, &self.indices));
+            match read {
+                Ok(table) => Some(Arc::new(table)),
+                // a missing table only leaves the positions it would cover unknown
+                Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+                Err(error) => {
+                    if let Ok(mut problem) = self.problem.lock() {
+                        *problem = Some(format!("cannot read {file_name}: {error}"));
+                    }
+                    None
+                }
+            }
+        });
+        tables.insert(file_name, table.clone());
+        table
+    }
+
+    /// Looks the position up in the table of the kind for its material.
+    ///
+    /// The tables are named with the stronger side first, as white to move, so the colors and the board are
+    /// switched around when black holds those pieces.
+    fn probe_table(&self, board: &Board, kind: Kind, wdl: Wdl) -> Option<Probe> {
+        let pieces: Vec<(usize, u8)> = board
+            .pieces()
+            .map(|(position, piece)| {
+                let square = position.rank() as usize * 8 + position.file() as usize;
+                (square, piece_code(piece.color(), piece.variant()))
+            })
+            .collect();
+        // two bare kings are drawn, and have no table
+        if pieces.len() == 2 {
+            return Some(Probe::Value(0));
+        }
+        if pieces.len() > MAX_PIECES {
+            return None;
+        }
+        let material = Material::from_board(board);
+        let (table, black_stronger) = match self.table(&material.name(), kind) {
+            Some(table) => (table, false),
+            None => (self.table(&material.flipped().name(), kind)?, true),
+        };
+        let black_to_move = board.turn() == PieceColor::Black;
+        let flip = black_stronger || (table.symmetric && black_to_move);
+        let (flip_color, flip_squares) = match flip {
+            true => (8, 56),
+            false => (0, 0),
+        };
+        let side = (flip ^ black_to_move) as usize;
+
+        let mut squares = [0usize; MAX_PIECES];
+        let mut codes = [0u8; MAX_PIECES];
+        let mut size = 0;
+        let mut lead_pawns = 0;
+        let mut file = 0;
+        let map_pawns = &self.indices.map_pawns;
+        if table.has_pawns {
+            // the leading pawns are those of the color of the first piece of the table, which is a pawn
+            let lead = table.pairs[0][0].pieces[0] ^ flip_color;
+            for &(square, code) in pieces.iter().filter(|(_, code)| *code == lead) {
+                squares[size] = square ^ flip_squares;
+                codes[size] = code ^ flip_color;
+                size += 1;
+            }
+            lead_pawns = size;
+            // the pawn nearest the edge, and the lowest of those, leads
+            let leader = (0..lead_pawns).max_by_key(|&i| map_pawns[squares[i]])?;
+            squares.swap(0, leader);
+            file = (squares[0] % 8).min(7 - squares[0] % 8);
+        }
+        if kind == Kind::Dtz && !table.stores_side(side, file) {
+            return Some(Probe::OtherSide);
+        }
+        for &(square, code) in &pieces {
+            if table.has_pawns && code == table.pairs[0][0].pieces[0] ^ flip_color {
+                continue;
+            }
+            squares[size] = square ^ flip_squares;
+            codes[size] = code ^ flip_color;
+            size += 1;
+        }
+        let pairs = table.pairs(side, file);
+
+        // the pieces are put in the order the table was encoded with
+        for i in lead_pawns..size.saturating_sub(1) {
+            if let Some(j) = (i + 1..size).find(|&j| codes[j] == pairs.pieces[i]) {
+                codes.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        let index = self
+            .indices
+            .position(&table, pairs, &mut squares, size, lead_pawns)?;
+        let value = table.decompress(pairs, index)? as i32;
+        Some(Probe::Value(match kind {
+            Kind::Wdl => value - 2,
+            Kind::Dtz => table.dtz_value(file, value, wdl)?,
+        }))
+    }
+}
+
+/// Whether the move resets the fifty-move rule.
+fn is_zeroing(mv: &Move) -> bool {
+    mv.captured.is_some() || mv.piece.variant() == PieceType::Pawn
+}
+
+/// The code of a piece in the tables: 1 to 6 from the pawn to the king, plus 8 for black.
+fn piece_code(color: PieceColor, variant: PieceType) -> u8 {
+    let code = match variant {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    };
+    match color {
+        PieceColor::White => code,
+        PieceColor::Black => code + 8,
+    }
+}
+
+/// How far the square is above the a1-h8 diagonal, negative when below it.
+fn off_diagonal(square: usize) -> i32 {
+    (square / 8) as i32 - (square % 8) as i32
+}
+
+/// The pieces of either side besides the king, counted by type in the order of [`NAME_ORDER`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Material {
+    white: [usize; 5],
+    black: [usize; 5],
+}
+
+impl Material {
+    fn from_board(board: &Board) -> Self {
+        let mut material = Self {
+            white: [0; 5],
+            black: [0; 5],
+        };
+        for (_, piece) in board.pieces() {
+            let Some(index) = NAME_ORDER
+                .iter()
+                .position(|&variant| variant == piece.variant())
+            else {
+                continue;
+            };
+            match piece.color() {
+                PieceColor::White => material.white[index] += 1,
+                PieceColor::Black => material.black[index] += 1,
+            }
+        }
+        material
+    }
+
+    /// Reads the material from the name of a table, such as `KRPvKR`.
+    fn from_name(name: &str) -> Option<Self> {
+        let (white, black) = name.split_once('v')?;
+        let side = |pieces: &str| {
+            let mut counts = [0; 5];
+            let mut letters = pieces.chars();
+            if letters.next() != Some('K') {
+                return None;
+            }
+            for letter in letters {
+                let variant = PieceType::from_letter(letter).filter(|_| letter.is_uppercase())?;
+                counts[NAME_ORDER.iter().position(|&other| other == variant)?] += 1;
+            }
+            Some(counts)
+        };
+        Some(Self {
+            white: side(white)?,
+            black: side(black)?,
+        })
+    }
+
+    fn name(&self) -> String {
+        let side = |counts: &[usize; 5]| {
+            let mut name = String::from("K");
+            for (variant, &count) in NAME_ORDER.iter().zip(counts) {
+                name.extend(std::iter::repeat_n(variant.letter(), count));
+            }
+            name
+        };
+        format!("{}v{}", side(&self.white), side(&self.black))
+    }
+
+    fn flipped(&self) -> Self {
+        Self {
+            white: self.black,
+            black: self.white,
+        }
+    }
+
+    fn piece_count(&self) -> usize {
+        2 + self.white.iter().sum::<usize>() + self.black.iter().sum::<usize>()
+    }
+
+    fn pawns(&self, color: PieceColor) -> usize {
+        match color {
+            PieceColor::White => self.white[4],
+            PieceColor::Black => self.black[4],
+        }
+    }
+}
+
+/// The tables of combinations the positions are numbered with, as the Syzygy generator laid them out.
+struct Indices {
+    /// The number of ways to choose k of n squares, by k and n.
+    binomial: [[u64; 64]; MAX_PIECES],
+    /// The squares below the a1-h8 diagonal, numbered 0 to 27.
+    map_b1h1h7: [u64; 64],
+    /// The squares of the a1-d1-d4 triangle, numbered 0 to 9 with the diagonal last.
+    map_a1d1d4: [u64; 64],
+    /// The placements of two kings, by the number of the first in the triangle and the square of the second.
+    map_kk: [[u64; 64]; 10],
+    /// The squares of the second to the seventh rank, numbered so that the leading pawn has the highest one.
+    map_pawns: [u64; 64],
+    /// The first number of the placements of the leading pawns, by their count and the square of the leader.
+    lead_pawn_index: [[u64; 64]; 6],
+    /// The number of placements of the leading pawns, by their count and the file of the leader.
+    lead_pawns_size: [[u64; 4]; 6],
+}
+
+impl Indices {
+    fn new() -> Self {
+        let mut indices = Self {
+            binomial: [[0; 64]; MAX_PIECES],
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            map_pawns: [0; 64],
+            lead_pawn_index: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+
+        let mut code = 0;
+        for square in 0..64 {
+            if off_diagonal(square) < 0 {
+                indices.map_b1h1h7[square] = code;
+                code += 1;
+            }
+        }
+
+        let mut code = 0;
+        let mut diagonal = Vec::new();
+        for square in 0..=27 {
+            if square % 8 > 3 {
+                continue;
+            }
+            match off_diagonal(square) {
+                0 => diagonal.push(square),
+                off if off < 0 => {
+                    indices.map_a1d1d4[square] = code;
+                    code += 1;
+                }
+                _ => {}
+            }
+        }
+        for square in diagonal {
+            indices.map_a1d1d4[square] = code;
+            code += 1;
+        }
+
+        // the second king cannot stand next to the first, nor above the diagonal when both could be mirrored
+        let mut code = 0;
+        let mut both_on_diagonal = Vec::new();
+        for index in 0..10 {
+            for first in 0..=27 {
+                // b1 is numbered 0 along with a1, which only takes the king once it is found on the diagonal
+                if first % 8 > 3
+                    || indices.map_a1d1d4[first] != index as u64
+                    || (index == 0 && first != 1)
+                {
+                    continue;
+                }
+                for second in 0..64 {
+                    let touching = (first / 8).abs_diff(second / 8) <= 1
+                        && (first % 8).abs_diff(second % 8) <= 1;
+                    if touching || (off_diagonal(first) == 0 && off_diagonal(second) > 0) {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                        both_on_diagonal.push((index, second));
+                    } else {
+                        indices.map_kk[index][second] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (index, second) in both_on_diagonal {
+            indices.map_kk[index][second] = code;
+            code += 1;
+        }
+
+        indices.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_PIECES.min(n + 1) {
+                indices.binomial[k][n] = if k > 0 {
+                    indices.binomial[k - 1][n - 1]
+                } else {
+                    0
+                } + if k < n { indices.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        let mut available = 47;
+        for lead_pawns in 1..6 {
+            for file in 0..4 {
+                let mut index = 0;
+                for rank in 1..7 {
+                    let square = rank * 8 + file;
+                    if lead_pawns == 1 {
+                        indices.map_pawns[square] = available;
+                        indices.map_pawns[square ^ 7] = available - 1;
+                        available = available.saturating_sub(2);
+                    }
+                    indices.lead_pawn_index[lead_pawns][square] = index;
+                    index += indices.binomial[lead_pawns - 1][indices.map_pawns[square] as usize];
+                }
+                indices.lead_pawns_size[lead_pawns][file] = index;
+            }
+        }
+        indices
+    }
+
+    /// The number of the position among those of the table, once the pieces are in the order of the table.
+    ///
+    /// The board is first mirrored so that the leading piece is on the queen side, as the numbering
+    /// leaves out the positions that mirror each other.
+    fn position(
+        &self,
+        table: &Table,
+        pairs: &Pairs,
+        squares: &mut [usize; MAX_PIECES],
+        size: usize,
+        lead_pawns: usize,
+    ) -> Option<u64> {
+        let map_pawns = &self.map_pawns;
+        // the board is mirrored so that the leading piece is on the queen side
+        if squares[0] % 8 > 3 {
+            for square in &mut squares[..size] {
+                *square ^= 7;
+            }
+        }
+        let mut index = if table.has_pawns {
+            let mut index = self.lead_pawn_index[lead_pawns][squares[0]];
+            squares[1..lead_pawns].sort_by_key(|&square| map_pawns[square]);
+            for (i, &square) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+                index += self.binomial[i][map_pawns[square] as usize];
+            }
+            index
+        } else {
+            // without pawns the board is also mirrored so that the leading piece is below the fifth rank,
+            // and then below the a1-h8 diagonal
+            if squares[0] / 8 > 3 {
+                for square in &mut squares[..size] {
+                    *square ^= 56;
+                }
+            }
+            for i in 0..pairs.group_len[0] {
+                match off_diagonal(squares[i]) {
+                    0 => continue,
+                    off if off > 0 => {
+                        for square in &mut squares[i..size] {
+                            *square = ((*square >> 3) | (*square << 3)) & 63;
+                        }
+                    }
+                    _ => {}
+                }
+                break;
+            }
+            match table.has_unique_pieces {
+                true => self.triple_index(squares),
+                false => self.map_kk[self.map_a1d1d4[squares[0]] as usize][squares[1]],
+            }
+        };
+
+        // then the other groups, each as a combination of the squares left for it
+        index *= pairs.group_idx[0];
+        let mut start = pairs.group_len[0];
+        let mut remaining_pawns = table.has_pawns && table.pawn_counts[1] > 0;
+        let mut group = 1;
+        while pairs.group_len[group] != 0 {
+            let len = pairs.group_len[group];
+            squares[start..start + len].sort_unstable();
+            let mut combination = 0;
+            for i in 0..len {
+                let square = squares[start + i];
+                let below = squares[..start]
+                    .iter()
+                    .filter(|&&other| square > other)
+                    .count();
+                let skipped = if remaining_pawns { 8 } else { 0 };
+                combination += self.binomial[i + 1][square.checked_sub(below + skipped)?];
+            }
+            remaining_pawns = false;
+            index += combination * pairs.group_idx[group];
+            start += len;
+            group += 1;
+        }
+
+        Some(index)
+    }
+
+    /// The number of the placement of three unique pieces, the first in the a1-d1-d4 triangle.
+    fn triple_index(&self, squares: &[usize; MAX_PIECES]) -> u64 {
+        let [first, second, third] = [squares[0], squares[1], squares[2]];
+        let adjust1 = (second > first) as u64;
+        let adjust2 = (third > first) as u64 + (third > second) as u64;
+        let rank = |square: usize| (square / 8) as u64;
+        if off_diagonal(first) != 0 {
+            (self.map_a1d1d4[first] * 63 + second as u64 - adjust1) * 62 + third as u64 - adjust2
+        } else if off_diagonal(second) != 0 {
+            (6 * 63 + rank(first) * 28 + self.map_b1h1h7[second]) * 62 + third as u64 - adjust2
+        } else if off_diagonal(third) != 0 {
+            6 * 63 * 62
+                + 4 * 28 * 62
+                + rank(first) * 7 * 28
+                + (rank(second) - adjust1) * 28
+                + self.map_b1h1h7[third]
+        } else {
+            6 * 63 * 62
+                + 4 * 28 * 62
+                + 4 * 7 * 28
+                + rank(first) * 7 * 6
+                + (rank(second) - adjust1) * 6
+                + rank(third)
+                - adjust2
+        }
+    }
+}
+
+/// A whole table file, with the compressed values of every position of its material.
+struct Table {
+    data: Vec<u8>,
+    has_pawns: bool,
+    /// Whether a piece other than the kings is alone of its kind, so that three pieces lead the numbering.
+    has_unique_pieces: bool,
+    /// The pawns of the side leading the numbering, and of the other side.
+    pawn_counts: [usize; 2],
+    /// Whether both sides have the same pieces, in which case only white to move is stored.
+    symmetric: bool,
+    /// The compressed values by side to move and by file of the leading pawn.
+    pairs: Vec<Vec<Pairs>>,
+}
+
+/// The compressed values of a table for one side to move and one file of the leading pawn, by offset into the file.
+///
+/// The values are compressed by recursive pairing, the most frequent pairs of symbols becoming new symbols,
+/// and then encoded with a canonical Huffman code in blocks of a fixed size.
+#[derive(Debug, Default)]
+struct Pairs {
+    flags: u8,
+    /// The codes of the pieces, in the order they are numbered in.
+    pieces: [u8; MAX_PIECES],
+    /// The sizes of the groups of pieces numbered together, ending with a zero.
+    group_len: [usize; MAX_PIECES + 1],
+    /// The factor each group is multiplied by, followed by the number of positions.
+    group_idx: [u64; MAX_PIECES + 1],
+    block_size: usize,
+    /// The number of values between two entries of the sparse index.
+    span: u64,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    block_lengths: usize,
+    block_lengths_size: usize,
+    blocks: usize,
+    blocks_data: usize,
+    /// The shortest code, or the value of every position when there is a single one.
+    min_sym_len: u8,
+    lowest_sym: usize,
+    /// The smallest code of each length, left-aligned, longest last.
+    base64: Vec<u64>,
+    /// The number of values each symbol stands for, less one.
+    sym_len: Vec<u32>,
+    /// The pairs each symbol is made of, three bytes a symbol.
+    btree: usize,
+    /// Where the distances of each outcome start, when the distances are mapped.
+    map_idx: [usize; 4],
+}
+
+/// A broken table.
+/// Whether the file starts the way the tables of the kind do, which tells a broken or foreign file
+/// without reading all of it.
+fn has_magic(path: &Path, kind: Kind) -> bool {
+    let mut magic = [0; 4];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok()
+        && magic == kind.magic()
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "not a valid Syzygy table")
+}
+
+fn bytes<const N: usize>(data: &[u8], at: usize) -> Option<[u8; N]> {
+    data.get(at..at.checked_add(N)?)?.try_into().ok()
+}
+
+fn byte(data: &[u8], at: usize) -> Option<u8> {
+    data.get(at).copied()
+}
+
+fn u16_le(data: &[u8], at: usize) -> Option<u16> {
+    bytes(data, at).map(u16::from_le_bytes)
+}
+
+fn u32_le(data: &[u8], at: usize) -> Option<u32> {
+    bytes(data, at).map(u32::from_le_bytes)
+}
+
+impl Table {
+    /// Reads the layout of the table, checking it is of the kind and the material expected.
+    fn parse(
+        data: Vec<u8>,
+        kind: Kind,
+        material: &Material,
+        indices: &Indices,
+    ) -> io::Result<Self> {
+        if bytes::<4>(&data, 0) != Some(kind.magic()) {
+            return Err(invalid());
+        }
+        let has_pawns = material.pawns(PieceColor::White) + material.pawns(PieceColor::Black) > 0;
+        let flags = byte(&data, 4).ok_or_else(invalid)?;
+        if (flags & 2 != 0) != has_pawns {
+            return Err(invalid());
+        }
+        let has_unique_pieces = material.white.contains(&1) || material.black.contains(&1);
+        let (white_pawns, black_pawns) = (
+            material.pawns(PieceColor::White),
+            material.pawns(PieceColor::Black),
+        );
+        // the side with fewer pawns leads, as that compresses better
+        let pawn_counts = match black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns)
+        {
+            true => [white_pawns, black_pawns],
+            false => [black_pawns, white_pawns],
+        };
+        let symmetric = material.white == material.black;
+        let piece_count = material.piece_count();
+        let sides = match kind == Kind::Wdl && flags & 1 != 0 {
+            true => 2,
+            false => 1,
+        };
+        let files = if has_pawns { 4 } else { 1 };
+        let both_pawns = has_pawns && pawn_counts[1] > 0;
+
+        let mut table = Self {
+            data: Vec::new(),
+            has_pawns,
+            has_unique_pieces,
+            pawn_counts,
+            symmetric,
+            pairs: (0..sides)
+                .map(|_| (0..files).map(|_| Pairs::default()).collect())
+                .collect(),
+        };
+        let mut at = 5;
+        for file in 0..files {
+            let first = byte(&data, at).ok_or_else(invalid)?;
+            let second = match both_pawns {
+                true => byte(&data, at + 1).ok_or_else(invalid)?,
+                false => 0xff,
+            };
+            let order = [[first & 0xf, second & 0xf], [first >> 4, second >> 4]];
+            at += 1 + both_pawns as usize;
+            for k in 0..piece_count {
+                let code = byte(&data, at).ok_or_else(invalid)?;
+                for side in 0..sides {
+                    table.pairs[side][file].pieces[k] = match side {
+                        0 => code & 0xf,
+                        _ => code >> 4,
+                    };
+                }
+                at += 1;
+            }
+            for (side, order) in order.into_iter().enumerate().take(sides) {
+                table.set_groups(side, file, order, piece_count, indices)?;
+            }
+        }
+        at += at & 1;
+
+        for file in 0..files {
+            for side in 0..sides {
+                at = table.pairs[side][file]
+                    .set_sizes(&data, at)
+                    .ok_or_else(invalid)?;
+            }
+        }
+        if kind == Kind::Dtz {
+            at = table.set_dtz_map(&data, at).ok_or_else(invalid)?;
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut table.pairs[side][file];
+                pairs.sparse_index = at;
+                at += pairs.sparse_index_size * 6;
+            }
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut table.pairs[side][file];
+                pairs.block_lengths = at;
+                at += pairs.block_lengths_size * 2;
+            }
+        }
+        for file in 0..files {
+            for side in 0..sides {
+                let pairs = &mut table.pairs[side][file];
+                at = (at + 0x3f) & !0x3f;
+                pairs.blocks_data = at;
+                at += pairs.blocks * pairs.block_size;
+                // a table of a single value has no blocks, and may end before the alignment
+                if pairs.blocks > 0 && at > data.len() {
+                    return Err(invalid());
+                }
+            }
+        }
+        table.data = data;
+        Ok(table)
+    }
+
+    /// Splits the pieces into the groups they are numbered in, and works out the factor of each group
+    /// in the order the table encodes them.
+    fn set_groups(
+        &mut self,
+        side: usize,
+        file: usize,
+        order: [u8; 2],
+        piece_count: usize,
+        indices: &Indices,
+    ) -> io::Result<()> {
+        let (has_pawns, has_unique_pieces) = (self.has_pawns, self.has_unique_pieces);
+        let both_pawns = has_pawns && self.pawn_counts[1] > 0;
+        let pairs = &mut self.pairs[side][file];
+        let mut first_len: i32 = match (has_pawns, has_unique_pieces) {
+            (true, _) => 0,
+            (false, true) => 3,
+            (false, false) => 2,
+        };
+        let mut n = 0;
+        pairs.group_len[0] = 1;
+        for i in 1..piece_count {
+            first_len -= 1;
+            if first_len > 0 || pairs.pieces[i] == pairs.pieces[i - 1] {
+                pairs.group_len[n] += 1;
+            } else {
+                n += 1;
+                pairs.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        pairs.group_len[n] = 0;
+
+        let mut next = if both_pawns { 2 } else { 1 };
+        let mut free = 64 - pairs.group_len[0] - if both_pawns { pairs.group_len[1] } else { 0 };
+        let mut index: u64 = 1;
+        let mut k = 0;
+        while next < n || k == order[0] || k == order[1] {
+            if k == order[0] {
+                pairs.group_idx[0] = index;
+                index *= match (has_pawns, has_unique_pieces) {
+                    (true, _) => *indices
+                        .lead_pawns_size
+                        .get(pairs.group_len[0])
+                        .ok_or_else(invalid)?
+                        .get(file)
+                        .ok_or_else(invalid)?,
+                    (false, true) => TRIPLE_PLACEMENTS,
+                    (false, false) => KINGS_PLACEMENTS,
+                };
+            } else if k == order[1] {
+                pairs.group_idx[1] = index;
+                index *= indices.binomial[pairs.group_len[1]][48 - pairs.group_len[0]];
+            } else {
+                if next >= n || pairs.group_len[next] >= MAX_PIECES {
+                    return Err(invalid());
+                }
+                pairs.group_idx[next] = index;
+                index *= indices.binomial[pairs.group_len[next]][free];
+                free -= pairs.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        pairs.group_idx[n] = index;
+        Ok(())
+    }
+
+    /// Where the distances are mapped to for each outcome, in distance tables whose values are mapped.
+    fn set_dtz_map(&mut self, data: &[u8], mut at: usize) -> Option<usize> {
+        for pairs in &mut self.pairs[0] {
+            if pairs.flags & MAPPED_FLAG == 0 {
+                continue;
+            }
+            for map_idx in &mut pairs.map_idx {
+                if pairs.flags & WIDE_FLAG != 0 {
+                    at += at & 1;
+                    *map_idx = at + 2;
+                    at += 2 * u16_le(data, at)? as usize + 2;
+                } else {
+                    *map_idx = at + 1;
+                    at += byte(data, at)? as usize + 1;
+                }
+            }
+        }
+        Some(at + (at & 1))
+    }
+
+    fn pairs(&self, side: usize, file: usize) -> &Pairs {
+        let sides = &self.pairs[side % self.pairs.len()];
+        &sides[file.min(sides.len() - 1)]
+    }
+
+    /// Whether the distance table stores the positions with the side to move.
+    fn stores_side(&self, side: usize, file: usize) -> bool {
+        (self.pairs(side, file).flags & STM_FLAG) as usize == side
+            || (self.symmetric && !self.has_pawns)
+    }
+
+    /// The distance to zeroing of a value of a distance table, in plies, given the outcome of the position.
+    fn dtz_value(&self, file: usize, value: i32, wdl: Wdl) -> Option<i32> {
+        let pairs = self.pairs(0, file);
+        let mut value = value;
+        if pairs.flags & MAPPED_FLAG != 0 {
+            // the maps come in the order win, loss, cursed win and blessed loss
+            let map = match wdl {
+                Wdl::Win | Wdl::Draw => 0,
+                Wdl::Loss => 1,
+                Wdl::CursedWin => 2,
+                Wdl::BlessedLoss => 3,
+            };
+            let start = pairs.map_idx[map];
+            value = match pairs.flags & WIDE_FLAG != 0 {
+                true => u16_le(&self.data, start + 2 * value as usize)? as i32,
+                false => byte(&self.data, start + value as usize)? as i32,
+            };
+        }
+        let in_moves = match wdl {
+            Wdl::Win => pairs.flags & WIN_PLIES_FLAG == 0,
+            Wdl::Loss => pairs.flags & LOSS_PLIES_FLAG == 0,
+            Wdl::CursedWin | Wdl::BlessedLoss => true,
+            Wdl::Draw => false,
+        };
+        if in_moves {
+            value *= 2;
+        }
+        Some(value + 1)
+    }
+
+    /// The value stored for the position of the given number.
+    fn decompress(&self, pairs: &Pairs, index: u64) -> Option<u16> {
+        if pairs.flags & SINGLE_VALUE_FLAG != 0 {
+            return Some(pairs.min_sym_len as u16);
+        }
+        let data = &self.data;
+        // the sparse index points to a block and an offset in it for every span of values
+        let entry = pairs.sparse_index + (index / pairs.span) as usize * 6;
+        let mut block = u32_le(data, entry)? as usize;
+        let mut offset = u16_le(data, entry + 4)? as i64;
+        offset += (index % pairs.span) as i64 - (pairs.span / 2) as i64;
+        let block_length = |block: usize| {
+            u16_le(data, pairs.block_lengths + 2 * block).map(|length| length as i64)
+        };
+        while offset < 0 {
+            block = block.checked_sub(1)?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        let mut at = pairs.blocks_data + block * pairs.block_size;
+        let mut buffer = u64::from_be_bytes(bytes(data, at)?);
+        at += 8;
+        let mut buffer_size = 64;
+        let min_len = pairs.min_sym_len as usize;
+        let mut symbol = loop {
+            let mut len = 0;
+            while buffer < *pairs.base64.get(len)? {
+                len += 1;
+            }
+            let shift = (64 - len - min_len) as u32;
+            let mut symbol = (buffer - pairs.base64[len]).checked_shr(shift).unwrap_or(0) as usize;
+            symbol += u16_le(data, pairs.lowest_sym + 2 * len)? as usize;
+            let length = *pairs.sym_len.get(symbol)? as i64;
+            if offset <= length {
+                break symbol;
+            }
+            offset -= length + 1;
+            let bits = len + min_len;
+            buffer = buffer.checked_shl(bits as u32).unwrap_or(0);
+            buffer_size -= bits;
+            if buffer_size <= 32 {
+                buffer_size += 32;
+                buffer |= (u32::from_be_bytes(bytes(data, at)?) as u64) << (64 - buffer_size);
+                at += 4;
+            }
+        };
+        // the symbol stands for a run of values, walked down to the one at the offset
+        while pairs.sym_len[symbol] != 0 {
+            let (left, right) = pairs.children(data, symbol)?;
+            let left_length = *pairs.sym_len.get(left)? as i64;
+            if offset <= left_length {
+                symbol = left;
+            } else {
+                offset -= left_length + 1;
+                symbol = right;
+            }
+        }
+        pairs.children(data, symbol).map(|(left, _)| left as u16)
+    }
+}
+
+impl Pairs {
+    /// Reads the sizes of the blocks and the Huffman code, returning where the next part of the file starts.
+    fn set_sizes(&mut self, data: &[u8], mut at: usize) -> Option<usize> {
+        self.flags = byte(data, at)?;
+        at += 1;
+        if self.flags & SINGLE_VALUE_FLAG != 0 {
+            self.min_sym_len = byte(data, at)?;
+            return Some(at + 1);
+        }
+        let end = self.group_len.iter().position(|&len| len == 0)?;
+        let size = self.group_idx[end];
+        self.block_size = 1usize.checked_shl(byte(data, at)? as u32)?;
+        self.span = 1u64.checked_shl(byte(data, at + 1)? as u32)?;
+        self.sparse_index_size = size.div_ceil(self.span) as usize;
+        let padding = byte(data, at + 2)? as usize;
+        self.blocks = u32_le(data, at + 3)? as usize;
+        self.block_lengths_size = self.blocks + padding;
+        let max_sym_len = byte(data, at + 7)? as usize;
+        self.min_sym_len = byte(data, at + 8)?;
+        at += 9;
+        self.lowest_sym = at;
+
+        let lengths = max_sym_len.checked_sub(self.min_sym_len as usize)? + 1;
+        let lowest = |len: usize| u16_le(data, self.lowest_sym + 2 * len).map(u64::from);
+        self.base64 = vec![0; lengths];
+        for len in (0..lengths - 1).rev() {
+            self.base64[len] =
+                (self.base64[len + 1] + lowest(len)?).checked_sub(lowest(len + 1)?)? / 2;
+        }
+        for (len, base) in self.base64.iter_mut().enumerate() {
+            let shift = 64usize.checked_sub(len + self.min_sym_len as usize)?;
+            *base = base.checked_shl(shift as u32).unwrap_or(0);
+        }
+        at += lengths * 2;
+
+        let symbols = u16_le(data, at)? as usize;
+        at += 2;
+        self.btree = at;
+        self.sym_len = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for symbol in 0..symbols {
+            if !visited[symbol] {
+                self.sym_len[symbol] = self.set_sym_len(data, symbol, &mut visited)?;
+            }
+        }
+        Some(at + symbols * 3 + (symbols & 1))
+    }
+
+    /// Works out the number of values the symbol stands for, less one, from those of the pair it is made of.
+    fn set_sym_len(&mut self, data: &[u8], symbol: usize, visited: &mut [bool]) -> Option<u32> {
+        visited[symbol] = true;
+        let (left, right) = self.children(data, symbol)?;
+        if right == 0xfff {
+            return Some(0);
+        }
+        for child in [left, right] {
+            if !*visited.get(child)? {
+                self.sym_len[child] = self.set_sym_len(data, child, visited)?;
+            }
+        }
+        Some(self.sym_len[left] + self.sym_len[right] + 1)
+    }
+
+    /// The pair of symbols the symbol is made of, twelve bits each; a symbol of a single value holds it on the left.
+    fn children(&self, data: &[u8], symbol: usize) -> Option<(usize, usize)> {
+        let [first, second, third] = bytes::<3>(data, self.btree + 3 * symbol)?;
+        let left = ((second as usize & 0xf) << 8) | first as usize;
+        let right = ((third as usize) << 4) | (second as usize >> 4);
+        Some((left, right))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::game::san;
+
+    /// Where the official Syzygy tables of three and four pieces the probes are checked against are kept:
+    /// KQvK, KRvK and KPvK, each as `.rtbw` and `.rtbz`.
+    const FIXTURES: &str = "tests/fixtures/syzygy";
+
+    fn fixtures() -> Tablebase {
+        Tablebase::open(&Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURES)).unwrap()
+    }
+
+    fn probe(tablebase: &Tablebase, fen: &str) -> (Option<PieceColor>, String, i32) {
+        let board = Board::from_fen(fen).unwrap();
+        let verdict = tablebase.verdict(&board).unwrap();
+        (verdict.winner, san(&board, &verdict.best_move), verdict.dtz)
+    }
+
+    /// An empty directory of its own for the test, in the temporary directory.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "terminal-games-syzygy-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn tells_a_broken_table_apart() {
+        let dir = scratch_dir("broken");
+        fs::write(dir.join("KQvK.rtbw"), b"not a table").unwrap();
+        let tablebase = Tablebase::open(&dir).unwrap();
+        assert_eq!(tablebase.max_pieces, 0);
+        let problem = tablebase.problem.lock().unwrap().clone().unwrap();
+        assert!(problem.contains("KQvK.rtbw"), "{problem}");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn fails_to_open_a_missing_directory() {
+        let dir = scratch_dir("missing");
+        fs::remove_dir_all(&dir).unwrap();
+        let error = Tablebase::open(&dir).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn needs_no_table_for_bare_kings() {
+        let dir = scratch_dir("kings");
+        // only the first bytes are read as long as no position needs the table
+        fs::write(dir.join("KQvK.rtbw"), WDL_MAGIC).unwrap();
+        let tablebase = Tablebase::open(&dir).unwrap();
+        let kings = Board::from_fen("8/8/8/4k3/8/8/8/4K3 w - - 0 1").unwrap();
+        let verdict = tablebase.verdict(&kings).unwrap();
+        assert_eq!((verdict.winner, verdict.dtz), (None, 0));
+        // the only move takes the queen, leaving bare kings
+        let (winner, best_move, dtz) = probe(&tablebase, "8/8/8/8/8/8/1Q6/k6K b - - 0 1");
+        assert_eq!((winner, best_move.as_str(), dtz), (None, "Kxb2", 0));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn counts_the_distance_in_moves() {
+        let board = Board::from_fen("k7/8/1K6/8/8/8/8/7Q w - - 0 1").unwrap();
+        let best_move = board.legal_moves()[0];
+        let verdict = |winner, dtz| Verdict {
+            winner,
+            best_move,
+            dtz,
+        };
+        let white = Some(PieceColor::White);
+        assert_eq!(verdict(white, 1).to_string(), "win in 1 move (DTZ)");
+        assert_eq!(verdict(white, 6).to_string(), "win in 3 moves (DTZ)");
+        assert_eq!(verdict(white, -4).to_string(), "loss in 2 moves (DTZ)");
+        assert_eq!(verdict(None, 0).to_string(), "draw");
+    }
+
+    #[test]
+    #[ignore = "needs the Syzygy fixtures in tests/fixtures/syzygy"]
+    fn mates_with_the_queen() {
+        let tablebase = fixtures();
+        let (winner, best_move, dtz) = probe(&tablebase, "k7/8/1K6/8/8/8/8/7Q w - - 0 1");
+        assert_eq!(winner, Some(PieceColor::White));
+        assert!(["Qh8", "Qb7"].contains(&best_move.as_str()), "{best_move}");
+        assert_eq!(dtz, 1);
+        let (winner, _, dtz) = probe(&tablebase, "k7/8/1KQ5/8/8/8/8/8 b - - 0 1");
+        assert_eq!(winner, Some(PieceColor::White));
+        assert!(dtz < 0, "{dtz}");
+    }
+
+    #[test]
+    #[ignore = "needs the Syzygy fixtures in tests/fixtures/syzygy"]
+    fn mates_with_the_rook() {
+        let tablebase = fixtures();
+        let (winner, best_move, dtz) = probe(&tablebase, "k7/8/1K6/8/8/8/8/7R w - - 0 1");
+        assert_eq!(
+            (winner, best_move.as_str(), dtz),
+            (Some(PieceColor::White), "Rh8", 1)
+        );
+    }
+
+    #[test]
+    #[ignore = "needs the Syzygy fixtures in tests/fixtures/syzygy"]
+    fn promotes_the_pawn_unless_the_corner_holds_it() {
+        let tablebase = fixtures();
+        let (winner, best_move, dtz) = probe(&tablebase, "8/4P3/8/8/8/8/k7/4K3 w - - 0 1");
+        assert_eq!(winner, Some(PieceColor::White));
+        assert!(best_move.starts_with("e8="), "{best_move}");
+        assert_eq!(dtz, 1);
+        let (winner, _, dtz) = probe(&tablebase, "k7/8/8/8/8/8/P7/7K w - - 0 1");
+        assert_eq!((winner, dtz), (None, 0));
+    }
+}
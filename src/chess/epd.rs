@@ -0,0 +1,264 @@
+use std::{fs, sync::atomic::AtomicBool, time::Duration};
+
+use super::{
+    engine::search::{search, SearchLimits},
+    game::{board::Board, chess_move::Move, parse_san, san},
+};
+
+/// The time spent on each position when neither a time nor a depth is given.
+const DEFAULT_TIME: Duration = Duration::from_secs(1);
+
+/// How the runner is used, shown when it is started with arguments it does not understand.
+const USAGE: &str = "usage: terminal-games epd <file> [--time <milliseconds>] [--depth <plies>]";
+
+/// A position of a test suite in Extended Position Description, with the moves the engine should
+/// or should not find in it.
+pub struct EpdPosition {
+    /// The name of the position, from its `id` operation, if it has one.
+    pub id: Option<String>,
+    pub board: Board,
+    /// The best moves, one of which must be played to solve the position.
+    pub best_moves: Vec<Move>,
+    /// The moves to avoid, none of which may be played to solve the position.
+    pub avoid_moves: Vec<Move>,
+}
+
+impl EpdPosition {
+    /// Parses a line of an EPD file: the first four fields of a FEN, followed by operations ending in semicolons,
+    /// such as `bm Qg6; id "WAC.001";`. Operations other than `bm`, `am` and `id` are ignored.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut fields = Vec::new();
+        let mut operations = line.trim_start();
+        while fields.len() < 4 && !operations.is_empty() {
+            let end = operations
+                .find(char::is_whitespace)
+                .unwrap_or(operations.len());
+            fields.push(&operations[..end]);
+            operations = operations[end..].trim_start();
+        }
+        if fields.len() < 4 {
+            return Err(String::from("a position needs four fields"));
+        }
+        let board = Board::from_fen(&fields.join(" "))?;
+        let mut position = Self {
+            id: None,
+            board,
+            best_moves: Vec::new(),
+            avoid_moves: Vec::new(),
+        };
+        for operation in split_operations(operations) {
+            let (opcode, operands) = operation.split_once(' ').unwrap_or((operation, ""));
+            match opcode {
+                "bm" | "am" => {
+                    let moves = operands
+                        .split_whitespace()
+                        .map(|text| {
+                            parse_san(&board, text)
+                                .ok_or_else(|| format!("'{text}' is not a legal move"))
+                        })
+                        .collect::<Result<Vec<Move>, String>>()?;
+                    match opcode {
+                        "bm" => position.best_moves.extend(moves),
+                        _ => position.avoid_moves.extend(moves),
+                    }
+                }
+                "id" => position.id = Some(operands.trim_matches('"').to_owned()),
+                _ => {}
+            }
+        }
+        if position.best_moves.is_empty() && position.avoid_moves.is_empty() {
+            return Err(String::from(
+                "the position has neither a bm nor an am operation",
+            ));
+        }
+        Ok(position)
+    }
+
+    /// Whether the move solves the position: it is one of the best moves, if there are any,
+    /// and none of the moves to avoid.
+    pub fn is_solved_by(&self, mv: &Move) -> bool {
+        (self.best_moves.is_empty() || self.best_moves.contains(mv))
+            && !self.avoid_moves.contains(mv)
+    }
+}
+
+/// Splits the operations of an EPD line at their semicolons, leaving the ones inside quoted strings alone.
+fn split_operations(text: &str) -> Vec<&str> {
+    let mut operations = Vec::new();
+    let mut start = 0;
+    let mut is_quoted = false;
+    for (index, symbol) in text.char_indices() {
+        match symbol {
+            '"' => is_quoted = !is_quoted,
+            ';' if !is_quoted => {
+                operations.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    operations.push(text[start..].trim());
+    operations.retain(|operation| !operation.is_empty());
+    operations
+}
+
+/// Runs the test suite in the EPD file named by the arguments, searching each position within the given time
+/// or to the given depth, and prints whether each was solved, followed by how many were solved in all,
+/// with the nodes searched and the time taken. The nodes and the time are the ones it took to complete the deepest depth,
+/// as the search throws away the depth it was in the middle of when the time ran out.
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut path = None;
+    let mut time = None;
+    let mut depth = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--time" => {
+                let value = args.next().ok_or(USAGE)?;
+                let millis = value
+                    .parse()
+                    .map_err(|_| format!("'{value}' is not a number of milliseconds"))?;
+                time = Some(Duration::from_millis(millis));
+            }
+            "--depth" => {
+                let value = args.next().ok_or(USAGE)?;
+                depth = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|depth| *depth > 0)
+                        .ok_or_else(|| format!("'{value}' is not a depth"))?,
+                );
+            }
+            other if path.is_none() && !other.starts_with("--") => path = Some(other),
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+    let path = path.ok_or(USAGE)?;
+    let text =
+        fs::read_to_string(path).map_err(|error| format!("could not read {path}: {error}"))?;
+    let limits = match (time, depth) {
+        (None, None) => SearchLimits::time(DEFAULT_TIME),
+        (time, depth) => SearchLimits {
+            time,
+            depth: depth.unwrap_or(SearchLimits::infinite().depth),
+            nodes: None,
+        },
+    };
+
+    let (mut solved, mut total, mut nodes, mut elapsed) = (0, 0, 0, Duration::ZERO);
+    let stop = AtomicBool::new(false);
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let position = match EpdPosition::parse(line) {
+            Ok(position) => position,
+            Err(error) => {
+                println!("line {}: skipped, {error}", number + 1);
+                continue;
+            }
+        };
+        let name = position
+            .id
+            .clone()
+            .unwrap_or_else(|| format!("line {}", number + 1));
+        total += 1;
+        let Some(info) = search(&position.board, &[], limits, &stop, |_| {}) else {
+            println!("{name}: the game is already over");
+            continue;
+        };
+        nodes += info.nodes;
+        elapsed += info.elapsed;
+        let found = info.pv.first();
+        let is_solved = found.is_some_and(|mv| position.is_solved_by(mv));
+        if is_solved {
+            solved += 1;
+        }
+        let expected: Vec<String> = [("bm", &position.best_moves), ("am", &position.avoid_moves)]
+            .into_iter()
+            .filter(|(_, moves)| !moves.is_empty())
+            .map(|(opcode, moves)| {
+                let moves: Vec<String> = moves.iter().map(|mv| san(&position.board, mv)).collect();
+                format!("{opcode} {}", moves.join(" "))
+            })
+            .collect();
+        println!(
+            "{name}: {} · {} · found {} · depth {} · {} nodes · {:.2}s",
+            if is_solved { "solved" } else { "failed" },
+            expected.join(" · "),
+            found
+                .map(|mv| san(&position.board, mv))
+                .unwrap_or_else(|| String::from("nothing")),
+            info.depth,
+            info.nodes,
+            info.elapsed.as_secs_f64()
+        );
+    }
+
+    let nodes_per_second = match elapsed.as_secs_f64() {
+        seconds if seconds > 0.0 => (nodes as f64 / seconds).round() as u64,
+        _ => 0,
+    };
+    println!(
+        "Solved {solved}/{total} · {nodes} nodes · {:.2}s · {nodes_per_second} nodes/s",
+        elapsed.as_secs_f64()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The move in standard algebraic notation in the position, which must be legal.
+    fn mv(position: &EpdPosition, text: &str) -> Move {
+        parse_san(&position.board, text).unwrap()
+    }
+
+    #[test]
+    fn reads_the_best_moves_and_the_name() {
+        let position = EpdPosition::parse(
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PP3PP1/R4RK1 w - - bm Qg6; id \"WAC.001\";",
+        )
+        .unwrap();
+        assert_eq!(position.id.as_deref(), Some("WAC.001"));
+        assert_eq!(
+            position.board.to_fen(),
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PP3PP1/R4RK1 w - - 0 1"
+        );
+        assert!(position.avoid_moves.is_empty());
+        assert!(position.is_solved_by(&mv(&position, "Qg6")));
+        assert!(!position.is_solved_by(&mv(&position, "Qh4")));
+    }
+
+    #[test]
+    fn reads_the_moves_to_avoid_and_skips_other_operations() {
+        let position = EpdPosition::parse(
+            "4k3/8/8/8/8/8/4P3/4K3 w - -  am e3 Kd1;c0 \"a comment; with a semicolon\"; id \"pawn\"",
+        )
+        .unwrap();
+        assert_eq!(position.id.as_deref(), Some("pawn"));
+        assert_eq!(position.avoid_moves.len(), 2);
+        assert!(position.best_moves.is_empty());
+        assert!(position.is_solved_by(&mv(&position, "e4")));
+        assert!(!position.is_solved_by(&mv(&position, "e3")));
+        assert!(!position.is_solved_by(&mv(&position, "Kd1")));
+    }
+
+    #[test]
+    fn refuses_a_position_it_cannot_test() {
+        for line in [
+            "4k3/8/8/8/8/8/4P3/4K3 w -",
+            "4k3/8/8/8/8/8/4P3/4K3 w - - id \"no moves\";",
+            "4k3/8/8/8/8/8/4P3/4K3 w - - bm e5;",
+            "4k3/8/8/8/8/8/4P3/4K3 x - - bm e4;",
+        ] {
+            assert!(
+                EpdPosition::parse(line).is_err(),
+                "'{line}' was read as a position"
+            );
+        }
+    }
+}
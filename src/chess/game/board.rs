@@ -1,67 +1,869 @@
-use tui::{text::Line, widgets::Widget};
+use std::fmt;
 
-use super::piece::Piece;
-const CHESS_BOARD_SIZE: usize = 8;
+use super::{
+    chess_move::{CastleSide, Move, MoveKind},
+    piece::{Piece, PieceColor, PieceType},
+    variant::Variant,
+};
+pub const CHESS_BOARD_SIZE: usize = 8;
 
-#[derive(Clone)]
+const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+
+const KING_OFFSETS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+
+/// The number of distinct Chess960 starting positions.
+pub const CHESS960_POSITIONS: u16 = 960;
+
+/// The placements of the two knights among the five squares left after the bishops and queen, in Scharnagl order.
+const CHESS960_KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/// The order of the pieces on the back rank in the standard starting position.
+const BACK_RANK: [PieceType; CHESS_BOARD_SIZE] = [
+    PieceType::Rook,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Queen,
+    PieceType::King,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Rook,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Board {
-    /// Represents the chess board, as a 2D, 8 x 8 array.
+    /// Represents the chess board, as a 2D, 8 x 8 array, indexed by rank and then file.
     board: [[Option<Piece>; CHESS_BOARD_SIZE]; CHESS_BOARD_SIZE],
+    /// The color of the side to move.
+    turn: PieceColor,
+    castling: CastlingRights,
+    /// The square a pawn skipped over with a double push on the previous move.
+    en_passant: Option<Position>,
+    /// The number of half moves since the last capture or pawn move, used for the fifty-move rule.
+    halfmove_clock: u16,
+    /// The number of the full move, starting at 1 and incremented after black moves.
+    fullmove_number: u16,
+    /// The rules the position is played by.
+    variant: Variant,
+    /// The squares of pieces which were promoted from pawns in Crazyhouse, one bit per square from a1 to h8.
+    promoted: u64,
 }
 
 impl Board {
+    /// Constructs a [`Board`] in the standard starting position.
     pub fn new() -> Self {
+        Self::from_back_rank(BACK_RANK)
+    }
+
+    /// Constructs a [`Board`] in one of the 960 Chess960 starting positions, numbered as in the Scharnagl scheme.
+    ///
+    /// The provided index must be within the range 0-959, otherwise the function panics.
+    pub fn chess960(index: u16) -> Self {
+        assert!(
+            index < CHESS960_POSITIONS,
+            "there are only 960 chess960 positions"
+        );
+        let mut back_rank: [Option<PieceType>; CHESS_BOARD_SIZE] = [None; CHESS_BOARD_SIZE];
+        let mut index = index as usize;
+
+        // the bishops go on a light and a dark square respectively, in any of the four of each
+        back_rank[(index % 4) * 2 + 1] = Some(PieceType::Bishop);
+        index /= 4;
+        back_rank[(index % 4) * 2] = Some(PieceType::Bishop);
+        index /= 4;
+
+        // the rest of the pieces fill the remaining empty squares, counted from the a-file
+        let mut place = |nth: usize, variant: PieceType| {
+            let file = (0..CHESS_BOARD_SIZE)
+                .filter(|&file| back_rank[file].is_none())
+                .nth(nth)
+                .expect("should always have an empty square left");
+            back_rank[file] = Some(variant);
+        };
+        place(index % 6, PieceType::Queen);
+        index /= 6;
+        let (first, second) = CHESS960_KNIGHTS[index];
+        // the second knight is counted before the first is placed
+        place(second, PieceType::Knight);
+        place(first, PieceType::Knight);
+        place(0, PieceType::Rook);
+        place(0, PieceType::King);
+        place(0, PieceType::Rook);
+
+        Self::from_back_rank(
+            back_rank.map(|variant| variant.expect("should always fill every square")),
+        )
+    }
+
+    /// Constructs a starting position with the given pieces on the back ranks, and full castling rights with the outermost rooks.
+    fn from_back_rank(back_rank: [PieceType; CHESS_BOARD_SIZE]) -> Self {
+        let mut board = Self::empty();
+        for (file, variant) in back_rank.into_iter().enumerate() {
+            let file = file as u8;
+            board.set_piece(
+                Position::new(file, 0),
+                Some(Piece::new(PieceColor::White, variant)),
+            );
+            board.set_piece(
+                Position::new(file, 1),
+                Some(Piece::new(PieceColor::White, PieceType::Pawn)),
+            );
+            board.set_piece(
+                Position::new(file, 6),
+                Some(Piece::new(PieceColor::Black, PieceType::Pawn)),
+            );
+            board.set_piece(
+                Position::new(file, 7),
+                Some(Piece::new(PieceColor::Black, variant)),
+            );
+        }
+        for color in [PieceColor::White, PieceColor::Black] {
+            for side in [CastleSide::King, CastleSide::Queen] {
+                let rook = board.outermost_rook(color, side);
+                board.castling.set(color, side, rook);
+            }
+        }
+        board
+    }
+
+    /// Constructs a [`Board`] without any pieces, castling rights or en passant square.
+    pub fn empty() -> Self {
         Self {
             board: [[None; CHESS_BOARD_SIZE]; CHESS_BOARD_SIZE],
+            turn: PieceColor::White,
+            castling: CastlingRights::none(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            variant: Variant::Standard,
+            promoted: 0,
+        }
+    }
+
+    pub fn piece_at(&self, position: Position) -> Option<Piece> {
+        self.board[position.rank() as usize][position.file() as usize]
+    }
+
+    pub fn set_piece(&mut self, position: Position, piece: Option<Piece>) {
+        self.board[position.rank() as usize][position.file() as usize] = piece;
+    }
+
+    pub fn turn(&self) -> PieceColor {
+        self.turn
+    }
+
+    pub fn castling(&self) -> CastlingRights {
+        self.castling
+    }
+
+    pub fn en_passant(&self) -> Option<Position> {
+        self.en_passant
+    }
+
+    pub fn set_turn(&mut self, turn: PieceColor) {
+        self.turn = turn;
+    }
+
+    pub fn set_castling(&mut self, castling: CastlingRights) {
+        self.castling = castling;
+    }
+
+    pub fn set_en_passant(&mut self, en_passant: Option<Position>) {
+        self.en_passant = en_passant;
+    }
+
+    pub fn set_move_counters(&mut self, halfmove_clock: u16, fullmove_number: u16) {
+        self.halfmove_clock = halfmove_clock;
+        self.fullmove_number = fullmove_number;
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+
+    /// Whether the piece on the square was promoted from a pawn in Crazyhouse, and so returns to the pocket as a pawn.
+    pub fn is_promoted(&self, position: Position) -> bool {
+        self.promoted & position.bit() != 0
+    }
+
+    pub fn halfmove_clock(&self) -> u16 {
+        self.halfmove_clock
+    }
+
+    pub fn fullmove_number(&self) -> u16 {
+        self.fullmove_number
+    }
+
+    /// Iterates over every occupied square, from a1 to h8.
+    pub fn pieces(&self) -> impl Iterator<Item = (Position, Piece)> + '_ {
+        Position::all()
+            .filter_map(|position| self.piece_at(position).map(|piece| (position, piece)))
+    }
+
+    pub fn king_position(&self, color: PieceColor) -> Option<Position> {
+        self.pieces()
+            .find(|(_, piece)| piece.color() == color && piece.variant() == PieceType::King)
+            .map(|(position, _)| position)
+    }
+
+    /// The file of the rook furthest from the king on the given side of the back rank, if any.
+    pub fn outermost_rook(&self, color: PieceColor, side: CastleSide) -> Option<u8> {
+        let king = self
+            .king_position(color)
+            .filter(|king| king.rank() == color.back_rank())?;
+        let rook = Piece::new(color, PieceType::Rook);
+        let is_rook =
+            |file: &u8| self.piece_at(Position::new(*file, color.back_rank())) == Some(rook);
+        match side {
+            CastleSide::King => (king.file() + 1..CHESS_BOARD_SIZE as u8)
+                .rev()
+                .find(is_rook),
+            CastleSide::Queen => (0..king.file()).find(is_rook),
+        }
+    }
+
+    /// The square of the rook a castling move is made with.
+    pub fn castling_rook(&self, mv: &Move) -> Option<Position> {
+        let MoveKind::Castle(side) = mv.kind else {
+            return None;
+        };
+        let color = mv.piece.color();
+        self.castling
+            .get(color, side)
+            .map(|file| Position::new(file, color.back_rank()))
+    }
+
+    /// Whether the side to move has its king under attack.
+    pub fn in_check(&self) -> bool {
+        self.is_king_attacked(self.turn)
+    }
+
+    fn is_king_attacked(&self, color: PieceColor) -> bool {
+        let Some(king) = self.king_position(color) else {
+            return false;
+        };
+        // in atomic chess, touching kings cannot be captured, as the capture would explode the capturing king as well
+        if self.variant == Variant::Atomic
+            && KING_OFFSETS.iter().any(|&(file, rank)| {
+                king.offset(file, rank)
+                    .and_then(|position| self.piece_at(position))
+                    == Some(Piece::new(color.opposite(), PieceType::King))
+            })
+        {
+            return false;
+        }
+        self.is_attacked(king, color.opposite())
+    }
+
+    /// Whether any piece of the given color attacks the square.
+    pub fn is_attacked(&self, target: Position, by: PieceColor) -> bool {
+        let is = |position: Option<Position>, variants: &[PieceType]| {
+            position
+                .and_then(|position| self.piece_at(position))
+                .map(|piece| piece.color() == by && variants.contains(&piece.variant()))
+                .unwrap_or(false)
+        };
+
+        let pawn_rank = -by.pawn_direction();
+        if is(target.offset(-1, pawn_rank), &[PieceType::Pawn])
+            || is(target.offset(1, pawn_rank), &[PieceType::Pawn])
+        {
+            return true;
+        }
+
+        if KNIGHT_OFFSETS
+            .iter()
+            .any(|&(file, rank)| is(target.offset(file, rank), &[PieceType::Knight]))
+        {
+            return true;
+        }
+
+        if KING_OFFSETS
+            .iter()
+            .any(|&(file, rank)| is(target.offset(file, rank), &[PieceType::King]))
+        {
+            return true;
+        }
+
+        let sliders = [
+            (ROOK_DIRECTIONS, PieceType::Rook),
+            (BISHOP_DIRECTIONS, PieceType::Bishop),
+        ];
+        sliders.iter().any(|(directions, variant)| {
+            directions.iter().any(|&direction| {
+                self.first_piece_in_direction(target, direction)
+                    .map(|piece| {
+                        piece.color() == by
+                            && (piece.variant() == *variant || piece.variant() == PieceType::Queen)
+                    })
+                    .unwrap_or(false)
+            })
+        })
+    }
+
+    fn first_piece_in_direction(&self, from: Position, (file, rank): (i8, i8)) -> Option<Piece> {
+        let mut current = from.offset(file, rank);
+        while let Some(position) = current {
+            if let Some(piece) = self.piece_at(position) {
+                return Some(piece);
+            }
+            current = position.offset(file, rank);
+        }
+        None
+    }
+
+    /// Every legal move available to the side to move.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for (position, piece) in self.pieces() {
+            if piece.color() == self.turn {
+                self.pseudo_legal_moves_from(position, piece, &mut moves);
+            }
+        }
+        moves.retain(|mv| self.is_legal(mv));
+        moves
+    }
+
+    /// Every legal move of the piece on the given square; empty if the piece does not belong to the side to move.
+    pub fn legal_moves_from(&self, from: Position) -> Vec<Move> {
+        let mut moves = Vec::new();
+        if let Some(piece) = self
+            .piece_at(from)
+            .filter(|piece| piece.color() == self.turn)
+        {
+            self.pseudo_legal_moves_from(from, piece, &mut moves);
+        }
+        moves.retain(|mv| self.is_legal(mv));
+        moves
+    }
+
+    /// The moves the piece on the given square could make once the opponent has moved, going by the way it moves
+    /// alone: any piece in its way may move off, its own pieces may be taken on the squares they stand on, and
+    /// the checks and pins of the position may be gone. Whether one of them is legal is known once it is played.
+    ///
+    /// The castling moves come first, so that moving the king onto its own rook castles rather than steps there.
+    pub fn premoves_from(&self, from: Position) -> Vec<Move> {
+        let Some(piece) = self.piece_at(from) else {
+            return Vec::new();
+        };
+        let color = piece.color();
+        let to = |to: Position, kind: MoveKind| Move {
+            piece,
+            from,
+            to,
+            captured: None,
+            promotion: None,
+            kind,
+        };
+        let mut moves = Vec::new();
+        let (directions, reach): (&[(i8, i8)], i8) = match piece.variant() {
+            PieceType::Pawn => {
+                let direction = color.pawn_direction();
+                let start_rank = match color {
+                    PieceColor::White => 1,
+                    PieceColor::Black => 6,
+                };
+                let mut steps = vec![(0, direction, MoveKind::Normal)];
+                if from.rank() == start_rank {
+                    steps.push((0, direction * 2, MoveKind::DoublePawnPush));
+                }
+                steps.extend([-1, 1].map(|file| (file, direction, MoveKind::Normal)));
+                for (file, rank, kind) in steps {
+                    if let Some(square) = from.offset(file, rank) {
+                        let mv = to(square, kind);
+                        moves.push(match square.rank() == color.opposite().back_rank() {
+                            true => mv.with_promotion(PieceType::Queen),
+                            false => mv,
+                        });
+                    }
+                }
+                return moves;
+            }
+            PieceType::Knight => (&KNIGHT_OFFSETS, 1),
+            PieceType::Bishop => (&BISHOP_DIRECTIONS, 7),
+            PieceType::Rook => (&ROOK_DIRECTIONS, 7),
+            PieceType::Queen => (&KING_OFFSETS, 7),
+            PieceType::King => {
+                if from.rank() == color.back_rank() {
+                    for side in [CastleSide::King, CastleSide::Queen] {
+                        if self.castling.get(color, side).is_some() {
+                            let square = Position::new(side.king_file(), from.rank());
+                            moves.push(to(square, MoveKind::Castle(side)));
+                        }
+                    }
+                }
+                (&KING_OFFSETS, 1)
+            }
+        };
+        for &(file, rank) in directions {
+            for distance in 1..=reach {
+                let Some(square) = from.offset(file * distance, rank * distance) else {
+                    break;
+                };
+                moves.push(to(square, MoveKind::Normal));
+            }
+        }
+        moves
+    }
+
+    /// A pseudo-legal move is legal if it does not leave the mover's own king in check.
+    ///
+    /// In atomic chess a move is also illegal if it explodes the mover's own king, while exploding
+    /// the opposing king wins the game, and so is legal regardless of check.
+    pub fn is_legal(&self, mv: &Move) -> bool {
+        let mut after = *self;
+        after.apply(mv);
+        if self.variant == Variant::Atomic {
+            if after.king_position(self.turn).is_none() {
+                return false;
+            }
+            if after.king_position(self.turn.opposite()).is_none() {
+                return true;
+            }
+        }
+        !after.is_king_attacked(self.turn)
+    }
+
+    fn pseudo_legal_moves_from(&self, from: Position, piece: Piece, moves: &mut Vec<Move>) {
+        match piece.variant() {
+            PieceType::Pawn => self.pawn_moves(from, piece, moves),
+            PieceType::Knight => self.step_moves(from, piece, &KNIGHT_OFFSETS, moves),
+            PieceType::Bishop => self.slide_moves(from, piece, &BISHOP_DIRECTIONS, moves),
+            PieceType::Rook => self.slide_moves(from, piece, &ROOK_DIRECTIONS, moves),
+            PieceType::Queen => {
+                self.slide_moves(from, piece, &BISHOP_DIRECTIONS, moves);
+                self.slide_moves(from, piece, &ROOK_DIRECTIONS, moves);
+            }
+            PieceType::King => {
+                let mut steps = Vec::new();
+                self.step_moves(from, piece, &KING_OFFSETS, &mut steps);
+                // an atomic king would explode along with whatever it captured
+                if self.variant == Variant::Atomic {
+                    steps.retain(|mv| mv.captured.is_none());
+                }
+                moves.extend(steps);
+                self.castling_moves(from, piece, moves);
+            }
+        }
+    }
+
+    fn pawn_moves(&self, from: Position, piece: Piece, moves: &mut Vec<Move>) {
+        let direction = piece.color().pawn_direction();
+        let start_rank = match piece.color() {
+            PieceColor::White => 1,
+            PieceColor::Black => 6,
+        };
+        let last_rank = piece.color().opposite().back_rank();
+
+        let mut push = |to: Position, captured: Option<Piece>, kind: MoveKind| {
+            let mv = Move {
+                piece,
+                from,
+                to,
+                captured,
+                promotion: None,
+                kind,
+            };
+            if to.rank() == last_rank {
+                moves.extend(PieceType::PROMOTIONS.map(|variant| mv.with_promotion(variant)));
+            } else {
+                moves.push(mv);
+            }
+        };
+
+        if let Some(one) = from
+            .offset(0, direction)
+            .filter(|to| self.piece_at(*to).is_none())
+        {
+            push(one, None, MoveKind::Normal);
+            if from.rank() == start_rank {
+                if let Some(two) = one
+                    .offset(0, direction)
+                    .filter(|to| self.piece_at(*to).is_none())
+                {
+                    push(two, None, MoveKind::DoublePawnPush);
+                }
+            }
+        }
+
+        for file in [-1, 1] {
+            let Some(to) = from.offset(file, direction) else {
+                continue;
+            };
+            match self.piece_at(to) {
+                Some(target) if target.color() != piece.color() => {
+                    push(to, Some(target), MoveKind::Normal);
+                }
+                None if self.en_passant == Some(to) => {
+                    let captured = Piece::new(piece.color().opposite(), PieceType::Pawn);
+                    push(to, Some(captured), MoveKind::EnPassant);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn step_moves(
+        &self,
+        from: Position,
+        piece: Piece,
+        offsets: &[(i8, i8)],
+        moves: &mut Vec<Move>,
+    ) {
+        for &(file, rank) in offsets {
+            let Some(to) = from.offset(file, rank) else {
+                continue;
+            };
+            let captured = self.piece_at(to);
+            if captured
+                .map(|target| target.color() != piece.color())
+                .unwrap_or(true)
+            {
+                moves.push(Move {
+                    piece,
+                    from,
+                    to,
+                    captured,
+                    promotion: None,
+                    kind: MoveKind::Normal,
+                });
+            }
         }
     }
 
-    fn rows(self) -> Vec<String> {
-        vec![
-            String::from("  ┌───┬───┬───┬───┬───┬───┬───┬───┐"),
-            String::from("8 │ ♖ │ ♘ │ ♗ │ ♕ │ ♔ │ ♗ │ ♘ │ ♖ │"),
-            String::from("  ├───┼───┼───┼───┼───┼───┼───┼───┤"),
-            String::from("7 │ ♙ │ ♙ │ ♙ │ ♙ │ ♙ │ ♙ │ ♙ │ ♙ │"),
-            String::from("  ├───┼───┼───┼───┼───┼───┼───┼───┤"),
-            String::from("6 │   │   │   │   │   │   │   │   │"),
-            String::from("  ├───┼───┼───┼───┼───┼───┼───┼───┤"),
-            String::from("5 │   │   │   │   │   │   │   │   │"),
-            String::from("  ├───┼───┼───┼───┼───┼───┼───┼───┤"),
-            String::from("4 │   │   │   │   │ . │   │   │   │"),
-            String::from("  ├───┼───┼───┼───┼───┼───┼───┼───┤"),
-            String::from("3 │   │   │   │   │ . │   │   │   │"),
-            String::from("  ├───┼───┼───┼───┼───┼───┼───┼───┤"),
-            String::from("2 │ ♟ │ ♟ │ ♟ │ ♟ │ ♟ │ ♟ │ ♟ │ ♟ │"),
-            String::from("  ├───┼───┼───┼───┼───┼───┼───┼───┤"),
-            String::from("1 │ ♜ │ ♞ │ ♝ │ ♛ │ ♚ │ ♝ │ ♞ │ ♜ │"),
-            String::from("  └───┴───┴───┴───┴───┴───┴───┴───┘"),
-            String::from("    A   B   C   D   E   F   G   H  "),
-        ]
+    fn slide_moves(
+        &self,
+        from: Position,
+        piece: Piece,
+        directions: &[(i8, i8)],
+        moves: &mut Vec<Move>,
+    ) {
+        for &(file, rank) in directions {
+            let mut current = from.offset(file, rank);
+            while let Some(to) = current {
+                let captured = self.piece_at(to);
+                if captured
+                    .map(|target| target.color() == piece.color())
+                    .unwrap_or(false)
+                {
+                    break;
+                }
+                moves.push(Move {
+                    piece,
+                    from,
+                    to,
+                    captured,
+                    promotion: None,
+                    kind: MoveKind::Normal,
+                });
+                if captured.is_some() {
+                    break;
+                }
+                current = to.offset(file, rank);
+            }
+        }
+    }
+
+    /// Castling follows the Chess960 rules, of which standard chess is a special case: the king and the rook
+    /// end up on the same squares as in standard chess, wherever they start from.
+    fn castling_moves(&self, from: Position, piece: Piece, moves: &mut Vec<Move>) {
+        let color = piece.color();
+        let rank = color.back_rank();
+        if from.rank() != rank {
+            return;
+        }
+
+        for side in [CastleSide::King, CastleSide::Queen] {
+            let Some(rook_file) = self.castling.get(color, side) else {
+                continue;
+            };
+            let rook_from = Position::new(rook_file, rank);
+            let is_on_side = match side {
+                CastleSide::King => rook_file > from.file(),
+                CastleSide::Queen => rook_file < from.file(),
+            };
+            if !is_on_side || self.piece_at(rook_from) != Some(Piece::new(color, PieceType::Rook)) {
+                continue;
+            }
+
+            // both pieces are lifted off the board, as they may stand on each other's path or destination
+            let mut without = *self;
+            without.set_piece(from, None);
+            without.set_piece(rook_from, None);
+            let span = |start: u8, end: u8| start.min(end)..=start.max(end);
+            let (king_to, rook_to) = (side.king_file(), side.rook_file());
+            let path_is_empty = span(from.file(), king_to)
+                .chain(span(rook_file, rook_to))
+                .all(|file| without.piece_at(Position::new(file, rank)).is_none());
+            // the king may not start on, pass through or land on an attacked square
+            let path_is_safe = span(from.file(), king_to)
+                .all(|file| !without.is_attacked(Position::new(file, rank), color.opposite()));
+
+            if path_is_empty && path_is_safe {
+                moves.push(Move {
+                    piece,
+                    from,
+                    to: Position::new(king_to, rank),
+                    captured: None,
+                    promotion: None,
+                    kind: MoveKind::Castle(side),
+                });
+            }
+        }
+    }
+
+    /// Plays the move on the board without checking its legality.
+    pub fn apply(&mut self, mv: &Move) {
+        let color = mv.piece.color();
+        let explosion = self.explosion(mv);
+        self.set_piece(mv.from, None);
+        self.promoted &= !mv.from.bit();
+
+        match mv.kind {
+            MoveKind::EnPassant => self.set_piece(mv.captured_square(), None),
+            MoveKind::Castle(side) => {
+                let rank = color.back_rank();
+                if let Some(rook_file) = self.castling.get(color, side) {
+                    self.set_piece(Position::new(rook_file, rank), None);
+                    self.set_piece(
+                        Position::new(side.rook_file(), rank),
+                        Some(Piece::new(color, PieceType::Rook)),
+                    );
+                }
+            }
+            MoveKind::Normal | MoveKind::DoublePawnPush | MoveKind::Drop => {}
+        }
+
+        let placed = match mv.promotion {
+            Some(variant) => Piece::new(color, variant),
+            None => mv.piece,
+        };
+        self.set_piece(mv.to, Some(placed));
+        self.promoted &= !mv.to.bit();
+        if mv.is_promotion() && self.variant == Variant::Crazyhouse {
+            self.promoted |= mv.to.bit();
+        }
+
+        for (position, _) in &explosion {
+            self.set_piece(*position, None);
+            self.promoted &= !position.bit();
+        }
+
+        if mv.piece.variant() == PieceType::King {
+            self.castling.set(color, CastleSide::King, None);
+            self.castling.set(color, CastleSide::Queen, None);
+        }
+        // moving a castling rook away, or capturing it, forfeits castling to that side
+        let mut vacated = vec![mv.from, mv.to];
+        vacated.extend(explosion.iter().map(|(position, _)| *position));
+        for color in [PieceColor::White, PieceColor::Black] {
+            // an exploded king forfeits castling altogether
+            let has_king = explosion.is_empty() || self.king_position(color).is_some();
+            for side in [CastleSide::King, CastleSide::Queen] {
+                let rook = self
+                    .castling
+                    .get(color, side)
+                    .map(|file| Position::new(file, color.back_rank()));
+                if !has_king || rook.map(|rook| vacated.contains(&rook)).unwrap_or(false) {
+                    self.castling.set(color, side, None);
+                }
+            }
+        }
+
+        self.en_passant = match mv.kind {
+            MoveKind::DoublePawnPush => mv.from.offset(0, color.pawn_direction()),
+            _ => None,
+        };
+
+        if mv.piece.variant() == PieceType::Pawn || mv.captured.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if color == PieceColor::Black {
+            self.fullmove_number += 1;
+        }
+        self.turn = color.opposite();
+    }
+
+    /// Whether neither side has enough material left to deliver checkmate.
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut minors = Vec::new();
+        for (position, piece) in self.pieces() {
+            match piece.variant() {
+                PieceType::King => {}
+                PieceType::Bishop | PieceType::Knight => minors.push((position, piece)),
+                _ => return false,
+            }
+        }
+
+        match minors.as_slice() {
+            [] | [_] => true,
+            // bishops all on the same square color can never force mate
+            bishops => bishops.iter().all(|(position, piece)| {
+                piece.variant() == PieceType::Bishop
+                    && position.is_light() == bishops[0].0.is_light()
+            }),
+        }
+    }
+
+    /// Whether two boards describe the same position for the purposes of repetition, ignoring move counters.
+    pub fn is_same_position(&self, other: &Board) -> bool {
+        self.board == other.board
+            && self.turn == other.turn
+            && self.castling == other.castling
+            && self.en_passant == other.en_passant
+            && self.promoted == other.promoted
     }
 }
 
-impl Widget for Board {
-    fn render(self, area: tui::prelude::Rect, buf: &mut tui::prelude::Buffer) {
-        for (y, row) in (area.top()..area.bottom()).zip(self.rows()) {
-            buf.set_line(area.x, y, &Line::from(row), area.width);
+/// The castling moves each side is still entitled to, given that the path is clear and safe.
+///
+/// Each right holds the file of the rook castled with, as in Chess960 it need not start in the corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CastlingRights {
+    pub white_king_side: Option<u8>,
+    pub white_queen_side: Option<u8>,
+    pub black_king_side: Option<u8>,
+    pub black_queen_side: Option<u8>,
+}
+
+impl CastlingRights {
+    pub fn none() -> Self {
+        Self {
+            white_king_side: None,
+            white_queen_side: None,
+            black_king_side: None,
+            black_queen_side: None,
+        }
+    }
+
+    pub fn get(&self, color: PieceColor, side: CastleSide) -> Option<u8> {
+        match (color, side) {
+            (PieceColor::White, CastleSide::King) => self.white_king_side,
+            (PieceColor::White, CastleSide::Queen) => self.white_queen_side,
+            (PieceColor::Black, CastleSide::King) => self.black_king_side,
+            (PieceColor::Black, CastleSide::Queen) => self.black_queen_side,
+        }
+    }
+
+    pub fn set(&mut self, color: PieceColor, side: CastleSide, rook_file: Option<u8>) {
+        match (color, side) {
+            (PieceColor::White, CastleSide::King) => self.white_king_side = rook_file,
+            (PieceColor::White, CastleSide::Queen) => self.white_queen_side = rook_file,
+            (PieceColor::Black, CastleSide::King) => self.black_king_side = rook_file,
+            (PieceColor::Black, CastleSide::Queen) => self.black_queen_side = rook_file,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Position {
     letter: ChessLetter,
     number: ChessNumber,
 }
 
 impl Position {
-    fn new(letter: u8, number: u8) -> Self {
+    /// Constructs a [`Position`] from a file and rank, both within the range 0-7.
+    pub fn new(letter: u8, number: u8) -> Self {
         Self {
             letter: letter.into(),
             number: number.into(),
         }
     }
+
+    /// The file of the position, from 0 (A) to 7 (H).
+    pub fn file(&self) -> u8 {
+        self.letter as u8
+    }
+
+    /// The rank of the position, from 0 (1) to 7 (8).
+    pub fn rank(&self) -> u8 {
+        self.number as u8
+    }
+
+    /// Returns the position shifted by the given number of files and ranks, if it is still on the board.
+    pub fn offset(&self, file: i8, rank: i8) -> Option<Self> {
+        let file = self.file() as i8 + file;
+        let rank = self.rank() as i8 + rank;
+        let range = 0..CHESS_BOARD_SIZE as i8;
+        (range.contains(&file) && range.contains(&rank)).then(|| Self::new(file as u8, rank as u8))
+    }
+
+    /// The bit of the position in a set of squares, ordered from a1 to h8.
+    fn bit(&self) -> u64 {
+        1 << (self.rank() as usize * CHESS_BOARD_SIZE + self.file() as usize)
+    }
+
+    /// Whether the square is a light square.
+    pub fn is_light(&self) -> bool {
+        (self.file() + self.rank()) % 2 == 1
+    }
+
+    /// Iterates over every position on the board, from a1 to h8 rank by rank.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..CHESS_BOARD_SIZE as u8)
+            .flat_map(|rank| (0..CHESS_BOARD_SIZE as u8).map(move |file| Self::new(file, rank)))
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file = (b'a' + self.file()) as char;
+        write!(f, "{file}{}", self.rank() + 1)
+    }
+}
+
+impl std::str::FromStr for Position {
+    type Err = String;
+
+    /// Parses a square in algebraic notation, such as `e4`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Ok(Self::new(file - b'a', rank - b'1')),
+            _ => Err(format!("'{text}' is not a square on the board")),
+        }
+    }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum ChessLetter {
     A,
     B,
@@ -91,6 +893,7 @@ impl From<u8> for ChessLetter {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum ChessNumber {
     One,
     Two,
@@ -119,3 +922,110 @@ impl From<u8> for ChessNumber {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn perft(board: &Board, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        board
+            .legal_moves()
+            .iter()
+            .map(|mv| {
+                let mut after = *board;
+                after.apply(mv);
+                perft(&after, depth - 1)
+            })
+            .sum()
+    }
+
+    fn assert_perft(board: &Board, counts: &[u64]) {
+        for (depth, count) in (1..).zip(counts) {
+            assert_eq!(
+                perft(board, depth),
+                *count,
+                "depth {depth} of {}",
+                board.to_fen()
+            );
+        }
+    }
+
+    #[test]
+    fn counts_the_moves_of_the_standard_positions() {
+        assert_perft(&Board::new(), &[20, 400, 8902, 197_281]);
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        assert_perft(&Board::from_fen(kiwipete).unwrap(), &[48, 2039, 97_862]);
+        let endgame = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+        assert_perft(&Board::from_fen(endgame).unwrap(), &[14, 191, 2812, 43_238]);
+    }
+
+    #[test]
+    fn counts_the_moves_of_chess960_positions() {
+        // the standard position is one of the Chess960 positions, castling with the same rooks
+        assert!(Board::chess960(518).is_same_position(&Board::new()));
+        assert_perft(&Board::chess960(0), &[20, 400, 9006]);
+        assert_perft(&Board::chess960(959), &[20, 400, 9006]);
+        for (fen, counts) in [
+            (
+                "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+                [21, 528, 12_189],
+            ),
+            (
+                "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+                [21, 807, 18_002],
+            ),
+            (
+                "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+                [20, 479, 10_471],
+            ),
+        ] {
+            assert_perft(&Board::from_fen(fen).unwrap(), &counts);
+        }
+    }
+
+    #[test]
+    fn castles_onto_the_squares_of_standard_chess_in_chess960() {
+        // the king on b1 castles with the rook on a1, ending up on c1 and d1
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/RK2R3 w AE - 0 1").unwrap();
+        let castle = board
+            .legal_moves()
+            .into_iter()
+            .find(|mv| mv.kind == MoveKind::Castle(CastleSide::Queen))
+            .unwrap();
+        assert_eq!(board.castling_rook(&castle), Some("a1".parse().unwrap()));
+        board.apply(&castle);
+        assert_eq!(board.to_shredder_fen(), "4k3/8/8/8/8/8/8/2KRR3 b - - 1 1");
+    }
+
+    fn targets(moves: Vec<Move>) -> Vec<String> {
+        moves.iter().map(|mv| mv.to.to_string()).collect()
+    }
+
+    #[test]
+    fn premoves_onto_own_pieces_and_empty_capture_squares() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/4PP2/4K1NR b K - 0 1").unwrap();
+        let knight = targets(board.premoves_from("g1".parse().unwrap()));
+        assert!(knight.contains(&String::from("e2")), "{knight:?}");
+        let mut pawn = targets(board.premoves_from("e2".parse().unwrap()));
+        pawn.sort();
+        assert_eq!(pawn, ["d3", "e3", "e4", "f3"]);
+        // the rook is blocked by the knight for now, and the king may castle once it has moved
+        let rook = targets(board.premoves_from("h1".parse().unwrap()));
+        assert!(rook.contains(&String::from("a1")), "{rook:?}");
+        let king = board.premoves_from("e1".parse().unwrap());
+        assert_eq!(king[0].kind, MoveKind::Castle(CastleSide::King));
+    }
+
+    #[test]
+    fn premoves_promote_to_a_queen() {
+        let board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        let moves = board.premoves_from("b7".parse().unwrap());
+        assert_eq!(moves.len(), 3);
+        assert!(moves
+            .iter()
+            .all(|mv| mv.promotion == Some(PieceType::Queen)));
+    }
+}
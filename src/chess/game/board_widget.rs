@@ -0,0 +1,363 @@
+use tui::{
+    prelude::{Buffer, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::Widget,
+};
+
+use super::{
+    board::{Board, Position, CHESS_BOARD_SIZE},
+    chess_move::Move,
+    piece::{PieceColor, PieceSet, PieceType},
+};
+
+// the square colors are mid-tones, so that both white and black pieces stand out against them
+pub const LIGHT_SQUARE: Color = Color::Rgb(209, 166, 116);
+pub const DARK_SQUARE: Color = Color::Rgb(150, 104, 66);
+pub const LIGHT_LAST_MOVE: Color = Color::Rgb(190, 180, 80);
+pub const DARK_LAST_MOVE: Color = Color::Rgb(150, 140, 45);
+const SELECTED_SQUARE: Color = Color::Rgb(90, 120, 170);
+const LIGHT_TARGET: Color = Color::Rgb(140, 175, 105);
+const DARK_TARGET: Color = Color::Rgb(100, 140, 75);
+const CHECK_SQUARE: Color = Color::Rgb(200, 50, 50);
+const LIGHT_HINT: Color = Color::Rgb(110, 175, 190);
+const DARK_HINT: Color = Color::Rgb(75, 135, 150);
+const LIGHT_PREMOVE: Color = Color::Rgb(190, 130, 170);
+const DARK_PREMOVE: Color = Color::Rgb(150, 90, 130);
+const WHITE_PIECE: Color = Color::White;
+const BLACK_PIECE: Color = Color::Black;
+
+/// Renders a [`Board`] along with the interactive state of the player using it.
+pub struct BoardWidget<'a> {
+    board: &'a Board,
+    cursor: Option<Position>,
+    selected: Option<Position>,
+    targets: Vec<Position>,
+    last_move: Option<Move>,
+    /// The squares of the move suggested to the player.
+    hint: Vec<Position>,
+    /// The move queued to be played once it is the turn of its side.
+    premove: Option<Move>,
+    piece_set: PieceSet,
+    scale: BoardScale,
+    /// Signifies whether the board is seen from the side of black, with the eighth rank at the bottom.
+    flipped: bool,
+    /// Signifies whether only the squares and the cursor are drawn, leaving out the pieces and every highlight
+    /// that would give them away, as in blindfold games.
+    pieces_hidden: bool,
+}
+
+/// The sizes the board can be rendered at, so it can make the most of the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoardScale {
+    /// A single character per square, without any grid lines.
+    Compact,
+    /// Three characters per square, separated by grid lines.
+    #[default]
+    Normal,
+    /// Twice the size of [`BoardScale::Normal`] in both directions.
+    Large,
+}
+
+impl BoardScale {
+    /// Every scale, ordered from the largest to the smallest.
+    pub const LARGEST_FIRST: [BoardScale; 3] =
+        [BoardScale::Large, BoardScale::Normal, BoardScale::Compact];
+
+    fn square_width(self) -> u16 {
+        match self {
+            BoardScale::Compact => 1,
+            BoardScale::Normal => 3,
+            BoardScale::Large => 7,
+        }
+    }
+
+    fn square_height(self) -> u16 {
+        match self {
+            BoardScale::Compact | BoardScale::Normal => 1,
+            BoardScale::Large => 3,
+        }
+    }
+
+    /// The width of the grid lines between squares.
+    fn grid(self) -> u16 {
+        match self {
+            BoardScale::Compact => 0,
+            BoardScale::Normal | BoardScale::Large => 1,
+        }
+    }
+
+    /// The total width of the board in characters; includes the number markings.
+    pub fn width(self) -> u16 {
+        2 + self.grid() + CHESS_BOARD_SIZE as u16 * (self.square_width() + self.grid())
+    }
+
+    /// The total height of the board in characters; includes the letter markings.
+    pub fn height(self) -> u16 {
+        self.grid() + CHESS_BOARD_SIZE as u16 * (self.square_height() + self.grid()) + 1
+    }
+
+    /// The top left corner of the square, when the board is rendered in the given area.
+    fn origin(self, area: Rect, position: Position) -> (u16, u16) {
+        let x =
+            area.x + 2 + self.grid() + position.file() as u16 * (self.square_width() + self.grid());
+        let row = CHESS_BOARD_SIZE as u16 - 1 - position.rank() as u16;
+        let y = area.y + self.grid() + row * (self.square_height() + self.grid());
+        (x, y)
+    }
+}
+
+impl<'a> BoardWidget<'a> {
+    pub fn new(board: &'a Board) -> Self {
+        Self {
+            board,
+            cursor: None,
+            selected: None,
+            targets: Vec::new(),
+            last_move: None,
+            hint: Vec::new(),
+            premove: None,
+            piece_set: PieceSet::default(),
+            scale: BoardScale::default(),
+            flipped: false,
+            pieces_hidden: false,
+        }
+    }
+
+    pub fn cursor(mut self, cursor: Position) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    pub fn selected(mut self, selected: Option<Position>) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    pub fn targets(mut self, targets: Vec<Position>) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    pub fn last_move(mut self, last_move: Option<Move>) -> Self {
+        self.last_move = last_move;
+        self
+    }
+
+    pub fn hint(mut self, hint: Vec<Position>) -> Self {
+        self.hint = hint;
+        self
+    }
+
+    pub fn premove(mut self, premove: Option<Move>) -> Self {
+        self.premove = premove;
+        self
+    }
+
+    pub fn piece_set(mut self, piece_set: PieceSet) -> Self {
+        self.piece_set = piece_set;
+        self
+    }
+
+    pub fn scale(mut self, scale: BoardScale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn flipped(mut self, flipped: bool) -> Self {
+        self.flipped = flipped;
+        self
+    }
+
+    pub fn pieces_hidden(mut self, pieces_hidden: bool) -> Self {
+        self.pieces_hidden = pieces_hidden;
+        self
+    }
+
+    /// Maps a terminal cell to the square rendered there, if the board was rendered in the given area and scale,
+    /// and flipped or not.
+    pub fn square_at(
+        area: Rect,
+        scale: BoardScale,
+        flipped: bool,
+        x: u16,
+        y: u16,
+    ) -> Option<Position> {
+        let (step_x, step_y) = (
+            scale.square_width() + scale.grid(),
+            scale.square_height() + scale.grid(),
+        );
+        let column = x.checked_sub(area.x + 2 + scale.grid())?;
+        let line = y.checked_sub(area.y + scale.grid())?;
+        // clicks on the grid lines between squares do not belong to any square
+        if column % step_x >= scale.square_width() || line % step_y >= scale.square_height() {
+            return None;
+        }
+        let (file, row) = (column / step_x, line / step_y);
+        if file >= CHESS_BOARD_SIZE as u16 || row >= CHESS_BOARD_SIZE as u16 {
+            return None;
+        }
+        let position = Position::new(file as u8, CHESS_BOARD_SIZE as u8 - 1 - row as u8);
+        Some(turned(position, flipped))
+    }
+
+    /// The background of a square, in order of precedence: check, selection, legal target, hint, premove, last move
+    /// and finally the square color. With the pieces hidden, there is only the square color.
+    fn background(&self, position: Position) -> Color {
+        if self.pieces_hidden {
+            return match position.is_light() {
+                true => LIGHT_SQUARE,
+                false => DARK_SQUARE,
+            };
+        }
+        let is_checked_king = self.board.in_check()
+            && self
+                .board
+                .piece_at(position)
+                .map(|piece| {
+                    piece.variant() == PieceType::King && piece.color() == self.board.turn()
+                })
+                .unwrap_or(false);
+        let is_last_move = self
+            .last_move
+            .map(|mv| mv.from == position || mv.to == position)
+            .unwrap_or(false);
+
+        let is_target = self.targets.contains(&position);
+        let is_hint = self.hint.contains(&position);
+        let is_premove = self
+            .premove
+            .map(|mv| mv.from == position || mv.to == position)
+            .unwrap_or(false);
+
+        match position.is_light() {
+            _ if is_checked_king => CHECK_SQUARE,
+            _ if self.selected == Some(position) => SELECTED_SQUARE,
+            true if is_target => LIGHT_TARGET,
+            false if is_target => DARK_TARGET,
+            true if is_hint => LIGHT_HINT,
+            false if is_hint => DARK_HINT,
+            true if is_premove => LIGHT_PREMOVE,
+            false if is_premove => DARK_PREMOVE,
+            true if is_last_move => LIGHT_LAST_MOVE,
+            false if is_last_move => DARK_LAST_MOVE,
+            true => LIGHT_SQUARE,
+            false => DARK_SQUARE,
+        }
+    }
+
+    fn render_square(&self, position: Position, area: Rect, buf: &mut Buffer) {
+        let (width, height) = (self.scale.square_width(), self.scale.square_height());
+        let (x, y) = self.scale.origin(area, turned(position, self.flipped));
+        if x + width > area.right() || y + height > area.bottom() {
+            return;
+        }
+
+        let piece = match self.pieces_hidden {
+            true => None,
+            false => self.board.piece_at(position),
+        };
+        let symbol = match piece {
+            Some(piece) => self.piece_set.board_symbol(piece),
+            None if self.targets.contains(&position) => "·",
+            None => " ",
+        };
+        let foreground = match piece.map(|piece| piece.color()) {
+            Some(PieceColor::White) => WHITE_PIECE,
+            Some(PieceColor::Black) | None => BLACK_PIECE,
+        };
+        let mut style = Style::default()
+            .fg(foreground)
+            .bg(self.background(position))
+            .add_modifier(Modifier::BOLD);
+        if self.cursor == Some(position) {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+
+        for line in 0..height {
+            buf.set_string(x, y + line, " ".repeat(width as usize), style);
+        }
+        buf.set_string(x + width / 2, y + height / 2, symbol, style);
+    }
+
+    /// The lines of the board grid, along with the rank and file markings.
+    fn grid(&self) -> Vec<Line<'static>> {
+        let (width, height) = (
+            self.scale.square_width() as usize,
+            self.scale.square_height() as usize,
+        );
+        let has_grid = self.scale.grid() > 0;
+        let separator = |left: &str, middle: &str, right: &str| {
+            let inner = vec!["─".repeat(width); CHESS_BOARD_SIZE].join(middle);
+            Line::from(format!("  {left}{inner}{right}"))
+        };
+        let (edge, between) = match has_grid {
+            true => ("│", "│"),
+            false => ("", ""),
+        };
+
+        let mut lines = Vec::new();
+        if has_grid {
+            lines.push(separator("┌", "┬", "┐"));
+        }
+        let ranks: Vec<usize> = match self.flipped {
+            true => (1..=CHESS_BOARD_SIZE).collect(),
+            false => (1..=CHESS_BOARD_SIZE).rev().collect(),
+        };
+        for (row, rank) in ranks.into_iter().enumerate() {
+            for line in 0..height {
+                // the rank number sits on the middle line of the squares
+                let label = match line == height / 2 {
+                    true => rank.to_string(),
+                    false => String::from(" "),
+                };
+                let squares = format!("{}{between}", " ".repeat(width)).repeat(CHESS_BOARD_SIZE);
+                lines.push(Line::from(format!("{label} {edge}{squares}")));
+            }
+            if has_grid && row + 1 < CHESS_BOARD_SIZE {
+                lines.push(separator("├", "┼", "┤"));
+            }
+        }
+        if has_grid {
+            lines.push(separator("└", "┴", "┘"));
+        }
+
+        let grid = self.scale.grid() as usize;
+        let letters: Vec<char> = match self.flipped {
+            true => ('A'..='H').rev().collect(),
+            false => ('A'..='H').collect(),
+        };
+        let letters: String = letters
+            .into_iter()
+            .map(|letter| {
+                let padding = width / 2;
+                let trailing = width - padding - 1 + grid;
+                format!("{}{letter}{}", " ".repeat(padding), " ".repeat(trailing))
+            })
+            .collect();
+        lines.push(Line::from(format!("  {}{letters}", " ".repeat(grid))));
+        lines
+    }
+}
+
+/// The square shown in place of the position, which is the one across the center of the board on a flipped board.
+fn turned(position: Position, flipped: bool) -> Position {
+    match flipped {
+        true => Position::new(
+            CHESS_BOARD_SIZE as u8 - 1 - position.file(),
+            CHESS_BOARD_SIZE as u8 - 1 - position.rank(),
+        ),
+        false => position,
+    }
+}
+
+impl Widget for BoardWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for (y, line) in (area.top()..area.bottom()).zip(self.grid()) {
+            buf.set_line(area.x, y, &line, area.width);
+        }
+        for position in Position::all() {
+            self.render_square(position, area, buf);
+        }
+    }
+}
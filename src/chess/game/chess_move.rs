@@ -0,0 +1,86 @@
+use super::{
+    board::Position,
+    piece::{Piece, PieceType},
+};
+
+/// A single move, carrying enough information to be applied to and described from a [`super::board::Board`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    /// The piece being moved, before any promotion.
+    pub piece: Piece,
+    pub from: Position,
+    pub to: Position,
+    /// The piece removed from the board by this move, if any.
+    pub captured: Option<Piece>,
+    /// The piece a pawn is promoted to when reaching the last rank.
+    pub promotion: Option<PieceType>,
+    pub kind: MoveKind,
+}
+
+impl Move {
+    /// Whether the move is a pawn reaching the last rank, and so requires a promotion choice.
+    pub fn is_promotion(&self) -> bool {
+        self.promotion.is_some()
+    }
+
+    /// Returns a copy of the move promoting to the given piece instead.
+    pub fn with_promotion(mut self, variant: PieceType) -> Self {
+        self.promotion = Some(variant);
+        self
+    }
+
+    /// The square of the captured piece, which differs from the destination for en passant.
+    pub fn captured_square(&self) -> Position {
+        match self.kind {
+            MoveKind::EnPassant => Position::new(self.to.file(), self.from.rank()),
+            _ => self.to,
+        }
+    }
+
+    /// The move in UCI long algebraic notation, e.g. `e7e8q`, or `N@f3` for a drop.
+    pub fn uci(&self) -> String {
+        if self.kind == MoveKind::Drop {
+            return format!("{}@{}", self.piece.variant().letter(), self.to);
+        }
+        let mut text = format!("{}{}", self.from, self.to);
+        if let Some(promotion) = self.promotion {
+            text.push(promotion.letter().to_ascii_lowercase());
+        }
+        text
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveKind {
+    Normal,
+    /// A pawn advancing two squares from its starting rank.
+    DoublePawnPush,
+    EnPassant,
+    Castle(CastleSide),
+    /// A piece from the pocket placed onto an empty square, as in Crazyhouse; the move starts and ends on that square.
+    Drop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastleSide {
+    King,
+    Queen,
+}
+
+impl CastleSide {
+    /// The file the king ends up on after castling to this side.
+    pub fn king_file(self) -> u8 {
+        match self {
+            CastleSide::King => 6,
+            CastleSide::Queen => 2,
+        }
+    }
+
+    /// The file the rook ends up on after castling to this side.
+    pub fn rook_file(self) -> u8 {
+        match self {
+            CastleSide::King => 5,
+            CastleSide::Queen => 3,
+        }
+    }
+}
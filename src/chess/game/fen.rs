@@ -0,0 +1,264 @@
+use super::{
+    board::{Board, CastlingRights, Position, CHESS_BOARD_SIZE},
+    chess_move::CastleSide,
+    piece::{Piece, PieceColor, PieceType},
+};
+
+impl Board {
+    /// Parses a position in Forsyth-Edwards Notation.
+    ///
+    /// The castling field may use the standard `KQkq` letters, the file letters of Shredder-FEN,
+    /// or a mix of the two as in X-FEN, so that Chess960 positions can be described.
+    /// The move counters may be left out, in which case they default to the start of the game.
+    pub fn from_fen(fen: &str) -> Result<Self, String> {
+        let mut fields = fen.split_whitespace();
+        let mut board = Board::empty();
+
+        let placement = fields.next().ok_or("the FEN is empty")?;
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != CHESS_BOARD_SIZE {
+            return Err(format!(
+                "expected {CHESS_BOARD_SIZE} ranks, found {}",
+                ranks.len()
+            ));
+        }
+        for (row, pieces) in ranks.into_iter().enumerate() {
+            let rank = (CHESS_BOARD_SIZE - 1 - row) as u8;
+            let mut file = 0;
+            for symbol in pieces.chars() {
+                if let Some(skip) = symbol.to_digit(10) {
+                    file += skip as usize;
+                    continue;
+                }
+                if file >= CHESS_BOARD_SIZE {
+                    return Err(format!(
+                        "rank {} has more than {CHESS_BOARD_SIZE} files",
+                        rank + 1
+                    ));
+                }
+                let variant = PieceType::from_letter(symbol)
+                    .ok_or_else(|| format!("'{symbol}' is not a piece"))?;
+                let color = match symbol.is_ascii_uppercase() {
+                    true => PieceColor::White,
+                    false => PieceColor::Black,
+                };
+                board.set_piece(
+                    Position::new(file as u8, rank),
+                    Some(Piece::new(color, variant)),
+                );
+                file += 1;
+            }
+            if file != CHESS_BOARD_SIZE {
+                return Err(format!(
+                    "rank {} does not have {CHESS_BOARD_SIZE} files",
+                    rank + 1
+                ));
+            }
+        }
+
+        let turn = match fields.next().unwrap_or("w") {
+            "w" => PieceColor::White,
+            "b" => PieceColor::Black,
+            other => return Err(format!("'{other}' is not a side to move")),
+        };
+        board.set_turn(turn);
+
+        let castling = parse_castling(&board, fields.next().unwrap_or("-"))?;
+        board.set_castling(castling);
+
+        let en_passant = match fields.next().unwrap_or("-") {
+            "-" => None,
+            square => Some(square.parse()?),
+        };
+        board.set_en_passant(en_passant);
+
+        let halfmove_clock = fields
+            .next()
+            .map(|field| {
+                field
+                    .parse()
+                    .map_err(|_| format!("'{field}' is not a halfmove clock"))
+            })
+            .transpose()?
+            .unwrap_or(0);
+        let fullmove_number = fields
+            .next()
+            .map(|field| {
+                field
+                    .parse()
+                    .map_err(|_| format!("'{field}' is not a move number"))
+            })
+            .transpose()?
+            .unwrap_or(1);
+        board.set_move_counters(halfmove_clock, fullmove_number);
+
+        Ok(board)
+    }
+
+    /// Describes the position in X-FEN, which is standard FEN unless a castling rook is not the outermost one.
+    pub fn to_fen(self) -> String {
+        self.fen_with_castling(|board, color, side, file| {
+            match board.outermost_rook(color, side) == Some(file) {
+                true => match side {
+                    CastleSide::King => 'K',
+                    CastleSide::Queen => 'Q',
+                },
+                false => (b'A' + file) as char,
+            }
+        })
+    }
+
+    /// Describes the position in Shredder-FEN, which always names the files of the castling rooks.
+    pub fn to_shredder_fen(self) -> String {
+        self.fen_with_castling(|_, _, _, file| (b'A' + file) as char)
+    }
+
+    fn fen_with_castling(
+        &self,
+        castling_letter: impl Fn(&Board, PieceColor, CastleSide, u8) -> char,
+    ) -> String {
+        let mut ranks = Vec::new();
+        for rank in (0..CHESS_BOARD_SIZE as u8).rev() {
+            let mut text = String::new();
+            let mut empty = 0;
+            for file in 0..CHESS_BOARD_SIZE as u8 {
+                match self.piece_at(Position::new(file, rank)) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            text.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        let letter = piece.variant().letter();
+                        text.push(match piece.color() {
+                            PieceColor::White => letter,
+                            PieceColor::Black => letter.to_ascii_lowercase(),
+                        });
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                text.push_str(&empty.to_string());
+            }
+            ranks.push(text);
+        }
+
+        let turn = match self.turn() {
+            PieceColor::White => "w",
+            PieceColor::Black => "b",
+        };
+
+        let mut castling = String::new();
+        for color in [PieceColor::White, PieceColor::Black] {
+            for side in [CastleSide::King, CastleSide::Queen] {
+                if let Some(file) = self.castling().get(color, side) {
+                    let letter = castling_letter(self, color, side, file);
+                    castling.push(match color {
+                        PieceColor::White => letter,
+                        PieceColor::Black => letter.to_ascii_lowercase(),
+                    });
+                }
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = self
+            .en_passant()
+            .map(|position| position.to_string())
+            .unwrap_or_else(|| String::from("-"));
+
+        format!(
+            "{} {turn} {castling} {en_passant} {} {}",
+            ranks.join("/"),
+            self.halfmove_clock(),
+            self.fullmove_number()
+        )
+    }
+}
+
+fn parse_castling(board: &Board, field: &str) -> Result<CastlingRights, String> {
+    let mut castling = CastlingRights::none();
+    if field == "-" {
+        return Ok(castling);
+    }
+
+    for letter in field.chars() {
+        let color = match letter.is_ascii_uppercase() {
+            true => PieceColor::White,
+            false => PieceColor::Black,
+        };
+        let king = board
+            .king_position(color)
+            .filter(|king| king.rank() == color.back_rank())
+            .ok_or_else(|| format!("castling '{letter}' requires a king on the back rank"))?;
+
+        let (side, rook) = match letter.to_ascii_uppercase() {
+            'K' => (
+                CastleSide::King,
+                board.outermost_rook(color, CastleSide::King),
+            ),
+            'Q' => (
+                CastleSide::Queen,
+                board.outermost_rook(color, CastleSide::Queen),
+            ),
+            file @ 'A'..='H' => {
+                let file = file as u8 - b'A';
+                let side = match file > king.file() {
+                    true => CastleSide::King,
+                    false => CastleSide::Queen,
+                };
+                let rook = Piece::new(color, PieceType::Rook);
+                let is_rook = board.piece_at(Position::new(file, color.back_rank())) == Some(rook);
+                (side, Some(file).filter(|_| is_rook))
+            }
+            _ => return Err(format!("'{letter}' is not a castling right")),
+        };
+        let rook = rook.ok_or_else(|| format!("castling '{letter}' has no rook to castle with"))?;
+        castling.set(color, side, Some(rook));
+    }
+    Ok(castling)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+            "r3k2r/8/8/8/8/8/8/4K3 b kq - 12 40",
+            "8/8/8/4k3/8/8/8/4K3 w - - 0 1",
+        ] {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn names_the_castling_rooks_in_shredder_and_x_fen() {
+        let shredder = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+        let board = Board::from_fen(shredder).unwrap();
+        assert_eq!(board.to_shredder_fen(), shredder);
+        // the castling rooks are the outermost ones, so X-FEN names them like standard FEN
+        let x_fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9";
+        assert_eq!(board.to_fen(), x_fen);
+        assert_eq!(Board::from_fen(x_fen).unwrap(), board);
+
+        // with a second rook outside it on the same side, the castling rook is named by its file
+        let inner = Board::from_fen("4k3/8/8/8/8/8/8/R1R1K3 w C - 0 1").unwrap();
+        assert_eq!(inner.castling().white_queen_side, Some(2));
+        assert_eq!(inner.to_fen(), "4k3/8/8/8/8/8/8/R1R1K3 w C - 0 1");
+    }
+
+    #[test]
+    fn defaults_the_move_counters_and_rejects_broken_positions() {
+        let board = Board::from_fen("8/8/8/4k3/8/8/8/4K3 w -").unwrap();
+        assert_eq!((board.halfmove_clock(), board.fullmove_number()), (0, 1));
+        assert!(Board::from_fen("8/8/8/8/8/8/8 w - - 0 1").is_err());
+        assert!(Board::from_fen("8/8/8/4k3/8/8/8/4K3 x - - 0 1").is_err());
+        assert!(Board::from_fen("8/8/8/4k3/8/8/8/4K3 w K - 0 1").is_err());
+    }
+}
@@ -1,26 +1,111 @@
-use self::{board::Board, player::Player};
+use self::{
+    board::{Board, Position},
+    chess_move::{CastleSide, Move, MoveKind},
+    piece::{Piece, PieceColor, PieceType},
+    player::Player,
+    variant::{Variant, CHECKS_TO_WIN},
+};
 
-mod board;
-mod piece;
+pub mod board;
+pub mod board_widget;
+pub mod chess_move;
+pub mod fen;
+pub mod pgn;
+pub mod piece;
 mod player;
+pub mod polyglot;
+pub mod variant;
 
 pub struct Game {
     board: Board,
     white: Player,
     black: Player,
+    history: Vec<Ply>,
+    /// How the game ended, when it was decided off the board, such as by running out of time.
+    outcome: Option<GameStatus>,
+}
+
+/// A move which has been played in the game, along with the position it was played from.
+pub struct Ply {
+    pub board: Board,
+    pub mv: Move,
+    /// The move in standard algebraic notation.
+    pub san: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    Ongoing,
+    Checkmate {
+        winner: PieceColor,
+    },
+    Stalemate,
+    FiftyMoveRule,
+    ThreefoldRepetition,
+    InsufficientMaterial,
+    /// The winner brought their king to the center in King of the Hill.
+    KingOfTheHill {
+        winner: PieceColor,
+    },
+    /// The winner checked the opposing king for the third time in Three-Check.
+    ThreeChecks {
+        winner: PieceColor,
+    },
+    /// The winner exploded the opposing king in Atomic.
+    KingExploded {
+        winner: PieceColor,
+    },
+    /// The loser ran out of time on their clock.
+    OutOfTime {
+        winner: PieceColor,
+    },
+    /// The loser gave up the game.
+    Resignation {
+        winner: PieceColor,
+    },
+    /// The players agreed to a draw.
+    DrawAgreed,
+    /// The game was left unfinished, as the connection to the opponent was lost for good.
+    Abandoned,
+}
+
+impl GameStatus {
+    pub fn is_over(&self) -> bool {
+        *self != GameStatus::Ongoing
+    }
+
+    /// The side that won the game, or none if it is drawn or not decided.
+    pub fn winner(&self) -> Option<PieceColor> {
+        match *self {
+            GameStatus::Checkmate { winner }
+            | GameStatus::KingOfTheHill { winner }
+            | GameStatus::ThreeChecks { winner }
+            | GameStatus::KingExploded { winner }
+            | GameStatus::OutOfTime { winner }
+            | GameStatus::Resignation { winner } => Some(winner),
+            _ => None,
+        }
+    }
 }
 
 impl Game {
     pub fn new() -> Self {
+        Self::with_board(Board::new())
+    }
+
+    /// Constructs a [`Game`] starting from the given position.
+    pub fn with_board(board: Board) -> Self {
         Self {
-            board: Board::new(),
+            board,
             white: Player::new(),
             black: Player::new(),
+            history: Vec::new(),
+            outcome: None,
         }
     }
 
-    pub fn owned_board(&self) -> Board {
-        self.board.to_owned()
+    pub fn board(&self) -> &Board {
+        &self.board
     }
 
     pub fn white(&self) -> &Player {
@@ -30,4 +115,248 @@ impl Game {
     pub fn black(&self) -> &Player {
         &self.black
     }
+
+    pub fn player(&self, color: PieceColor) -> &Player {
+        match color {
+            PieceColor::White => &self.white,
+            PieceColor::Black => &self.black,
+        }
+    }
+
+    pub fn history(&self) -> &[Ply] {
+        &self.history
+    }
+
+    pub fn last_move(&self) -> Option<&Move> {
+        self.history.last().map(|ply| &ply.mv)
+    }
+
+    /// The legal moves of the piece on the given square.
+    pub fn legal_moves_from(&self, from: Position) -> Vec<Move> {
+        if self.status().is_over() {
+            return Vec::new();
+        }
+        self.board.legal_moves_from(from)
+    }
+
+    /// The legal drops of the pieces in the pocket of the side to move, which are only allowed in Crazyhouse.
+    pub fn legal_drops(&self) -> Vec<Move> {
+        if self.status().is_over() {
+            return Vec::new();
+        }
+        self.pocket_drops()
+    }
+
+    fn pocket_drops(&self) -> Vec<Move> {
+        if self.board.variant() != Variant::Crazyhouse {
+            return Vec::new();
+        }
+        let pocket: Vec<PieceType> = self
+            .player(self.board.turn())
+            .taken_pieces()
+            .iter()
+            .map(|piece| piece.variant())
+            .collect();
+        self.board.drop_moves(&pocket)
+    }
+
+    /// Plays a move, which must be one of the legal moves in the current position.
+    pub fn make_move(&mut self, mv: Move) {
+        let before = self.board;
+        let explosion = before.explosion(&mv);
+        self.board.apply(&mv);
+
+        let color = mv.piece.color();
+        let player = match color {
+            PieceColor::White => &mut self.white,
+            PieceColor::Black => &mut self.black,
+        };
+        // a promoted piece goes back to the pocket as the pawn it once was
+        let mut recorded = mv;
+        if before.is_promoted(mv.to) {
+            recorded.captured = mv
+                .captured
+                .map(|piece| Piece::new(piece.color(), PieceType::Pawn));
+        }
+        player.record(recorded);
+        for (position, piece) in explosion {
+            if piece.color() != color && position != mv.captured_square() {
+                player.take(piece);
+            }
+        }
+        if self.board.in_check() {
+            player.record_check();
+        }
+
+        let mut san = san(&before, &mv);
+        if self.board.in_check() {
+            san.push(match self.status() {
+                GameStatus::Checkmate { .. } => '#',
+                _ => '+',
+            });
+        }
+        self.history.push(Ply {
+            board: before,
+            mv,
+            san,
+        });
+    }
+
+    /// Ends the game in a way the board cannot tell, such as by running out of time.
+    pub fn conclude(&mut self, outcome: GameStatus) {
+        if !self.status().is_over() {
+            self.outcome = Some(outcome);
+        }
+    }
+
+    pub fn status(&self) -> GameStatus {
+        if let Some(outcome) = self.outcome {
+            return outcome;
+        }
+        if let Some(status) = self.variant_status() {
+            return status;
+        }
+        if self.board.legal_moves().is_empty() && self.pocket_drops().is_empty() {
+            return match self.board.in_check() {
+                true => GameStatus::Checkmate {
+                    winner: self.board.turn().opposite(),
+                },
+                false => GameStatus::Stalemate,
+            };
+        }
+        if self.board.halfmove_clock() >= 100 {
+            return GameStatus::FiftyMoveRule;
+        }
+        if self.board.variant().has_insufficient_material_rule()
+            && self.board.is_insufficient_material()
+        {
+            return GameStatus::InsufficientMaterial;
+        }
+        let repetitions = self
+            .history
+            .iter()
+            .filter(|ply| ply.board.is_same_position(&self.board))
+            .count();
+        if repetitions >= 2 {
+            return GameStatus::ThreefoldRepetition;
+        }
+        GameStatus::Ongoing
+    }
+
+    /// The outcome of the game by the win conditions the variant adds to checkmate, if any were met.
+    fn variant_status(&self) -> Option<GameStatus> {
+        let colors = [PieceColor::White, PieceColor::Black];
+        match self.board.variant() {
+            Variant::KingOfTheHill => colors
+                .into_iter()
+                .find(|color| self.board.is_king_on_hill(*color))
+                .map(|winner| GameStatus::KingOfTheHill { winner }),
+            Variant::ThreeCheck => colors
+                .into_iter()
+                .find(|color| self.player(*color).checks() >= CHECKS_TO_WIN)
+                .map(|winner| GameStatus::ThreeChecks { winner }),
+            Variant::Atomic => colors
+                .into_iter()
+                .find(|color| self.board.king_position(*color).is_none())
+                .map(|loser| GameStatus::KingExploded {
+                    winner: loser.opposite(),
+                }),
+            Variant::Standard | Variant::Crazyhouse => None,
+        }
+    }
+}
+
+/// Finds the legal move described in standard algebraic notation, ignoring any check markers and annotations.
+pub fn parse_san(board: &Board, text: &str) -> Option<Move> {
+    let text = text
+        .trim_end_matches(['+', '#', '!', '?'])
+        .replace('0', "O");
+    board
+        .legal_moves()
+        .into_iter()
+        .find(|mv| san(board, mv) == text)
+}
+
+/// Describes a line of moves played from the position in standard algebraic notation, numbered as in a game record.
+pub fn san_line(board: &Board, moves: &[Move]) -> String {
+    let mut board = *board;
+    let mut text = Vec::new();
+    for (index, mv) in moves.iter().enumerate() {
+        match board.turn() {
+            PieceColor::White => text.push(format!("{}.", board.fullmove_number())),
+            PieceColor::Black if index == 0 => text.push(format!("{}...", board.fullmove_number())),
+            PieceColor::Black => {}
+        }
+        let mut san = san(&board, mv);
+        board.apply(mv);
+        if board.in_check() {
+            san.push(match board.legal_moves().is_empty() {
+                true => '#',
+                false => '+',
+            });
+        }
+        text.push(san);
+    }
+    text.join(" ")
+}
+
+/// Describes a move in standard algebraic notation, given the position before it, leaving out any check marker.
+pub fn san(before: &Board, mv: &Move) -> String {
+    match mv.kind {
+        MoveKind::Castle(CastleSide::King) => String::from("O-O"),
+        MoveKind::Castle(CastleSide::Queen) => String::from("O-O-O"),
+        MoveKind::Drop => format!("{}@{}", mv.piece.variant().letter(), mv.to),
+        _ if mv.piece.variant() == PieceType::Pawn => {
+            let mut text = String::new();
+            if mv.captured.is_some() {
+                text.push((b'a' + mv.from.file()) as char);
+                text.push('x');
+            }
+            text.push_str(&mv.to.to_string());
+            if let Some(promotion) = mv.promotion {
+                text.push('=');
+                text.push(promotion.letter());
+            }
+            text
+        }
+        _ => {
+            let mut text = String::from(mv.piece.variant().letter());
+            text.push_str(&disambiguation(before, mv));
+            if mv.captured.is_some() {
+                text.push('x');
+            }
+            text.push_str(&mv.to.to_string());
+            text
+        }
+    }
+}
+
+/// The origin file, rank or square needed to tell the move apart from other moves of the same piece type.
+fn disambiguation(before: &Board, mv: &Move) -> String {
+    let rivals: Vec<Move> = before
+        .legal_moves()
+        .into_iter()
+        .filter(|other| {
+            other.to == mv.to
+                && other.from != mv.from
+                && other.piece.variant() == mv.piece.variant()
+        })
+        .collect();
+
+    let origin = mv.from.to_string();
+    if rivals.is_empty() {
+        String::new()
+    } else if rivals
+        .iter()
+        .all(|other| other.from.file() != mv.from.file())
+    {
+        origin[..1].to_string()
+    } else if rivals
+        .iter()
+        .all(|other| other.from.rank() != mv.from.rank())
+    {
+        origin[1..].to_string()
+    } else {
+        origin
+    }
 }
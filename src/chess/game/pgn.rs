@@ -0,0 +1,369 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{board::Board, piece::PieceColor, san, variant::Variant, Game, GameStatus};
+
+/// The longest line of movetext, as recommended by the PGN standard.
+const LINE_WIDTH: usize = 79;
+
+impl Game {
+    /// The result of the game as written in PGN, `*` while it is still being played.
+    pub fn result(&self) -> &'static str {
+        match self.status() {
+            GameStatus::Ongoing | GameStatus::Abandoned => "*",
+            GameStatus::Checkmate { winner }
+            | GameStatus::KingOfTheHill { winner }
+            | GameStatus::ThreeChecks { winner }
+            | GameStatus::KingExploded { winner }
+            | GameStatus::OutOfTime { winner }
+            | GameStatus::Resignation { winner } => match winner {
+                PieceColor::White => "1-0",
+                PieceColor::Black => "0-1",
+            },
+            GameStatus::Stalemate
+            | GameStatus::FiftyMoveRule
+            | GameStatus::ThreefoldRepetition
+            | GameStatus::InsufficientMaterial
+            | GameStatus::DrawAgreed => "1/2-1/2",
+        }
+    }
+
+    /// Writes the game in Portable Game Notation, with the given tags after the ones the game knows itself,
+    /// or in their place when they have the same name, e.g. to name the players.
+    ///
+    /// The notes are written after the move of the same index, e.g. `?!` or a `{ comment }`.
+    pub fn to_pgn(&self, tags: &[(&str, String)], notes: &[String]) -> String {
+        let start = self
+            .history()
+            .first()
+            .map(|ply| ply.board)
+            .unwrap_or(*self.board());
+
+        let mut all_tags = vec![
+            ("Event", String::from("Casual game")),
+            ("Site", String::from("Terminal Games")),
+            ("Date", today()),
+            ("Round", String::from("-")),
+            ("White", String::from("White")),
+            ("Black", String::from("Black")),
+            ("Result", self.result().to_owned()),
+        ];
+        if start != Board::new() {
+            all_tags.push(("SetUp", String::from("1")));
+            all_tags.push(("FEN", start.to_fen()));
+        }
+        for (name, value) in tags {
+            match all_tags.iter_mut().find(|(other, _)| other == name) {
+                Some(tag) => tag.1 = value.clone(),
+                None => all_tags.push((name, value.clone())),
+            }
+        }
+
+        let mut text = String::new();
+        for (name, value) in all_tags {
+            text.push_str(&format!("[{name} \"{}\"]\n", value.replace('"', "'")));
+        }
+        text.push('\n');
+
+        let mut tokens = Vec::new();
+        for (index, ply) in self.history().iter().enumerate() {
+            match ply.board.turn() {
+                PieceColor::White => tokens.push(format!("{}.", ply.board.fullmove_number())),
+                PieceColor::Black if index == 0 => {
+                    tokens.push(format!("{}...", ply.board.fullmove_number()))
+                }
+                PieceColor::Black => {}
+            }
+            let note = notes.get(index).map(String::as_str).unwrap_or_default();
+            match note.starts_with(['!', '?']) {
+                true => tokens.push(format!("{}{note}", ply.san)),
+                false => {
+                    tokens.push(ply.san.clone());
+                    tokens.extend(note.split_whitespace().map(String::from));
+                }
+            }
+        }
+        tokens.push(self.result().to_owned());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                text.push_str(&line);
+                text.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        text.push_str(&line);
+        text.push('\n');
+        text
+    }
+
+    /// Reads a single game in Portable Game Notation, as written by [`Game::to_pgn`], along with its tags.
+    ///
+    /// The starting position is taken from the `FEN` tag and the rules from the `Variant` tag.
+    /// Comments, variations and numeric annotations are skipped. A game ending before the board decided it
+    /// is concluded by its `Termination` tag and result, as lost on time, abandoned, resigned or agreed drawn.
+    pub fn from_pgn(text: &str) -> Result<(Self, Vec<(String, String)>), String> {
+        let tags = pgn_tags(text);
+        let tag = |name: &str| {
+            tags.iter()
+                .find(|(other, _)| other == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        let mut board = match tag("FEN") {
+            Some(fen) => Board::from_fen(fen)?,
+            None => Board::new(),
+        };
+        if let Some(name) = tag("Variant") {
+            if let Some(variant) = Variant::ALL
+                .into_iter()
+                .find(|variant| variant.name() == name)
+            {
+                board.set_variant(variant);
+            }
+        }
+
+        let mut game = Game::with_board(board);
+        for token in movetext_tokens(text) {
+            if ["1-0", "0-1", "1/2-1/2", "*"].contains(&token.as_str()) {
+                break;
+            }
+            let written = token
+                .trim_end_matches(['+', '#', '!', '?'])
+                .replace('0', "O");
+            let mv = game
+                .board()
+                .legal_moves()
+                .into_iter()
+                .chain(game.legal_drops())
+                .find(|mv| san(game.board(), mv) == written)
+                .ok_or_else(|| format!("'{token}' is not a legal move"))?;
+            game.make_move(mv);
+        }
+
+        let winner = match tag("Result") {
+            Some("1-0") => Some(PieceColor::White),
+            Some("0-1") => Some(PieceColor::Black),
+            _ => None,
+        };
+        match (tag("Termination"), winner, tag("Result")) {
+            (Some(TIME_FORFEIT), Some(winner), _) => {
+                game.conclude(GameStatus::OutOfTime { winner })
+            }
+            (Some(ABANDONED), _, _) => game.conclude(GameStatus::Abandoned),
+            (_, Some(winner), _) => game.conclude(GameStatus::Resignation { winner }),
+            (_, None, Some("1/2-1/2")) => game.conclude(GameStatus::DrawAgreed),
+            _ => {}
+        }
+        Ok((game, tags))
+    }
+}
+
+/// The `Termination` tag of a game lost on time.
+pub const TIME_FORFEIT: &str = "time forfeit";
+
+/// The `Termination` tag of a game left unfinished.
+pub const ABANDONED: &str = "abandoned";
+
+/// The tag pairs at the top of a game in Portable Game Notation, in the order they were written.
+pub fn pgn_tags(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter_map(|line| line.strip_prefix('[')?.strip_suffix(']'))
+        .filter_map(|tag| {
+            let (name, value) = tag.split_once(' ')?;
+            let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+            Some((name.to_owned(), value.to_owned()))
+        })
+        .collect()
+}
+
+/// The moves and the result of the movetext, without move numbers, comments, variations and numeric annotations.
+fn movetext_tokens(text: &str) -> Vec<String> {
+    let mut movetext = String::new();
+    for line in text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('['))
+    {
+        // a semicolon comments out the rest of the line
+        movetext.push_str(line.split(';').next().unwrap_or_default());
+        movetext.push(' ');
+    }
+
+    let mut plain = String::new();
+    let (mut in_comment, mut depth) = (false, 0);
+    for symbol in movetext.chars() {
+        match symbol {
+            '{' => in_comment = true,
+            '}' => in_comment = false,
+            '(' if !in_comment => depth += 1,
+            ')' if !in_comment => depth -= 1,
+            _ if in_comment || depth > 0 => {}
+            symbol => plain.push(symbol),
+        }
+    }
+
+    plain
+        .split_whitespace()
+        .filter(|token| !token.starts_with('$'))
+        // move numbers may be written right up against the move, as in `1.e4`
+        .map(|token| token.rsplit('.').next().unwrap_or_default())
+        .filter(|token| !token.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Today's date in the `YYYY.MM.DD` form of PGN, going by the UTC calendar.
+pub fn today() -> String {
+    let Ok(elapsed) = SystemTime::now().duration_since(UNIX_EPOCH) else {
+        return String::from("????.??.??");
+    };
+    // converts the days since the epoch to a civil date, as laid out by Howard Hinnant
+    let days = (elapsed.as_secs() / 86_400) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}.{month:02}.{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::game::parse_san;
+
+    /// The game played out from the position with the moves in standard algebraic notation.
+    fn play(fen: &str, moves: &[&str]) -> Game {
+        let mut game = Game::with_board(Board::from_fen(fen).unwrap());
+        for san in moves {
+            let mv = parse_san(game.board(), san)
+                .unwrap_or_else(|| panic!("{san} is not legal in {}", game.board().to_fen()));
+            game.make_move(mv);
+        }
+        game
+    }
+
+    fn sans(game: &Game) -> Vec<&str> {
+        game.history().iter().map(|ply| ply.san.as_str()).collect()
+    }
+
+    #[test]
+    fn reads_back_the_games_it_writes() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let game = play(
+            start,
+            &[
+                "e4", "e5", "Nf3", "Nc6", "Bc4", "Nf6", "O-O", "Nxe4", "Re1", "d5", "Bxd5", "Qxd5",
+                "Nc3",
+            ],
+        );
+        let notes = vec![
+            String::new(),
+            String::from("{ the open game }"),
+            String::from("?!"),
+        ];
+        let text = game.to_pgn(&[("White", String::from("Alice"))], &notes);
+
+        assert!(text.contains("[White \"Alice\"]\n"));
+        assert!(!text.contains("[FEN "));
+        assert!(text.contains("1. e4 e5 { the open game } 2. Nf3?! Nc6 3. Bc4"));
+        assert!(text.trim_end().ends_with(" *"));
+        assert!(text.lines().all(|line| line.len() <= LINE_WIDTH));
+
+        let (read, tags) = Game::from_pgn(&text).unwrap();
+        assert_eq!(sans(&read), sans(&game));
+        assert_eq!(read.board().to_fen(), game.board().to_fen());
+        assert!(tags.contains(&(String::from("White"), String::from("Alice"))));
+        assert_eq!(read.status(), GameStatus::Ongoing);
+    }
+
+    #[test]
+    fn keeps_the_starting_position_and_the_result() {
+        let game = play("6k1/1p3ppp/8/8/8/8/8/R5K1 b - - 3 40", &["b6", "Ra8#"]);
+        let text = game.to_pgn(&[], &[]);
+        assert!(text.contains("[SetUp \"1\"]\n"));
+        assert!(text.contains("[FEN \"6k1/1p3ppp/8/8/8/8/8/R5K1 b - - 3 40\"]\n"));
+        assert!(text.contains("40... b6 41. Ra8# 1-0"));
+
+        let (read, _) = Game::from_pgn(&text).unwrap();
+        assert_eq!(sans(&read), ["b6", "Ra8#"]);
+        assert_eq!(
+            read.status(),
+            GameStatus::Checkmate {
+                winner: PieceColor::White
+            }
+        );
+    }
+
+    #[test]
+    fn skips_comments_variations_and_annotations() {
+        let text = "[Event \"Test\"]\n[Result \"0-1\"]\n\n\
+            1.e4 {best by test} e5 (1... c5 2. Nf3) 2. Nf3 $1 Nc6 ; the rest of the line\n\
+            3. Bb5 a6 0-1\n";
+        let (game, tags) = Game::from_pgn(text).unwrap();
+        assert_eq!(sans(&game), ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+        assert_eq!(tags[0], (String::from("Event"), String::from("Test")));
+        // the board did not decide the game, so the loser must have resigned
+        assert_eq!(
+            game.status(),
+            GameStatus::Resignation {
+                winner: PieceColor::Black
+            }
+        );
+
+        let text = "[Result \"1-0\"]\n[Termination \"time forfeit\"]\n\n1. d4 1-0\n";
+        let (game, _) = Game::from_pgn(text).unwrap();
+        assert_eq!(
+            game.status(),
+            GameStatus::OutOfTime {
+                winner: PieceColor::White
+            }
+        );
+    }
+
+    #[test]
+    fn refuses_an_illegal_move() {
+        let error = Game::from_pgn("1. e4 e5 2. Ke3 *").err();
+        assert_eq!(error.as_deref(), Some("'Ke3' is not a legal move"));
+    }
+
+    #[test]
+    fn writes_moves_in_standard_algebraic_notation() {
+        let san_of = |fen: &str, from: &str, to: &str| {
+            let board = Board::from_fen(fen).unwrap();
+            let mv = board
+                .legal_moves()
+                .into_iter()
+                .find(|mv| mv.from.to_string() == from && mv.to.to_string() == to)
+                .unwrap();
+            san(&board, &mv)
+        };
+        // both knights reach d2 and both rooks reach a4
+        let fen = "4k3/8/8/R7/8/1N3N2/8/R3K3 w Q - 0 1";
+        assert_eq!(san_of(fen, "b3", "d2"), "Nbd2");
+        assert_eq!(san_of(fen, "a5", "a4"), "R5a4");
+        assert_eq!(san_of(fen, "e1", "c1"), "O-O-O");
+        // one queen shares the file of the one on a4 and the other shares its rank
+        let fen = "5k2/8/8/8/Q6Q/8/8/Q3K3 w - - 0 1";
+        assert_eq!(san_of(fen, "a4", "d4"), "Qa4d4");
+        assert_eq!(san_of(fen, "a1", "d4"), "Q1d4");
+
+        let board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let mv = parse_san(&board, "b8=N+").unwrap();
+        assert_eq!(san(&board, &mv), "b8=N");
+        assert!(parse_san(&board, "b8").is_none());
+    }
+}
@@ -1,18 +1,32 @@
-const BLACK_KING: &str = "♔";
-const BLACK_QUEEN: &str = "♕";
-const BLACK_ROOK: &str = "♖";
-const BLACK_KNIGHT: &str = "♘";
-const BLACK_BISHOP: &str = "♗";
-const BLACK_PAWN: &str = "♙";
-
-const WHITE_KING: &str = "♚";
-const WHITE_QUEEN: &str = "♛";
-const WHITE_ROOK: &str = "♜";
-const WHITE_KNIGHT: &str = "♞";
-const WHITE_BISHOP: &str = "♝";
-const WHITE_PAWN: &str = "♟";
-
-#[derive(Debug, Clone, Copy)]
+const OUTLINED_KING: &str = "♔";
+const OUTLINED_QUEEN: &str = "♕";
+const OUTLINED_ROOK: &str = "♖";
+const OUTLINED_KNIGHT: &str = "♘";
+const OUTLINED_BISHOP: &str = "♗";
+const OUTLINED_PAWN: &str = "♙";
+
+const FILLED_KING: &str = "♚";
+const FILLED_QUEEN: &str = "♛";
+const FILLED_ROOK: &str = "♜";
+const FILLED_KNIGHT: &str = "♞";
+const FILLED_BISHOP: &str = "♝";
+const FILLED_PAWN: &str = "♟";
+
+const WHITE_KING: &str = "K";
+const WHITE_QUEEN: &str = "Q";
+const WHITE_ROOK: &str = "R";
+const WHITE_KNIGHT: &str = "N";
+const WHITE_BISHOP: &str = "B";
+const WHITE_PAWN: &str = "P";
+
+const BLACK_KING: &str = "k";
+const BLACK_QUEEN: &str = "q";
+const BLACK_ROOK: &str = "r";
+const BLACK_KNIGHT: &str = "n";
+const BLACK_BISHOP: &str = "b";
+const BLACK_PAWN: &str = "p";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Piece {
     color: PieceColor,
     variant: PieceType,
@@ -23,7 +37,50 @@ impl Piece {
         Self { color, variant }
     }
 
-    pub fn as_str(&self) -> &str {
+    pub fn color(&self) -> PieceColor {
+        self.color
+    }
+
+    pub fn variant(&self) -> PieceType {
+        self.variant
+    }
+
+    /// The conventional glyph of the piece, outlined for white and filled for black.
+    pub fn as_str(&self) -> &'static str {
+        match self.color {
+            PieceColor::White => self.outlined(),
+            PieceColor::Black => self.filled(),
+        }
+    }
+
+    fn outlined(&self) -> &'static str {
+        use PieceType::*;
+
+        match self.variant {
+            Pawn => OUTLINED_PAWN,
+            Bishop => OUTLINED_BISHOP,
+            Knight => OUTLINED_KNIGHT,
+            Rook => OUTLINED_ROOK,
+            Queen => OUTLINED_QUEEN,
+            King => OUTLINED_KING,
+        }
+    }
+
+    fn filled(&self) -> &'static str {
+        use PieceType::*;
+
+        match self.variant {
+            Pawn => FILLED_PAWN,
+            Bishop => FILLED_BISHOP,
+            Knight => FILLED_KNIGHT,
+            Rook => FILLED_ROOK,
+            Queen => FILLED_QUEEN,
+            King => FILLED_KING,
+        }
+    }
+
+    /// The letter of the piece, uppercase for white and lowercase for black.
+    fn ascii(&self) -> &'static str {
         use PieceColor::*;
         use PieceType::*;
 
@@ -48,13 +105,85 @@ impl Piece {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// The symbols pieces are drawn with, as not every terminal and font renders the chess glyphs well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PieceSet {
+    /// Filled glyphs for both sides, told apart by color.
+    #[default]
+    UnicodeFilled,
+    /// Outlined glyphs for both sides, told apart by color.
+    UnicodeOutlined,
+    /// The algebraic notation letters, uppercase for white and lowercase for black.
+    Ascii,
+}
+
+impl PieceSet {
+    pub const ALL: [PieceSet; 3] = [
+        PieceSet::UnicodeFilled,
+        PieceSet::UnicodeOutlined,
+        PieceSet::Ascii,
+    ];
+
+    /// Returns the set following this one, wrapping around after the last.
+    pub fn next(self) -> Self {
+        match self {
+            PieceSet::UnicodeFilled => PieceSet::UnicodeOutlined,
+            PieceSet::UnicodeOutlined => PieceSet::Ascii,
+            PieceSet::Ascii => PieceSet::UnicodeFilled,
+        }
+    }
+
+    /// The symbol of the piece on the board, where the side is also shown through its color.
+    pub fn board_symbol(self, piece: Piece) -> &'static str {
+        match self {
+            PieceSet::UnicodeFilled => piece.filled(),
+            PieceSet::UnicodeOutlined => piece.outlined(),
+            PieceSet::Ascii => piece.ascii(),
+        }
+    }
+
+    /// The symbol of the piece in plain text, where the symbol alone has to tell the sides apart.
+    pub fn text_symbol(self, piece: Piece) -> &'static str {
+        match self {
+            PieceSet::UnicodeFilled | PieceSet::UnicodeOutlined => piece.as_str(),
+            PieceSet::Ascii => piece.ascii(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PieceColor {
     White,
     Black,
 }
 
-#[derive(Debug, Clone, Copy)]
+impl PieceColor {
+    /// Returns the color of the opposing side.
+    pub fn opposite(self) -> Self {
+        match self {
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Black => PieceColor::White,
+        }
+    }
+
+    /// The direction pawns of this color advance in, expressed as a rank offset.
+    pub fn pawn_direction(self) -> i8 {
+        match self {
+            PieceColor::White => 1,
+            PieceColor::Black => -1,
+        }
+    }
+
+    /// The rank index (0-7) of this color's back rank.
+    pub fn back_rank(self) -> u8 {
+        match self {
+            PieceColor::White => 0,
+            PieceColor::Black => 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PieceType {
     Pawn,
     Bishop,
@@ -63,3 +192,47 @@ pub enum PieceType {
     Queen,
     King,
 }
+
+impl PieceType {
+    /// The pieces a pawn may promote to, ordered as presented to the player.
+    pub const PROMOTIONS: [PieceType; 4] = [
+        PieceType::Queen,
+        PieceType::Rook,
+        PieceType::Bishop,
+        PieceType::Knight,
+    ];
+
+    /// The pieces that can be captured and dropped back onto the board, ordered as presented to the player.
+    pub const DROPS: [PieceType; 5] = [
+        PieceType::Pawn,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ];
+
+    /// The uppercase letter used for the piece in algebraic notation.
+    pub fn letter(self) -> char {
+        match self {
+            PieceType::Pawn => 'P',
+            PieceType::Bishop => 'B',
+            PieceType::Knight => 'N',
+            PieceType::Rook => 'R',
+            PieceType::Queen => 'Q',
+            PieceType::King => 'K',
+        }
+    }
+
+    /// Parses an algebraic notation letter, in either case, into a piece type.
+    pub fn from_letter(letter: char) -> Option<Self> {
+        match letter.to_ascii_uppercase() {
+            'P' => Some(PieceType::Pawn),
+            'B' => Some(PieceType::Bishop),
+            'N' => Some(PieceType::Knight),
+            'R' => Some(PieceType::Rook),
+            'Q' => Some(PieceType::Queen),
+            'K' => Some(PieceType::King),
+            _ => None,
+        }
+    }
+}
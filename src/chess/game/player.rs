@@ -1,8 +1,13 @@
-use super::{board::Position, piece::Piece};
+use super::{
+    chess_move::{Move, MoveKind},
+    piece::Piece,
+};
 
 pub struct Player {
-    moves: Vec<(Piece, Position, Position)>,
+    moves: Vec<Move>,
     taken_pieces: Vec<Piece>,
+    /// The number of times the player has put the opposing king in check.
+    checks: u8,
 }
 
 impl Player {
@@ -10,10 +15,46 @@ impl Player {
         Self {
             moves: Vec::new(),
             taken_pieces: Vec::new(),
+            checks: 0,
         }
     }
 
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The pieces the player has taken, which also make up the pocket pieces are dropped from in Crazyhouse.
     pub fn taken_pieces(&self) -> &[Piece] {
         &self.taken_pieces
     }
+
+    pub fn checks(&self) -> u8 {
+        self.checks
+    }
+
+    /// Records a move made by the player, along with any piece it captured, or removes the dropped piece from the pocket.
+    pub fn record(&mut self, mv: Move) {
+        if let Some(captured) = mv.captured {
+            self.taken_pieces.push(captured);
+        }
+        if mv.kind == MoveKind::Drop {
+            if let Some(index) = self
+                .taken_pieces
+                .iter()
+                .position(|piece| piece.variant() == mv.piece.variant())
+            {
+                self.taken_pieces.remove(index);
+            }
+        }
+        self.moves.push(mv);
+    }
+
+    /// Records a piece taken other than by the capture of a move, such as by an atomic explosion.
+    pub fn take(&mut self, piece: Piece) {
+        self.taken_pieces.push(piece);
+    }
+
+    pub fn record_check(&mut self) {
+        self.checks += 1;
+    }
 }
@@ -0,0 +1,266 @@
+use super::{
+    board::{Board, Position, CHESS_BOARD_SIZE},
+    chess_move::{Move, MoveKind},
+    piece::{Piece, PieceColor, PieceType},
+};
+
+/// The squares in the center of the board, which a king has to reach to win King of the Hill.
+const HILL: [(u8, u8); 4] = [(3, 3), (4, 3), (3, 4), (4, 4)];
+
+/// The number of checks that wins a game of Three-Check.
+pub const CHECKS_TO_WIN: u8 = 3;
+
+/// The set of rules a game is played by, each adding to or changing the rules of standard chess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// Bringing the king to one of the four center squares also wins the game.
+    KingOfTheHill,
+    /// Checking the opposing king three times also wins the game.
+    ThreeCheck,
+    /// Every capture explodes, removing all pieces but pawns around the captured square; exploding the opposing king wins the game.
+    Atomic,
+    /// Captured pieces change sides, and may be dropped back onto the board in place of a move.
+    Crazyhouse,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 5] = [
+        Variant::Standard,
+        Variant::KingOfTheHill,
+        Variant::ThreeCheck,
+        Variant::Atomic,
+        Variant::Crazyhouse,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Standard => "Standard",
+            Variant::KingOfTheHill => "King of the Hill",
+            Variant::ThreeCheck => "Three-Check",
+            Variant::Atomic => "Atomic",
+            Variant::Crazyhouse => "Crazyhouse",
+        }
+    }
+
+    /// The variant after this one, wrapping around to the first.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|variant| *variant == self);
+        Self::ALL[(index.unwrap_or(0) + 1) % Self::ALL.len()]
+    }
+
+    /// The variant before this one, wrapping around to the last.
+    pub fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|variant| *variant == self);
+        Self::ALL[(index.unwrap_or(0) + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Whether a game is drawn once neither side has the material to checkmate.
+    ///
+    /// The other variants can still be decided without checkmating, or bring captured material back,
+    /// so they are left to the other draw rules.
+    pub fn has_insufficient_material_rule(self) -> bool {
+        self == Variant::Standard
+    }
+
+    /// Whether the engine plays by the rules of the variant.
+    ///
+    /// The search knows nothing of the checks given in Three-Check or of the pockets in Crazyhouse,
+    /// so it would misjudge those games; the computer, hints, reviews and the engine of analysis mode are left out of them.
+    pub fn has_engine_support(self) -> bool {
+        !matches!(self, Variant::ThreeCheck | Variant::Crazyhouse)
+    }
+}
+
+impl Board {
+    /// Whether the king of the given color stands on one of the center squares.
+    pub fn is_king_on_hill(&self, color: PieceColor) -> bool {
+        self.king_position(color)
+            .map(|king| HILL.contains(&(king.file(), king.rank())))
+            .unwrap_or(false)
+    }
+
+    /// The squares cleared by the explosion of an atomic capture, along with the pieces on them.
+    ///
+    /// The capturing and captured pieces are always destroyed, along with every piece other than a pawn around the capture.
+    pub fn explosion(&self, mv: &Move) -> Vec<(Position, Piece)> {
+        if self.variant() != Variant::Atomic || mv.captured.is_none() {
+            return Vec::new();
+        }
+
+        let mut exploded = vec![(mv.to, mv.piece)];
+        let captured_square = mv.captured_square();
+        if let Some(captured) = self.piece_at(captured_square) {
+            exploded.push((captured_square, captured));
+        }
+        for file in -1..=1 {
+            for rank in -1..=1 {
+                let Some(position) = mv.to.offset(file, rank) else {
+                    continue;
+                };
+                let Some(piece) = self.piece_at(position) else {
+                    continue;
+                };
+                if position != mv.from
+                    && position != captured_square
+                    && piece.variant() != PieceType::Pawn
+                {
+                    exploded.push((position, piece));
+                }
+            }
+        }
+        exploded
+    }
+
+    /// Every legal drop of the given pieces by the side to move, as played in Crazyhouse.
+    ///
+    /// Pieces may be dropped onto any empty square, except for pawns on the first and last ranks.
+    pub fn drop_moves(&self, pocket: &[PieceType]) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for variant in PieceType::DROPS
+            .into_iter()
+            .filter(|variant| pocket.contains(variant))
+        {
+            let piece = Piece::new(self.turn(), variant);
+            for position in Position::all() {
+                let is_pawn_rank =
+                    position.rank() == 0 || position.rank() == CHESS_BOARD_SIZE as u8 - 1;
+                if self.piece_at(position).is_some() || (variant == PieceType::Pawn && is_pawn_rank)
+                {
+                    continue;
+                }
+                let mv = Move {
+                    piece,
+                    from: position,
+                    to: position,
+                    captured: None,
+                    promotion: None,
+                    kind: MoveKind::Drop,
+                };
+                if self.is_legal(&mv) {
+                    moves.push(mv);
+                }
+            }
+        }
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::game::{parse_san, Game, GameStatus};
+
+    /// A game of the variant from the position, with the moves played in it.
+    fn play(variant: Variant, fen: &str, moves: &[&str]) -> Game {
+        let mut board = Board::from_fen(fen).unwrap();
+        board.set_variant(variant);
+        let mut game = Game::with_board(board);
+        for san in moves {
+            let mv = parse_san(game.board(), san)
+                .or_else(|| game.legal_drops().into_iter().find(|mv| &mv.uci() == san))
+                .unwrap_or_else(|| panic!("{san} is not legal in {}", game.board().to_fen()));
+            game.make_move(mv);
+        }
+        game
+    }
+
+    #[test]
+    fn explodes_the_pieces_around_an_atomic_capture() {
+        let game = play(
+            Variant::Atomic,
+            "4k3/8/2n1b3/3p4/4P3/2N5/8/4K3 w - - 0 1",
+            &["Nxd5"],
+        );
+        // the capturing knight goes up along with the pieces around it, but not the pawn next to it
+        assert_eq!(game.board().to_fen(), "4k3/8/8/8/4P3/8/8/4K3 b - - 0 1");
+        assert_eq!(game.status(), GameStatus::Ongoing);
+
+        let game = play(
+            Variant::Atomic,
+            "4k3/4p3/8/8/8/8/8/4QK2 w - - 0 1",
+            &["Qxe7"],
+        );
+        assert_eq!(
+            game.status(),
+            GameStatus::KingExploded {
+                winner: PieceColor::White
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_the_atomic_king_from_capturing() {
+        let board = Board::from_fen("8/8/8/8/8/8/3p4/4K2k w - - 0 1").unwrap();
+        let mut atomic = board;
+        atomic.set_variant(Variant::Atomic);
+        let captures = |board: &Board| {
+            board
+                .legal_moves()
+                .iter()
+                .filter(|mv| mv.captured.is_some())
+                .count()
+        };
+        assert_eq!(captures(&board), 1);
+        assert_eq!(captures(&atomic), 0);
+    }
+
+    #[test]
+    fn wins_with_the_third_check() {
+        let fen = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1";
+        let game = play(Variant::ThreeCheck, fen, &["Ra8+", "Kd7", "Ra7+", "Kd6"]);
+        assert_eq!(game.status(), GameStatus::Ongoing);
+        let game = play(
+            Variant::ThreeCheck,
+            fen,
+            &["Ra8+", "Kd7", "Ra7+", "Kd6", "Ra6+"],
+        );
+        assert_eq!(
+            game.status(),
+            GameStatus::ThreeChecks {
+                winner: PieceColor::White
+            }
+        );
+    }
+
+    #[test]
+    fn wins_with_the_king_on_the_hill() {
+        let game = play(
+            Variant::KingOfTheHill,
+            "4k3/8/8/8/8/3K4/8/8 w - - 0 1",
+            &["Kd4"],
+        );
+        assert_eq!(
+            game.status(),
+            GameStatus::KingOfTheHill {
+                winner: PieceColor::White
+            }
+        );
+        // the same king walk is a draw by insufficient material in standard chess
+        let game = play(Variant::Standard, "4k3/8/8/8/8/3K4/8/8 w - - 0 1", &["Kd4"]);
+        assert_eq!(game.status(), GameStatus::InsufficientMaterial);
+    }
+
+    #[test]
+    fn drops_captured_pieces_in_crazyhouse() {
+        let fen = "4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1";
+        let game = play(Variant::Crazyhouse, fen, &["exd5", "Ke7"]);
+        let drops = game.legal_drops();
+        assert!(drops
+            .iter()
+            .all(|mv| mv.piece == Piece::new(PieceColor::White, PieceType::Pawn)));
+        // pawns are never dropped on the first or last rank
+        assert!(drops
+            .iter()
+            .all(|mv| (1..CHESS_BOARD_SIZE as u8 - 1).contains(&mv.to.rank())));
+        // every empty square of the ranks in between, the pawn on d5 and the king on e7 taking two
+        assert_eq!(drops.len(), 6 * 8 - 2);
+
+        let game = play(Variant::Crazyhouse, fen, &["exd5", "Ke7", "P@e6"]);
+        assert_eq!(game.board().to_fen(), "8/4k3/4P3/3P4/8/8/8/4K3 b - - 0 2");
+        assert!(play(Variant::Standard, fen, &["exd5", "Ke7"])
+            .legal_drops()
+            .is_empty());
+    }
+}
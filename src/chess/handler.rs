@@ -1,23 +1,137 @@
 use crate::app::AppResult;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use tui::prelude::Backend;
 
-use super::Chess;
+use super::{game::board_widget::BoardWidget, ui, Chess};
 
 pub fn handle_key_events<B: Backend>(
     key_event: KeyEvent,
     chess: &mut Chess<'_, B>,
 ) -> AppResult<()> {
+    if chess.promotion().is_some() {
+        return handle_promotion_key_events(key_event, chess);
+    }
+
+    if chess.is_resigning() && key_event.code != KeyCode::Char('r') {
+        chess.keep_playing();
+        return Ok(());
+    }
+
+    // in a blindfold game the letters and symbols of algebraic notation make up the move being typed,
+    // so the keys bound to letters are reached with alt instead, and every other key does what it always does
+    if chess.is_typing_moves() {
+        let is_plain = (key_event.modifiers - KeyModifiers::SHIFT).is_empty();
+        let is_typed = match key_event.code {
+            KeyCode::Char(symbol) if is_plain => {
+                chess.type_move_symbol(symbol) || symbol.is_ascii_alphabetic()
+            }
+            KeyCode::Backspace => {
+                chess.erase_move_symbol();
+                true
+            }
+            KeyCode::Enter => {
+                chess.play_typed_move();
+                true
+            }
+            KeyCode::Tab => {
+                chess.peek();
+                true
+            }
+            KeyCode::Esc => chess.clear_move_input(),
+            _ => false,
+        };
+        if is_typed {
+            return Ok(());
+        }
+    }
+
     match key_event.code {
-        KeyCode::Esc | KeyCode::Char('q') => {
+        KeyCode::Char('q') => {
             chess.quit();
         }
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            if key_event.modifiers == KeyModifiers::CONTROL {
+        KeyCode::Esc => {
+            // escape first backs out of a selection, and only quits once there is nothing to back out of
+            let had_selection = chess.deselect();
+            if !had_selection {
                 chess.quit();
             }
         }
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            chess.quit();
+        }
+        KeyCode::Up => chess.move_cursor(0, 1),
+        KeyCode::Down => chess.move_cursor(0, -1),
+        KeyCode::Left => chess.move_cursor(-1, 0),
+        KeyCode::Right => chess.move_cursor(1, 0),
+        KeyCode::Enter | KeyCode::Char(' ') => chess.select(chess.cursor),
+        KeyCode::Char('p') => chess.cycle_piece_set(),
+        KeyCode::Tab => chess.cycle_drop(),
+        KeyCode::Char('n') => chess.next_puzzle(),
+        KeyCode::Char('b') => chess.previous_lesson(),
+        KeyCode::Char('a') => chess.toggle_analysis(),
+        KeyCode::Char('[') => chess.step_back(),
+        KeyCode::Char(']') => chess.step_forward(),
+        KeyCode::Char('j') => chess.next_critical_moment(),
+        KeyCode::Char('k') => chess.previous_critical_moment(),
+        KeyCode::Char('e') => chess.export_review(),
+        KeyCode::Char('d') => chess.export_diagram(),
+        KeyCode::Char('s') => chess.save_game(),
+        KeyCode::Char('?') | KeyCode::Char('h') => chess.ask_hint(),
+        KeyCode::Char('r') => chess.resign(),
+        KeyCode::Char('o') => chess.offer_draw(),
+        KeyCode::Char('x') => chess.decline_draw(),
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_promotion_key_events<B: Backend>(
+    key_event: KeyEvent,
+    chess: &mut Chess<'_, B>,
+) -> AppResult<()> {
+    match key_event.code {
+        KeyCode::Esc => {
+            chess.deselect();
+        }
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            chess.quit();
+        }
+        KeyCode::Left | KeyCode::Up => chess.previous_promotion(),
+        KeyCode::Right | KeyCode::Down => chess.next_promotion(),
+        KeyCode::Enter | KeyCode::Char(' ') => chess.confirm_promotion(),
         _ => {}
     }
     Ok(())
 }
+
+pub fn handle_mouse_events<B: Backend>(
+    mouse_event: MouseEvent,
+    chess: &mut Chess<'_, B>,
+) -> AppResult<()> {
+    if mouse_event.kind == MouseEventKind::Down(MouseButton::Right) {
+        chess.cancel_premove();
+        return Ok(());
+    }
+    // moves are only typed in a blindfold game
+    if mouse_event.kind != MouseEventKind::Down(MouseButton::Left) || chess.is_typing_moves() {
+        return Ok(());
+    }
+    let (x, y) = (mouse_event.column, mouse_event.row);
+
+    if chess.promotion().is_some() {
+        if let Some(index) = ui::promotion_choice_at(chess.promotion_area, x, y) {
+            chess.pick_promotion(index);
+        }
+        return Ok(());
+    }
+
+    if let Some(index) = ui::pocket_choice_at(chess.pocket_area, x, y) {
+        chess.pick_drop(index);
+        return Ok(());
+    }
+    if let Some(position) = BoardWidget::square_at(chess.board_area, chess.board_scale, false, x, y)
+    {
+        chess.select(position);
+    }
+    Ok(())
+}
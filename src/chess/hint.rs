@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use super::{
+    engine::{search::SearchLimits, Analysis},
+    game::{chess_move::Move, Game},
+};
+
+/// How long the engine looks for the move to suggest.
+const HINT_TIME: Duration = Duration::from_secs(1);
+
+/// A move suggested by the engine to the player to move, showing only the piece to move until asked again.
+pub struct Hint {
+    search: Analysis,
+    /// Signifies whether the whole move is shown, rather than only the piece to move.
+    is_revealed: bool,
+}
+
+impl Hint {
+    /// Starts looking for a good move in the current position of the game.
+    pub fn start(game: &Game) -> Self {
+        Self {
+            search: Analysis::with_limits(game, SearchLimits::time(HINT_TIME)),
+            is_revealed: false,
+        }
+    }
+
+    /// Whether the engine is still looking for the move.
+    pub fn is_thinking(&self) -> bool {
+        !self.search.is_finished()
+    }
+
+    /// The suggested move, once the engine has settled on it.
+    pub fn best_move(&self) -> Option<Move> {
+        if self.is_thinking() {
+            return None;
+        }
+        self.search.best_move()
+    }
+
+    pub fn is_revealed(&self) -> bool {
+        self.is_revealed
+    }
+
+    /// Shows the whole move rather than only the piece to move.
+    pub fn reveal(&mut self) {
+        self.is_revealed = true;
+    }
+}
@@ -0,0 +1,22 @@
+use crate::app::AppResult;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::prelude::Backend;
+
+use super::Lobby;
+
+pub fn handle_key_events<B: Backend>(
+    key_event: KeyEvent,
+    lobby: &mut Lobby<'_, B>,
+) -> AppResult<()> {
+    match key_event.code {
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            lobby.quit();
+        }
+        KeyCode::Esc => lobby.cancel(),
+        KeyCode::Enter => lobby.confirm(),
+        KeyCode::Backspace => lobby.erase_symbol(),
+        KeyCode::Char(symbol) => lobby.type_symbol(symbol),
+        _ => {}
+    }
+    Ok(())
+}
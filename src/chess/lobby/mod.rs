@@ -0,0 +1,228 @@
+use std::net::IpAddr;
+
+use crate::{app::AppResult, event::Event, tui::Tui};
+use tui::prelude::Backend;
+
+use self::handler::handle_key_events;
+use super::{
+    clock::Clock,
+    game::{piece::PieceSet, Game},
+    network::{local_addresses, NetworkEvent, NetworkSession, DEFAULT_PORT},
+    save::SavedGame,
+    setup::{Blindfold, GameOptions, Opponent},
+    Chess,
+};
+
+mod handler;
+mod ui;
+
+/// The longest address or port that can be typed in.
+const MAX_INPUT_LENGTH: usize = 64;
+
+/// Whether the lobby hosts a game or joins one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LobbyMode {
+    /// Waits on a port for a player on the local network to join a game with the chosen options.
+    Host(GameOptions),
+    /// Joins the game hosted at an address.
+    Join,
+    /// Watches the game hosted at an address, without playing.
+    Watch,
+}
+
+/// The screen where a game over the local network is set up: the host waits for a guest to join,
+/// and the guest types the address of the host. The game starts as soon as both sides are there,
+/// and a spectator typing the address of the host starts watching it as soon as the host lets them in.
+pub struct Lobby<'a, B: Backend> {
+    running: bool,
+    tui: Option<&'a mut Tui<B>>,
+    mode: LobbyMode,
+    /// The port to host on, or the address of the host to join.
+    input: String,
+    /// The session once hosting or joining, while waiting for the other side.
+    session: Option<NetworkSession>,
+    /// The addresses of the network interfaces other players on the local network may join this one at.
+    local_addresses: Vec<IpAddr>,
+    /// A message for the player about the last thing that happened.
+    notice: Option<String>,
+}
+
+impl<'a, B: Backend> Lobby<'a, B> {
+    /// Constructs a lobby hosting a game with the options, which the guest takes over as they are.
+    pub fn host(tui: &'a mut Tui<B>, options: GameOptions) -> Self {
+        // the computer has no place in a game between two players on the network,
+        // and the guest takes over the options, so the host does not blindfold them
+        let options = GameOptions {
+            opponent: Opponent::Human,
+            blindfold: Blindfold::Off,
+            ..options.resolve()
+        };
+        Self::new(tui, LobbyMode::Host(options), DEFAULT_PORT.to_string())
+    }
+
+    /// Constructs a lobby joining a game hosted on the local network.
+    pub fn join(tui: &'a mut Tui<B>) -> Self {
+        Self::new(tui, LobbyMode::Join, String::new())
+    }
+
+    /// Constructs a lobby watching a game hosted on the local network.
+    pub fn watch(tui: &'a mut Tui<B>) -> Self {
+        Self::new(tui, LobbyMode::Watch, String::new())
+    }
+
+    fn new(tui: &'a mut Tui<B>, mode: LobbyMode, input: String) -> Self {
+        Self {
+            running: true,
+            tui: Some(tui),
+            mode,
+            input,
+            session: None,
+            local_addresses: local_addresses(),
+            notice: None,
+        }
+    }
+
+    pub fn launch_lobby(&mut self) -> AppResult<()> {
+        while self.running {
+            let tui = self.tui.take().expect("should always contain a tui");
+            tui.terminal.draw(|frame| ui::render(self, frame))?;
+            let event = tui.events.next()?;
+            // the tui is put back before handling the keys, as starting the game needs it
+            self.tui = Some(tui);
+            match event {
+                Event::Tick => {}
+                Event::Key(key_event) => handle_key_events(key_event, self)?,
+                Event::Mouse(_) => {}
+                Event::Resize(_, _) => {}
+            }
+            self.update()?;
+        }
+        Ok(())
+    }
+
+    pub fn quit(&mut self) {
+        self.running = false;
+    }
+
+    pub fn mode(&self) -> LobbyMode {
+        self.mode
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn session(&self) -> Option<&NetworkSession> {
+        self.session.as_ref()
+    }
+
+    pub fn local_addresses(&self) -> &[IpAddr] {
+        &self.local_addresses
+    }
+
+    pub fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    pub fn type_symbol(&mut self, symbol: char) {
+        let is_allowed = match self.mode {
+            LobbyMode::Host(_) => symbol.is_ascii_digit(),
+            LobbyMode::Join | LobbyMode::Watch => !symbol.is_whitespace(),
+        };
+        if self.session.is_none() && is_allowed && self.input.len() < MAX_INPUT_LENGTH {
+            self.input.push(symbol);
+            self.notice = None;
+        }
+    }
+
+    pub fn erase_symbol(&mut self) {
+        if self.session.is_none() {
+            self.input.pop();
+            self.notice = None;
+        }
+    }
+
+    /// Starts hosting on the typed port, or joins the game at the typed address.
+    pub fn confirm(&mut self) {
+        if self.session.is_some() {
+            return;
+        }
+        let session = match self.mode {
+            LobbyMode::Host(_) => match self.input.parse::<u16>() {
+                Ok(port) => NetworkSession::host(port),
+                Err(_) => {
+                    self.notice = Some(format!("'{}' is not a port", self.input));
+                    return;
+                }
+            },
+            LobbyMode::Join | LobbyMode::Watch if self.input.is_empty() => {
+                self.notice = Some(String::from("Type the address of the host first"));
+                return;
+            }
+            LobbyMode::Join => NetworkSession::join(&self.input),
+            LobbyMode::Watch => NetworkSession::watch(&self.input),
+        };
+        match session {
+            Ok(session) => {
+                self.session = Some(session);
+                self.notice = None;
+            }
+            Err(error) => self.notice = Some(format!("Could not connect: {error}")),
+        }
+    }
+
+    /// Stops hosting or joining, or leaves the lobby if neither was started.
+    pub fn cancel(&mut self) {
+        if self.session.take().is_none() {
+            self.quit();
+        }
+    }
+
+    /// Waits for the other side, and plays the game once it is there, leaving the lobby after it.
+    fn update(&mut self) -> AppResult<()> {
+        let Some(session) = self.session.as_mut() else {
+            return Ok(());
+        };
+        // only the host is ever asked for the game, to welcome the guest with
+        let options = match self.mode {
+            LobbyMode::Host(options) => options,
+            LobbyMode::Join | LobbyMode::Watch => GameOptions::default(),
+        };
+        for event in session.poll(|| starting_game(options)) {
+            let saved = match event {
+                NetworkEvent::Joined => starting_game(options),
+                NetworkEvent::Welcomed(saved) => saved,
+                NetworkEvent::Rejected(reason) => {
+                    self.session = None;
+                    self.notice = Some(format!("Could not join: {reason}"));
+                    return Ok(());
+                }
+                _ => continue,
+            };
+            let Some(session) = self.session.take() else {
+                continue;
+            };
+            let tui = self.tui.take().expect("should always contain a tui");
+            Chess::network(&mut *tui, session, &saved).launch_chess()?;
+            self.tui = Some(tui);
+            self.quit();
+            return Ok(());
+        }
+        Ok(())
+    }
+}
+
+/// The game as it stands before the first move, with the options of the host.
+fn starting_game(options: GameOptions) -> SavedGame {
+    let board = options.board();
+    let clock = options
+        .time_control
+        .map(|time_control| Clock::new(time_control, board.turn()));
+    SavedGame::new(
+        options,
+        &Game::with_board(board),
+        clock.as_ref(),
+        PieceSet::default(),
+        &[],
+    )
+}
@@ -0,0 +1,152 @@
+use std::net::SocketAddr;
+
+use super::{Lobby, LobbyMode};
+use crate::chess::network::RECONNECT_WINDOW;
+use tui::{
+    backend::Backend,
+    layout::Alignment,
+    prelude::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Paragraph},
+    Frame,
+};
+
+/// The most addresses of this machine shown to the host for the guest to join at.
+const MAX_LISTED_ADDRESSES: usize = 3;
+
+/// Renders the user interface widgets.
+pub fn render<B: Backend>(lobby: &mut Lobby<B>, frame: &mut Frame<'_, B>) {
+    outer_block(lobby, frame);
+    titles(lobby, frame);
+    input(lobby, frame);
+    status(lobby, frame);
+    key_hints(lobby, frame);
+}
+
+#[inline(always)]
+fn outer_block<B: Backend>(lobby: &mut Lobby<B>, frame: &mut Frame<'_, B>) {
+    let title = match lobby.mode() {
+        LobbyMode::Host(_) => "Host LAN Game",
+        LobbyMode::Join => "Join LAN Game",
+        LobbyMode::Watch => "Watch LAN Game",
+    };
+    let outer_block = Block::default()
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    frame.render_widget(outer_block, frame.size());
+}
+
+#[inline(always)]
+fn titles<B: Backend>(lobby: &mut Lobby<B>, frame: &mut Frame<'_, B>) {
+    let y_axis = (frame.size().height / 2).saturating_sub(3);
+
+    let title_style = Style::default()
+        .add_modifier(Modifier::BOLD)
+        .fg(Color::White);
+    let title = match lobby.mode() {
+        LobbyMode::Host(options) => options.name(),
+        LobbyMode::Join | LobbyMode::Watch => String::from("Chess"),
+    };
+    let title = Paragraph::new(title)
+        .style(title_style)
+        .alignment(Alignment::Center);
+    frame.render_widget(title, Rect::new(0, y_axis, frame.size().width, 1));
+
+    let sub_title_style = Style::default()
+        .add_modifier(Modifier::ITALIC)
+        .fg(Color::White);
+    let sub_title = match lobby.mode() {
+        LobbyMode::Host(_) => "Play someone on the local network, who joins this game",
+        LobbyMode::Join => "Play someone on the local network, by joining their game",
+        LobbyMode::Watch => "Watch a game on the local network while it is played",
+    };
+    let sub_title = Paragraph::new(sub_title)
+        .style(sub_title_style)
+        .alignment(Alignment::Center);
+    frame.render_widget(sub_title, Rect::new(0, y_axis + 1, frame.size().width, 1));
+}
+
+/// The port or the address being typed, with a cursor until the lobby starts waiting.
+#[inline(always)]
+fn input<B: Backend>(lobby: &mut Lobby<B>, frame: &mut Frame<'_, B>) {
+    let label = match lobby.mode() {
+        LobbyMode::Host(_) => "Port",
+        LobbyMode::Join | LobbyMode::Watch => "Address",
+    };
+    let (cursor, style) = match lobby.session() {
+        Some(_) => ("", Style::default().fg(Color::DarkGray)),
+        None => ("_", Style::default().fg(Color::Cyan)),
+    };
+    let text = format!("{label}: {}{cursor}", lobby.input());
+    let area = Rect::new(0, frame.size().height / 2, frame.size().width, 1);
+    let paragraph = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(style);
+    frame.render_widget(paragraph, area);
+}
+
+/// What the lobby is waiting on, or the last notice.
+#[inline(always)]
+fn status<B: Backend>(lobby: &mut Lobby<B>, frame: &mut Frame<'_, B>) {
+    let text = match (lobby.notice(), lobby.session(), lobby.mode()) {
+        (Some(notice), _, _) => notice.to_owned(),
+        (None, Some(session), LobbyMode::Host(_)) => {
+            let port = session.port().unwrap_or_default();
+            // a machine has few interfaces, but only as many addresses are listed as fit on a line
+            let addresses: Vec<String> = lobby
+                .local_addresses()
+                .iter()
+                .take(MAX_LISTED_ADDRESSES)
+                .map(|address| SocketAddr::new(*address, port).to_string())
+                .collect();
+            match addresses.is_empty() {
+                true => format!("Waiting for your opponent to join on port {port}…"),
+                false => format!(
+                    "Waiting for your opponent to join at {}…",
+                    addresses.join(" or ")
+                ),
+            }
+        }
+        (None, Some(session), LobbyMode::Join | LobbyMode::Watch) => {
+            format!(
+                "Waiting for the host at {} to let you in…",
+                session.address()
+            )
+        }
+        (None, None, LobbyMode::Host(_)) => format!(
+            "Either side may reconnect within {}s if the connection drops",
+            RECONNECT_WINDOW.as_secs()
+        ),
+        (None, None, LobbyMode::Join | LobbyMode::Watch) => {
+            String::from("Type the address the host sees, e.g. 192.168.1.20 or 192.168.1.20:7878")
+        }
+    };
+    let area = Rect::new(
+        1,
+        frame.size().height / 2 + 2,
+        frame.size().width.saturating_sub(2),
+        1,
+    );
+    let paragraph = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::White));
+    frame.render_widget(paragraph, area);
+}
+
+#[inline(always)]
+fn key_hints<B: Backend>(lobby: &mut Lobby<B>, frame: &mut Frame<'_, B>) {
+    let text = match (lobby.session(), lobby.mode()) {
+        (Some(_), _) => "Esc stop waiting",
+        (None, LobbyMode::Host(_)) => "0-9 port · Enter host · Esc back",
+        (None, LobbyMode::Join) => "Enter join · Esc back",
+        (None, LobbyMode::Watch) => "Enter watch · Esc back",
+    };
+    let y_axis = frame.size().height.saturating_sub(2);
+    let area = Rect::new(1, y_axis, frame.size().width.saturating_sub(2), 1);
+    let hints = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(hints, area);
+}
@@ -1,43 +1,1500 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
 use crate::{app::AppResult, event::Event, tui::Tui};
-use tui::prelude::Backend;
+use tui::prelude::{Backend, Rect};
 
-use self::{game::Game, handler::handle_key_events};
+use self::{
+    analysis::AnalysisBoard,
+    book::OpeningBook,
+    clock::{Clock, TimeControl},
+    correspondence::GameFile,
+    diagram::Diagram,
+    engine::{search::SearchLimits, Analysis},
+    game::{
+        board::{Board, Position},
+        board_widget::BoardScale,
+        chess_move::Move,
+        parse_san,
+        pgn::{ABANDONED, TIME_FORFEIT},
+        piece::{PieceColor, PieceSet, PieceType},
+        san,
+        variant::Variant,
+        Game, GameStatus,
+    },
+    handler::{handle_key_events, handle_mouse_events},
+    hint::Hint,
+    network::{protocol::Message, NetworkEvent, NetworkSession},
+    openings::Openings,
+    profiles::{computer_rating, Players, Profile},
+    puzzle::PuzzleSession,
+    review::Review,
+    save::SavedGame,
+    setup::{Blindfold, GameOptions, Opponent},
+    tutorial::Tutorial,
+};
 
+mod analysis;
+pub mod archive;
+mod book;
+mod clock;
+pub mod coordinates;
+pub mod correspondence;
+mod diagram;
+pub mod editor;
+mod engine;
+pub mod epd;
 mod game;
 mod handler;
+mod hint;
+pub mod lobby;
+mod network;
+mod openings;
+pub mod profiles;
+mod puzzle;
+mod review;
+mod save;
+pub mod setup;
+mod storage;
+mod tutorial;
 mod ui;
 
 pub struct Chess<'a, B: Backend> {
     running: bool,
     tui: Option<&'a mut Tui<B>>,
     pub game: Game,
+    /// The options the game was set up with.
+    pub options: GameOptions,
+    /// The profiles playing the game, by name.
+    players: Players,
+    /// The profiles playing the game as they stood when it started, and once it was rated.
+    profiles: Vec<Profile>,
+    /// Signifies whether the game was rated once it finished, changing the ratings of its players.
+    is_rated: bool,
+    /// The puzzles being solved, when playing puzzles rather than a game.
+    puzzles: Option<PuzzleSession>,
+    /// The lessons being worked through, when following the tutorial rather than playing a game.
+    tutorial: Option<Tutorial>,
+    /// The clock of the game, if it is timed.
+    clock: Option<Clock>,
+    /// The search of the computer for its next move, while it is thinking.
+    computer: Option<Analysis>,
+    /// The connection to the opponent, when playing them over the network.
+    network: Option<NetworkSession>,
+    /// The file the game is kept in, when playing it by correspondence.
+    correspondence: Option<GameFile>,
+    /// The side offering a draw in a network game, until the offer is answered or a move is played.
+    draw_offer: Option<PieceColor>,
+    /// Signifies whether the player was asked to confirm resigning.
+    is_resigning: bool,
+    /// The move the player queued while waiting for their opponent, played as soon as it is their turn.
+    premove: Option<Move>,
+    /// The move suggested to the player to move, once they have asked for a hint.
+    hint: Option<Hint>,
+    /// The side each hint was given to, in the order they were asked for.
+    hints_used: Vec<PieceColor>,
+    /// The exploration of the game with the engine, when in analysis mode.
+    analysis: Option<AnalysisBoard>,
+    /// The review of the game, started once the game is over.
+    review: Option<Review>,
+    /// Signifies whether the game is already in the archive, as it is once finished or when replaying it.
+    is_archived: bool,
+    /// A message for the player about the last thing they did, such as where the game was exported to.
+    notice: Option<String>,
+    /// The opening book of the player, if they have put one in the data directory.
+    book: Option<OpeningBook>,
+    /// The named openings, used to tell which opening is being played.
+    openings: Openings,
+    /// The square currently highlighted for keyboard input.
+    cursor: Position,
+    /// The square of the piece picked up by the player, if any.
+    selected: Option<Position>,
+    /// The pending promotion waiting for the player to pick a piece, if any.
+    promotion: Option<Promotion>,
+    /// The piece picked from the pocket to be dropped in Crazyhouse, if any.
+    drop: Option<PieceType>,
+    /// The move being typed in algebraic notation, in a blindfold game.
+    move_input: String,
+    /// Until when the pieces are shown in a blindfold game, after the player asked to peek at them.
+    peek_until: Option<Instant>,
+    /// The symbols the pieces are drawn with.
+    piece_set: PieceSet,
+    /// The area the board was last rendered in, used to map mouse clicks to squares.
+    board_area: Rect,
+    /// The scale the board was last rendered at, used to map mouse clicks to squares.
+    board_scale: BoardScale,
+    /// The area the promotion picker was last rendered in, used to map mouse clicks to pieces.
+    promotion_area: Rect,
+    /// The area the pocket of the side to move was last rendered in, used to map mouse clicks to pieces.
+    pocket_area: Rect,
+}
+
+/// The share of the remaining time the computer spends on a move, as if there were this many moves left to play.
+const MOVES_TO_GO: u32 = 30;
+
+/// The time the computer spends on a move when the game is not timed.
+const UNTIMED_THINKING_TIME: Duration = Duration::from_secs(2);
+
+/// The least and the most time the computer spends on a move in a timed game.
+const THINKING_TIME_RANGE: (Duration, Duration) =
+    (Duration::from_millis(50), Duration::from_secs(10));
+
+/// The limits the computer searches for its move within, given the time control and the time it has left
+/// in a timed game.
+fn computer_limits(clock: Option<(TimeControl, Duration)>) -> SearchLimits {
+    let time = match clock {
+        Some((time_control, remaining)) => {
+            let (least, most) = THINKING_TIME_RANGE;
+            (remaining / MOVES_TO_GO + time_control.increment / 2).clamp(least, most)
+        }
+        None => UNTIMED_THINKING_TIME,
+    };
+    SearchLimits::time(time)
+}
+
+/// How long the pieces are shown for when peeking at them in a blindfold game.
+const PEEK_TIME: Duration = Duration::from_secs(3);
+
+/// The longest move that can be typed, fitting any move in algebraic notation.
+const MAX_MOVE_LENGTH: usize = 10;
+
+/// Whether there is an unfinished game saved to resume.
+pub fn has_saved_game() -> bool {
+    SavedGame::exists()
+}
+
+/// Reads the endgame tablebase from the directory instead of the one in the data directory.
+pub fn set_tablebase_directory(dir: PathBuf) {
+    engine::tablebase::set_directory(dir);
+}
+
+/// A pawn move to the last rank, waiting for the player to choose what to promote to.
+pub struct Promotion {
+    mv: Move,
+    /// The index into [`PieceType::PROMOTIONS`] of the highlighted choice.
+    index: usize,
+}
+
+impl Promotion {
+    fn new(mv: Move) -> Self {
+        Self { mv, index: 0 }
+    }
+
+    pub fn choice(&self) -> PieceType {
+        PieceType::PROMOTIONS[self.index]
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    fn previous(&mut self) {
+        if self.index > 0 {
+            self.index -= 1;
+        }
+    }
+
+    fn next(&mut self) {
+        if self.index < PieceType::PROMOTIONS.len() - 1 {
+            self.index += 1;
+        }
+    }
 }
 
 impl<'a, B: Backend> Chess<'a, B> {
-    pub fn new(tui: &'a mut Tui<B>) -> Self {
+    pub fn new(tui: &'a mut Tui<B>, options: GameOptions) -> Self {
+        let options = options.resolve();
+        let board = options.board();
         Self {
             running: true,
             tui: Some(tui),
-            game: Game::new(),
+            game: Game::with_board(board),
+            clock: options
+                .time_control
+                .map(|time_control| Clock::new(time_control, board.turn())),
+            computer: None,
+            network: None,
+            correspondence: None,
+            draw_offer: None,
+            is_resigning: false,
+            premove: None,
+            hint: None,
+            hints_used: Vec::new(),
+            options,
+            players: Players::default(),
+            profiles: Vec::new(),
+            is_rated: false,
+            puzzles: None,
+            tutorial: None,
+            analysis: None,
+            review: None,
+            is_archived: false,
+            notice: None,
+            book: OpeningBook::load(),
+            openings: Openings::load(),
+            cursor: Position::new(4, 1),
+            selected: None,
+            promotion: None,
+            drop: None,
+            move_input: String::new(),
+            peek_until: None,
+            piece_set: PieceSet::default(),
+            board_area: Rect::default(),
+            board_scale: BoardScale::default(),
+            promotion_area: Rect::default(),
+            pocket_area: Rect::default(),
         }
     }
 
+    /// Constructs a [`Chess`] screen playing a game between the profiles, which is rated once it is over
+    /// if every side not played by the computer has a profile.
+    pub fn with_players(tui: &'a mut Tui<B>, options: GameOptions, players: Players) -> Self {
+        let mut chess = Self::new(tui, options);
+        chess.set_players(players);
+        chess
+    }
+
+    /// Constructs a [`Chess`] screen for solving puzzles from the puzzle file, rather than playing a game.
+    pub fn puzzles(tui: &'a mut Tui<B>) -> Self {
+        let mut chess = Self::new(tui, GameOptions::default());
+        chess.puzzles = Some(PuzzleSession::new(&mut chess.game));
+        chess
+    }
+
+    /// Constructs a [`Chess`] screen walking a beginner through the lessons of the tutorial, rather than playing a game.
+    pub fn tutorial(tui: &'a mut Tui<B>) -> Self {
+        let mut chess = Self::new(tui, GameOptions::default());
+        chess.tutorial = Some(Tutorial::new(&mut chess.game));
+        chess
+    }
+
+    /// Constructs a [`Chess`] screen continuing the saved game, if there is one.
+    pub fn resume(tui: &'a mut Tui<B>) -> Option<Self> {
+        let saved = SavedGame::load()?;
+        let mut chess = Self::with_players(tui, saved.options, saved.players.clone());
+        chess.restore(&saved);
+        chess.piece_set = saved.piece_set;
+        chess.hints_used = saved.hints_used;
+        Some(chess)
+    }
+
+    /// Constructs a [`Chess`] screen playing the opponent on the other side of the network session,
+    /// starting from the game as the host has it.
+    pub fn network(tui: &'a mut Tui<B>, session: NetworkSession, saved: &SavedGame) -> Self {
+        let mut chess = Self::new(tui, saved.options);
+        chess.restore(saved);
+        chess.network = Some(session);
+        chess
+    }
+
+    /// Constructs a [`Chess`] screen playing the correspondence game kept in the file.
+    pub fn correspondence(tui: &'a mut Tui<B>, mut file: GameFile) -> Result<Self, String> {
+        let (game, _) = file.read()?;
+        let mut chess = Self::new(tui, GameOptions::of_game(&game));
+        chess.game = game;
+        chess.correspondence = Some(file);
+        // a game which ended while away was archived by the player who ended it
+        chess.is_archived = chess.game.status().is_over();
+        chess.finish();
+        Ok(chess)
+    }
+
+    /// Sets the game and the clock to the ones saved.
+    fn restore(&mut self, saved: &SavedGame) {
+        self.game = saved.game();
+        self.clock = saved.clock(&self.game);
+        self.selected = None;
+        self.promotion = None;
+        self.drop = None;
+        self.premove = None;
+    }
+
+    /// Constructs a [`Chess`] screen replaying a finished game from the archive, stepping through it in analysis mode.
+    pub fn replay(tui: &'a mut Tui<B>, pgn: &str) -> Result<Self, String> {
+        let (game, _) = Game::from_pgn(pgn)?;
+        let mut chess = Self::new(tui, GameOptions::of_game(&game));
+        chess.game = game;
+        chess.is_archived = true;
+        chess.review = Some(Review::start(&chess.game));
+        chess.jump_to(0);
+        Ok(chess)
+    }
+
+    /// Leaves the chess screen, saving the game first if it is unfinished.
     pub fn quit(&mut self) {
+        // failing to save is not worth keeping the player from quitting over
+        let _ = self.save();
         self.running = false;
     }
 
+    /// Saves the game so that it can be resumed from the main menu, as long as it is under way and unfinished.
+    fn save(&self) -> std::io::Result<bool> {
+        let game = match self.analysis.as_ref() {
+            Some(analysis) => analysis.game(),
+            None => &self.game,
+        };
+        let is_game =
+            !self.is_exercise() && self.network.is_none() && self.correspondence.is_none();
+        if !is_game || game.history().is_empty() || game.status().is_over() {
+            return Ok(false);
+        }
+        let mut saved = SavedGame::new(
+            self.options,
+            game,
+            self.clock.as_ref(),
+            self.piece_set,
+            &self.hints_used,
+        );
+        saved.players = self.players.clone();
+        saved.save()?;
+        Ok(true)
+    }
+
+    /// Saves the game on demand, telling the player how it went.
+    pub fn save_game(&mut self) {
+        self.notice = Some(match self.save() {
+            Ok(true) => String::from("Game saved, resume it from the main menu"),
+            Ok(false) => String::from("Only unfinished games can be saved"),
+            Err(error) => format!("Could not save the game: {error}"),
+        });
+    }
+
     pub fn launch_chess(&mut self) -> AppResult<()> {
         while self.running {
+            self.update();
             let tui = self.tui.take().expect("should always contain a tui");
             tui.terminal.draw(|frame| ui::render(self, frame))?;
             match tui.events.next()? {
                 Event::Tick => {}
                 Event::Key(key_event) => handle_key_events(key_event, self)?,
-                Event::Mouse(_) => {}
+                Event::Mouse(mouse_event) => handle_mouse_events(mouse_event, self)?,
                 Event::Resize(_, _) => {}
             }
             self.tui = Some(tui);
         }
         Ok(())
     }
+
+    /// Moves the cursor by the given number of files and ranks, stopping at the edge of the board.
+    pub fn move_cursor(&mut self, file: i8, rank: i8) {
+        if let Some(position) = self.cursor.offset(file, rank) {
+            self.cursor = position;
+        }
+    }
+
+    /// Switches to the next set of piece symbols.
+    pub fn cycle_piece_set(&mut self) {
+        self.piece_set = self.piece_set.next();
+    }
+
+    /// Keeps the game going between the moves of the player: ends it once a player runs out of time,
+    /// lets the computer think and move when it is its turn, and hears from the opponent over the network.
+    fn update(&mut self) {
+        if self.analysis.is_some() || self.is_exercise() {
+            return;
+        }
+        self.update_network();
+        self.update_correspondence();
+        // in a network game each side only calls its own flag, as the clocks of the two sides may differ slightly
+        let flagged = self.clock.as_ref().and_then(Clock::flagged);
+        let is_own_flag =
+            |loser: &PieceColor| self.network.is_none() || self.local_color() == Some(*loser);
+        if let Some(loser) = flagged.filter(is_own_flag) {
+            self.game.conclude(GameStatus::OutOfTime {
+                winner: loser.opposite(),
+            });
+            if let Some(network) = self.network.as_mut() {
+                network.send(Message::Flag);
+            }
+            self.finish();
+        }
+        if self.game.status().is_over() || !self.is_computer_turn() {
+            self.computer = None;
+            return;
+        }
+
+        match self.computer.as_ref() {
+            None => self.start_thinking(),
+            Some(computer) if computer.is_finished() => {
+                let best = computer.best_move();
+                self.computer = None;
+                if let Some(mv) = best {
+                    self.commit(mv);
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// Acts on what happened on the network since the last update.
+    fn update_network(&mut self) {
+        let Some(network) = self.network.as_mut() else {
+            return;
+        };
+        let (options, game, clock) = (self.options, &self.game, self.clock.as_ref());
+        let events =
+            network.poll(|| SavedGame::new(options, game, clock, PieceSet::default(), &[]));
+        for event in events {
+            self.handle_network_event(event);
+        }
+    }
+
+    fn handle_network_event(&mut self, event: NetworkEvent) {
+        let opponent = self.local_color().map(PieceColor::opposite);
+        let is_over = self.game.status().is_over();
+        match event {
+            NetworkEvent::Joined => {
+                if let Some(clock) = self.clock.as_mut().filter(|_| !is_over) {
+                    if !self.game.history().is_empty() {
+                        clock.resume();
+                    }
+                }
+                self.notice = None;
+                self.broadcast_clock();
+            }
+            NetworkEvent::SpectatorJoined => {
+                self.broadcast_clock();
+                self.broadcast_ending();
+            }
+            NetworkEvent::Welcomed(saved) => {
+                if !is_over {
+                    self.restore(&saved);
+                }
+                self.notice = None;
+            }
+            NetworkEvent::Lost if !is_over => {
+                if let Some(clock) = self.clock.as_mut() {
+                    clock.stop();
+                }
+                self.draw_offer = None;
+                self.broadcast_clock();
+            }
+            NetworkEvent::Abandoned if self.is_spectating() => {
+                self.notice = Some(String::from(
+                    "The host is gone, the game can no longer be watched",
+                ));
+            }
+            NetworkEvent::Abandoned => {
+                self.game.conclude(GameStatus::Abandoned);
+                self.finish();
+            }
+            NetworkEvent::Rejected(reason) => {
+                self.notice = Some(format!("The host turned this game away: {reason}"));
+            }
+            NetworkEvent::Received(Message::Move { uci, times }) if !is_over => {
+                let mv = self
+                    .game
+                    .board()
+                    .legal_moves()
+                    .into_iter()
+                    .chain(self.game.legal_drops())
+                    .find(|mv| mv.uci() == uci && Some(mv.piece.color()) != self.local_color());
+                let Some(mv) = mv else {
+                    return;
+                };
+                self.hint = None;
+                self.draw_offer = None;
+                self.game.make_move(mv);
+                if let Some(clock) = self.clock.as_mut() {
+                    clock.press(mv.piece.color());
+                    // the times of the mover are the ones to trust, as they were not delayed on the way here
+                    if let Some((white, black)) = times {
+                        clock.sync(white, black);
+                    }
+                }
+                if let Some(network) = self.network.as_mut().filter(|network| network.is_host()) {
+                    network.broadcast(Message::Move { uci, times });
+                }
+                self.finish();
+                self.play_premove();
+            }
+            NetworkEvent::Received(Message::Ended { status }) if self.is_spectating() => {
+                self.game.conclude(status);
+                self.finish();
+            }
+            NetworkEvent::Received(Message::Clock {
+                white,
+                black,
+                is_running,
+            }) if self.is_spectating() && !is_over => {
+                if let Some(clock) = self.clock.as_mut() {
+                    clock.sync(white, black);
+                    match is_running {
+                        true => clock.resume(),
+                        false => clock.stop(),
+                    }
+                }
+            }
+            NetworkEvent::Received(Message::OfferDraw) if !is_over => {
+                self.draw_offer = opponent;
+            }
+            NetworkEvent::Received(Message::AcceptDraw)
+                if self.draw_offer == self.local_color() =>
+            {
+                self.game.conclude(GameStatus::DrawAgreed);
+                self.finish();
+            }
+            NetworkEvent::Received(Message::DeclineDraw)
+                if self.draw_offer == self.local_color() =>
+            {
+                self.draw_offer = None;
+                self.notice = Some(String::from("Your opponent declined the draw"));
+            }
+            NetworkEvent::Received(Message::Resign | Message::Flag) => {
+                if let Some(winner) = self.local_color() {
+                    let outcome = match event {
+                        NetworkEvent::Received(Message::Flag) => GameStatus::OutOfTime { winner },
+                        _ => GameStatus::Resignation { winner },
+                    };
+                    self.game.conclude(outcome);
+                    self.finish();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Reloads the correspondence game once the file changed, such as when the opponent moved.
+    fn update_correspondence(&mut self) {
+        let Some(file) = self.correspondence.as_mut() else {
+            return;
+        };
+        if !file.has_changed() {
+            return;
+        }
+        let game = match file.read() {
+            Ok((game, _)) => game,
+            Err(error) => {
+                self.notice = Some(format!("Could not read the game file: {error}"));
+                return;
+            }
+        };
+        // the file is only taken to hold the same game if it goes on from the moves known here
+        let is_same_game = game.history().len() >= self.game.history().len()
+            && self
+                .game
+                .history()
+                .iter()
+                .zip(game.history())
+                .all(|(known, read)| known.san == read.san && known.board == read.board);
+        if !is_same_game {
+            self.notice = Some(String::from("The game file no longer holds this game"));
+            return;
+        }
+        if game.history().len() > self.game.history().len() || game.status() != self.game.status() {
+            self.game = game;
+            self.selected = None;
+            self.promotion = None;
+            self.drop = None;
+            self.notice = None;
+            self.finish();
+        }
+    }
+
+    /// Writes the game to its file when playing by correspondence, reloading the file if that fails
+    /// so that the game shows what the opponent will see.
+    fn write_correspondence(&mut self) {
+        let Some(file) = self.correspondence.as_mut() else {
+            return;
+        };
+        if let Err(error) = file.write(&self.game) {
+            if let Ok((game, _)) = file.read() {
+                self.game = game;
+            }
+            self.notice = Some(format!(
+                "Could not write the move to the game file: {error}"
+            ));
+        }
+    }
+
+    /// The side played on this screen in a network or correspondence game, which is the only side it may move.
+    pub fn local_color(&self) -> Option<PieceColor> {
+        let network = self
+            .network
+            .as_ref()
+            .filter(|network| !network.is_spectator())
+            .map(NetworkSession::color);
+        network.or(self.correspondence.as_ref().map(GameFile::color))
+    }
+
+    pub fn correspondence_file(&self) -> Option<&GameFile> {
+        self.correspondence.as_ref()
+    }
+
+    /// Whether the game is only watched on this screen, as it is played on another.
+    pub fn is_spectating(&self) -> bool {
+        self.network
+            .as_ref()
+            .is_some_and(NetworkSession::is_spectator)
+    }
+
+    pub fn network_session(&self) -> Option<&NetworkSession> {
+        self.network.as_ref()
+    }
+
+    pub fn draw_offer(&self) -> Option<PieceColor> {
+        self.draw_offer
+    }
+
+    /// Offers the opponent a draw in a network game, or accepts the draw they offered.
+    pub fn offer_draw(&mut self) {
+        let (Some(local), false) = (self.local_color(), self.game.status().is_over()) else {
+            return;
+        };
+        match self.draw_offer {
+            Some(color) if color != local => {
+                self.send(Message::AcceptDraw);
+                self.game.conclude(GameStatus::DrawAgreed);
+                self.finish();
+            }
+            Some(_) => {}
+            None => {
+                self.send(Message::OfferDraw);
+                self.draw_offer = Some(local);
+            }
+        }
+    }
+
+    /// Turns down the draw the opponent offered.
+    pub fn decline_draw(&mut self) {
+        if self.draw_offer.is_some() && self.draw_offer != self.local_color() {
+            self.send(Message::DeclineDraw);
+            self.draw_offer = None;
+        }
+    }
+
+    pub fn is_resigning(&self) -> bool {
+        self.is_resigning
+    }
+
+    /// Asks the player to confirm resigning the game, and resigns once they have.
+    ///
+    /// The side resigning is the one played here in a network game, the side not played by the computer,
+    /// and otherwise the side to move.
+    pub fn resign(&mut self) {
+        if self.is_exercise()
+            || self.is_spectating()
+            || self.analysis.is_some()
+            || self.game.status().is_over()
+        {
+            return;
+        }
+        if !self.is_resigning {
+            self.is_resigning = true;
+            return;
+        }
+        self.is_resigning = false;
+        let loser = match (self.local_color(), self.options.opponent) {
+            (Some(local), _) => local,
+            (None, Opponent::Computer(computer)) => computer.opposite(),
+            (None, Opponent::Human) => self.game.board().turn(),
+        };
+        self.send(Message::Resign);
+        self.game.conclude(GameStatus::Resignation {
+            winner: loser.opposite(),
+        });
+        self.write_correspondence();
+        self.finish();
+    }
+
+    /// Goes on playing rather than resigning.
+    pub fn keep_playing(&mut self) {
+        self.is_resigning = false;
+    }
+
+    /// Sends a message to the opponent, when playing over the network.
+    fn send(&mut self, message: Message) {
+        if let Some(network) = self.network.as_mut() {
+            network.send(message);
+        }
+    }
+
+    /// Passes a message on to the spectators, when hosting a network game.
+    fn broadcast(&mut self, message: Message) {
+        if let Some(network) = self.network.as_mut().filter(|network| network.is_host()) {
+            network.broadcast(message);
+        }
+    }
+
+    /// Lets the spectators know the times on the clock, and whether it is running.
+    fn broadcast_clock(&mut self) {
+        if let Some(clock) = self.clock.as_ref() {
+            self.broadcast(Message::Clock {
+                white: clock.remaining(PieceColor::White),
+                black: clock.remaining(PieceColor::Black),
+                is_running: clock.is_running(),
+            });
+        }
+    }
+
+    /// Lets the spectators know how the game ended, if that does not follow from the moves.
+    fn broadcast_ending(&mut self) {
+        let status = self.game.status();
+        if let GameStatus::Resignation { .. }
+        | GameStatus::OutOfTime { .. }
+        | GameStatus::DrawAgreed
+        | GameStatus::Abandoned = status
+        {
+            self.broadcast(Message::Ended { status });
+        }
+    }
+
+    /// Whether the side to move is not played on this screen: by the computer, by the opponent over the network,
+    /// by nobody while the connection to the opponent is lost, by either player while spectating,
+    /// or by the correspondent reading the game file elsewhere.
+    fn is_opponent_turn(&self) -> bool {
+        let is_remote_turn = self
+            .network
+            .as_ref()
+            .map(|network| {
+                network.is_spectator()
+                    || !network.is_connected()
+                    || network.color() != self.game.board().turn()
+            })
+            .unwrap_or(false);
+        let is_correspondent_turn = self
+            .correspondence
+            .as_ref()
+            .is_some_and(|file| file.color() != self.game.board().turn());
+        self.is_computer_turn() || is_remote_turn || is_correspondent_turn
+    }
+
+    /// Plays a move from the opening book if it knows the position, and otherwise starts searching for one
+    /// for as long as the clock allows.
+    fn start_thinking(&mut self) {
+        let board = self.game.board();
+        let book = self.book.as_ref().filter(|_| self.is_standard_opening());
+        if let Some(mv) = book.and_then(|book| book.pick_move(board)) {
+            return self.commit(mv);
+        }
+        let clock = self
+            .clock
+            .as_ref()
+            .map(|clock| (clock.time_control(), clock.remaining(board.turn())));
+        self.computer = Some(Analysis::with_limits(&self.game, computer_limits(clock)));
+    }
+
+    /// Whether the game is one of standard chess from the usual starting position, which is all the opening book
+    /// and the table of openings know: a position of another variant may well share its key with one of them.
+    fn is_standard_opening(&self) -> bool {
+        let start = self
+            .game
+            .history()
+            .first()
+            .map(|ply| &ply.board)
+            .unwrap_or(self.game.board());
+        start.variant() == Variant::Standard && start.is_same_position(&Board::new())
+    }
+
+    /// Whether the engine cannot play the variant of the game, telling the player so.
+    fn refuses_variant(&mut self) -> bool {
+        let variant = self.game.board().variant();
+        if !variant.has_engine_support() {
+            self.notice = Some(format!("The engine does not play {}", variant.name()));
+        }
+        !variant.has_engine_support()
+    }
+
+    /// Whether a puzzle or a lesson is being played rather than a game.
+    fn is_exercise(&self) -> bool {
+        self.puzzles.is_some() || self.tutorial.is_some()
+    }
+
+    fn is_computer_turn(&self) -> bool {
+        self.options.opponent == Opponent::Computer(self.game.board().turn())
+    }
+
+    /// Whether the game is being played against the clock or the computer and is not over yet,
+    /// which rules out getting help from the engine.
+    fn is_live(&self) -> bool {
+        let is_remote = self.network.is_some() || self.correspondence.is_some();
+        (self.clock.is_some() || self.options.opponent != Opponent::Human || is_remote)
+            && !self.game.status().is_over()
+    }
+
+    /// Stops the clock and the computer once the game is over, and starts reviewing it.
+    fn finish(&mut self) {
+        if !self.game.status().is_over() || self.review.is_some() {
+            return;
+        }
+        if let Some(clock) = self.clock.as_mut() {
+            clock.stop();
+        }
+        self.computer = None;
+        self.premove = None;
+        self.hint = None;
+        self.draw_offer = None;
+        self.is_resigning = false;
+        self.review = Some(Review::start(&self.game));
+        self.broadcast_ending();
+        // a finished game has nothing left to resume, but a game played with someone elsewhere was never saved
+        if self.network.is_none() && self.correspondence.is_none() {
+            let _ = SavedGame::delete();
+        }
+        // spectators leave archiving the game to the players
+        if !self.is_archived && !self.is_spectating() {
+            self.is_archived = true;
+            // the game can still be exported from the review if it could not be archived
+            let _ = archive::record(&self.game.to_pgn(&self.archive_tags(), &[]));
+            self.rate();
+        }
+    }
+
+    /// Sets the profiles playing the game, reading their ratings.
+    fn set_players(&mut self, players: Players) {
+        self.profiles = profiles::load()
+            .into_iter()
+            .filter(|profile| {
+                [PieceColor::White, PieceColor::Black]
+                    .into_iter()
+                    .any(|color| players.get(color) == Some(profile.name.as_str()))
+            })
+            .collect();
+        self.players = players;
+    }
+
+    /// The profile playing the side, if any, with its rating as it stood when the game started,
+    /// or as it was changed by the game once it was rated.
+    pub fn profile(&self, color: PieceColor) -> Option<&Profile> {
+        let name = self.players.get(color)?;
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    /// Whether the game is rated once it is over: a game played here between profiles, or between a profile
+    /// and the computer, without hints.
+    pub fn is_rated_game(&self) -> bool {
+        let computer = self.options.opponent.computer();
+        !self.is_exercise()
+            && self.network.is_none()
+            && self.correspondence.is_none()
+            && self.hints_used.is_empty()
+            && self.players.is_rated(computer)
+            && [PieceColor::White, PieceColor::Black]
+                .into_iter()
+                .all(|color| computer == Some(color) || self.profile(color).is_some())
+    }
+
+    pub fn is_rated(&self) -> bool {
+        self.is_rated
+    }
+
+    /// Changes the ratings of the profiles playing the finished game, if it is rated.
+    fn rate(&mut self) {
+        if !self.is_rated_game() || self.game.history().is_empty() {
+            return;
+        }
+        let computer = self.options.opponent.computer();
+        let (time_control, winner) = (self.options.time_control, self.game.status().winner());
+        match profiles::rate(&self.players, computer, time_control, winner) {
+            Ok(()) => {
+                self.set_players(self.players.clone());
+                self.is_rated = true;
+            }
+            Err(error) => self.notice = Some(format!("Could not rate the game: {error}")),
+        }
+    }
+
+    /// The tags the game is archived with, besides the ones it knows itself.
+    fn archive_tags(&self) -> Vec<(&'static str, String)> {
+        let mut tags = self.options.variant_tags();
+        for (color, tag) in [(PieceColor::White, "White"), (PieceColor::Black, "Black")] {
+            if self.options.opponent == Opponent::Computer(color) {
+                tags.push((tag, String::from("Computer")));
+            }
+            if let Some(profile) = self.profile(color) {
+                tags.push((tag, profile.name.clone()));
+            }
+            // the game is archived before it is rated, so the ratings are the ones the players went into it with
+            if self.is_rated_game() {
+                let rating = match self.profile(color) {
+                    Some(profile) => profile.rating(),
+                    None => computer_rating(self.options.time_control),
+                };
+                let elo_tag = match color {
+                    PieceColor::White => "WhiteElo",
+                    PieceColor::Black => "BlackElo",
+                };
+                tags.push((elo_tag, rating.to_string()));
+            }
+            if self.local_color() == Some(color.opposite()) {
+                tags.push((tag, String::from("Opponent")));
+            }
+        }
+        let opening = self
+            .openings
+            .find(&self.game)
+            .filter(|_| self.is_standard_opening());
+        if let Some(opening) = opening {
+            tags.push(("ECO", opening.eco.clone()));
+            tags.push(("Opening", opening.name.clone()));
+        }
+        tags.push(("PlyCount", self.game.history().len().to_string()));
+        if let Some(time_control) = self.options.time_control {
+            tags.push((
+                "TimeControl",
+                format!(
+                    "{}+{}",
+                    time_control.initial.as_secs(),
+                    time_control.increment.as_secs()
+                ),
+            ));
+        }
+        match self.game.status() {
+            GameStatus::OutOfTime { .. } => tags.push(("Termination", String::from(TIME_FORFEIT))),
+            GameStatus::Abandoned => tags.push(("Termination", String::from(ABANDONED))),
+            _ => {}
+        }
+        tags
+    }
+
+    pub fn hint(&self) -> Option<&Hint> {
+        self.hint.as_ref()
+    }
+
+    /// The number of hints the player of the side was given.
+    pub fn hints_used(&self, color: PieceColor) -> usize {
+        self.hints_used
+            .iter()
+            .filter(|side| **side == color)
+            .count()
+    }
+
+    /// Asks the engine for a good piece to move, and shows the whole move when asked again for the same position.
+    pub fn ask_hint(&mut self) {
+        if let Some(hint) = self.hint.as_mut() {
+            return hint.reveal();
+        }
+        if self.is_exercise()
+            || self.analysis.is_some()
+            || self.game.status().is_over()
+            || self.network.is_some()
+            || self.correspondence.is_some()
+            || self.is_computer_turn()
+            || self.refuses_variant()
+        {
+            return;
+        }
+        self.notice = None;
+        self.hints_used.push(self.game.board().turn());
+        self.hint = Some(Hint::start(&self.game));
+    }
+
+    /// The squares the hint points at: the piece to move, and where it goes once the hint is revealed.
+    pub fn hint_squares(&self) -> Vec<Position> {
+        let Some(hint) = self.hint.as_ref() else {
+            return Vec::new();
+        };
+        match hint.best_move() {
+            Some(mv) if hint.is_revealed() => vec![mv.from, mv.to],
+            Some(mv) => vec![mv.from],
+            None => Vec::new(),
+        }
+    }
+
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    pub fn premove(&self) -> Option<Move> {
+        self.premove
+    }
+
+    /// The side that may queue a premove right now: the player waiting for their opponent in a clock game.
+    fn premover(&self) -> Option<PieceColor> {
+        if self.clock.is_none() || self.analysis.is_some() || self.game.status().is_over() {
+            return None;
+        }
+        let waiting = self.game.board().turn().opposite();
+        if self.is_spectating() || self.local_color().is_some_and(|local| local != waiting) {
+            return None;
+        }
+        match self.options.opponent {
+            Opponent::Computer(computer) if computer == waiting => None,
+            _ => Some(waiting),
+        }
+    }
+
+    /// The moves the piece on the square may be picked up for: its legal moves, or the moves it could make
+    /// once the opponent has moved, when queuing a premove.
+    fn moves_from(&self, from: Position) -> Vec<Move> {
+        let board = self.game.board();
+        let color = board.piece_at(from).map(|piece| piece.color());
+        if color.is_some() && color == self.premover() {
+            return board.premoves_from(from);
+        }
+        if self.is_opponent_turn() {
+            return Vec::new();
+        }
+        self.game.legal_moves_from(from)
+    }
+
+    /// Plays the queued premove if it is the turn of its side and it is legal now, and otherwise throws it away.
+    fn play_premove(&mut self) {
+        let Some(premove) = self.premove else {
+            return;
+        };
+        if premove.piece.color() != self.game.board().turn() {
+            return;
+        }
+        self.premove = None;
+        if let Some(mv) = self
+            .game
+            .legal_moves_from(premove.from)
+            .into_iter()
+            .find(|mv| mv.to == premove.to && mv.promotion == premove.promotion)
+        {
+            self.commit(mv);
+        }
+    }
+
+    /// Throws away the queued premove, returning whether there was one.
+    pub fn cancel_premove(&mut self) -> bool {
+        self.premove.take().is_some()
+    }
+
+    /// The squares the selected piece may legally move to.
+    ///
+    /// Castling can also be played by moving the king onto its own rook, which is the only way to
+    /// castle in the Chess960 positions where the king already stands on its destination.
+    pub fn targets(&self) -> Vec<Position> {
+        if let Some(variant) = self.drop {
+            return self
+                .game
+                .legal_drops()
+                .iter()
+                .filter(|mv| mv.piece.variant() == variant)
+                .map(|mv| mv.to)
+                .collect();
+        }
+
+        let board = self.game.board();
+        self.selected
+            .map(|from| self.moves_from(from))
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|mv| [Some(mv.to), board.castling_rook(mv)])
+            .flatten()
+            .collect()
+    }
+
+    /// Picks up the piece on the square, or moves the selected piece to it if it is a legal target.
+    pub fn select(&mut self, position: Position) {
+        // moves are only typed in a blindfold game
+        if self.is_typing_moves() {
+            return;
+        }
+        self.cursor = position;
+        if let Some(variant) = self.drop.take() {
+            if let Some(mv) = self
+                .game
+                .legal_drops()
+                .into_iter()
+                .find(|mv| mv.piece.variant() == variant && mv.to == position)
+            {
+                self.commit(mv);
+                return;
+            }
+        }
+        if let Some(from) = self.selected {
+            if self.targets().contains(&position) {
+                self.play(from, position);
+                return;
+            }
+        }
+
+        let is_movable = !self.moves_from(position).is_empty();
+        self.selected = match self.selected {
+            Some(selected) if selected == position => None,
+            _ if is_movable => Some(position),
+            _ => None,
+        };
+    }
+
+    /// Clears the current selection, returning whether there was anything to clear.
+    pub fn deselect(&mut self) -> bool {
+        if self.promotion.take().is_some() || self.drop.take().is_some() {
+            return true;
+        }
+        self.selected.take().is_some() || self.cancel_premove()
+    }
+
+    fn play(&mut self, from: Position, to: Position) {
+        self.selected = None;
+        let Some(mv) = self
+            .moves_from(from)
+            .into_iter()
+            .find(|mv| mv.to == to || self.game.board().castling_rook(mv) == Some(to))
+        else {
+            return;
+        };
+
+        // premoves always promote to a queen, as there is no time to pick
+        if mv.piece.color() != self.game.board().turn() {
+            self.premove = Some(match mv.is_promotion() {
+                true => mv.with_promotion(PieceType::Queen),
+                false => mv,
+            });
+        } else if mv.is_promotion() {
+            self.promotion = Some(Promotion::new(mv));
+        } else {
+            self.commit(mv);
+        }
+    }
+
+    /// Plays a legal move in the game, tries it as the next move of the solution when solving puzzles,
+    /// or explores it when in analysis mode.
+    fn commit(&mut self, mv: Move) {
+        self.notice = None;
+        self.hint = None;
+        if let Some(analysis) = self.analysis.as_mut() {
+            return analysis.play(&mut self.game, mv);
+        }
+        if let Some(tutorial) = self.tutorial.as_mut() {
+            return tutorial.play(&mut self.game, mv);
+        }
+        match self.puzzles.as_mut() {
+            Some(puzzles) => puzzles.play(&mut self.game, mv),
+            None => {
+                self.game.make_move(mv);
+                if let Some(clock) = self.clock.as_mut() {
+                    clock.press(mv.piece.color());
+                }
+                if self.network.is_some() {
+                    let times = self.clock.as_ref().map(|clock| {
+                        (
+                            clock.remaining(PieceColor::White),
+                            clock.remaining(PieceColor::Black),
+                        )
+                    });
+                    self.send(Message::Move {
+                        uci: mv.uci(),
+                        times,
+                    });
+                    self.broadcast(Message::Move {
+                        uci: mv.uci(),
+                        times,
+                    });
+                    self.draw_offer = None;
+                }
+                self.write_correspondence();
+                self.finish();
+                self.play_premove();
+            }
+        }
+    }
+
+    /// Whether the moves are typed rather than played on the board, as they are in a blindfold game until it is over.
+    /// The board is shown as usual when analyzing.
+    pub fn is_typing_moves(&self) -> bool {
+        self.options.blindfold != Blindfold::Off
+            && self.analysis.is_none()
+            && !self.game.status().is_over()
+    }
+
+    /// How much of the board is hidden right now, which is nothing while peeking or outside of a blindfold game.
+    pub fn blindfold(&self) -> Blindfold {
+        match self.is_typing_moves() && !self.is_peeking() {
+            true => self.options.blindfold,
+            false => Blindfold::Off,
+        }
+    }
+
+    pub fn is_peeking(&self) -> bool {
+        self.peek_until
+            .is_some_and(|peek_until| Instant::now() < peek_until)
+    }
+
+    /// Shows the pieces of a blindfold game for a moment, or hides them again when already peeking.
+    pub fn peek(&mut self) {
+        if !self.is_typing_moves() {
+            return;
+        }
+        self.peek_until = match self.is_peeking() {
+            true => None,
+            false => Some(Instant::now() + PEEK_TIME),
+        };
+    }
+
+    pub fn move_input(&self) -> &str {
+        &self.move_input
+    }
+
+    /// Adds the symbol to the move being typed, returning whether it can be part of a move in algebraic notation,
+    /// where castling may also be typed with a lowercase `o`.
+    pub fn type_move_symbol(&mut self, symbol: char) -> bool {
+        let is_move_symbol = matches!(
+            symbol,
+            'a'..='h' | '1'..='8' | 'x' | 'P' | 'N' | 'B' | 'R' | 'Q' | 'K' | 'O' | 'o' | '0' | '-' | '=' | '@' | '+' | '#'
+        );
+        if is_move_symbol && self.move_input.chars().count() < MAX_MOVE_LENGTH {
+            self.notice = None;
+            self.move_input.push(symbol);
+        }
+        is_move_symbol
+    }
+
+    pub fn erase_move_symbol(&mut self) {
+        self.move_input.pop();
+    }
+
+    /// Clears the move being typed, returning whether there was one.
+    pub fn clear_move_input(&mut self) -> bool {
+        let had_input = !self.move_input.is_empty();
+        self.move_input.clear();
+        had_input
+    }
+
+    /// Plays the typed move, telling the player when it is not a legal move or not their turn.
+    pub fn play_typed_move(&mut self) {
+        if self.move_input.is_empty() {
+            return;
+        }
+        if self.is_opponent_turn() {
+            self.notice = Some(String::from("Wait for your opponent to move"));
+            return;
+        }
+        match typed_move(&self.game, &self.move_input) {
+            Some(mv) => {
+                self.move_input.clear();
+                self.peek_until = None;
+                self.commit(mv);
+            }
+            None => self.notice = Some(format!("{} is not a legal move", self.move_input)),
+        }
+    }
+
+    pub fn analysis(&self) -> Option<&AnalysisBoard> {
+        self.analysis.as_ref()
+    }
+
+    /// Starts exploring the game with the engine, or goes back to the game as it was before exploring.
+    ///
+    /// The moves can be stepped through even in the variants the engine does not play, which it then leaves alone.
+    pub fn toggle_analysis(&mut self) {
+        if self.is_exercise() || (self.analysis.is_none() && self.is_live()) {
+            return;
+        }
+        self.selected = None;
+        self.promotion = None;
+        self.drop = None;
+        self.notice = None;
+        match self.analysis.take() {
+            Some(analysis) => self.game = analysis.into_game(),
+            None => self.analysis = Some(AnalysisBoard::new(&mut self.game)),
+        }
+    }
+
+    /// Steps one move back through the explored line, when in analysis mode.
+    pub fn step_back(&mut self) {
+        if let Some(analysis) = self.analysis.as_mut() {
+            self.selected = None;
+            self.drop = None;
+            analysis.back(&mut self.game);
+        }
+    }
+
+    pub fn review(&self) -> Option<&Review> {
+        self.review.as_ref()
+    }
+
+    pub fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    /// Shows the position before the next annotated move of the reviewed game, in analysis mode.
+    pub fn next_critical_moment(&mut self) {
+        let shown = self.analysis.as_ref().map(|analysis| analysis.ply());
+        let moments = self.review.as_ref().map(Review::critical_moments);
+        let next = moments
+            .unwrap_or_default()
+            .into_iter()
+            .find(|moment| shown.map(|shown| *moment > shown).unwrap_or(true));
+        if let Some(moment) = next {
+            self.jump_to(moment);
+        }
+    }
+
+    /// Shows the position before the previous annotated move of the reviewed game, in analysis mode.
+    pub fn previous_critical_moment(&mut self) {
+        let shown = self.analysis.as_ref().map(|analysis| analysis.ply());
+        let moments = self.review.as_ref().map(Review::critical_moments);
+        let previous = moments
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .find(|moment| shown.map(|shown| *moment < shown).unwrap_or(true));
+        if let Some(moment) = previous {
+            self.jump_to(moment);
+        }
+    }
+
+    fn jump_to(&mut self, ply: usize) {
+        if self.analysis.is_none() {
+            self.toggle_analysis();
+        }
+        if let Some(analysis) = self.analysis.as_mut() {
+            self.selected = None;
+            self.drop = None;
+            self.notice = None;
+            analysis.jump(&mut self.game, ply);
+        }
+    }
+
+    /// Writes the position on the board to diagram files, telling the player where they went. The best move
+    /// found when analyzing, or the hint once revealed, is drawn as an arrow.
+    pub fn export_diagram(&mut self) {
+        let best = self
+            .analysis
+            .as_ref()
+            .and_then(AnalysisBoard::best_move)
+            .or_else(|| {
+                self.hint
+                    .as_ref()
+                    .filter(|hint| hint.is_revealed())
+                    .and_then(Hint::best_move)
+            });
+        let arrows = best.map(|mv| (mv.from, mv.to)).into_iter().collect();
+        let diagram = Diagram::new(self.game.board(), self.game.last_move().copied(), arrows);
+        self.notice = Some(match diagram.export() {
+            Ok(path) => format!("Diagram exported to {}.svg, .txt and .ans", path.display()),
+            Err(error) => format!("Could not export the diagram: {error}"),
+        });
+    }
+
+    /// Writes the reviewed game to a PGN file, telling the player where it went.
+    pub fn export_review(&mut self) {
+        let Some(review) = self.review.as_ref() else {
+            return;
+        };
+        let game = match self.analysis.as_ref() {
+            Some(analysis) => analysis.game(),
+            None => &self.game,
+        };
+        self.notice = Some(match review.export(game, &self.options.variant_tags()) {
+            Ok(path) => format!("Exported to {}", path.display()),
+            Err(error) => format!("Could not export the game: {error}"),
+        });
+    }
+
+    /// Steps one move forward through the explored line, when in analysis mode.
+    pub fn step_forward(&mut self) {
+        if let Some(analysis) = self.analysis.as_mut() {
+            self.selected = None;
+            self.drop = None;
+            analysis.forward(&mut self.game);
+        }
+    }
+
+    pub fn puzzle_session(&self) -> Option<&PuzzleSession> {
+        self.puzzles.as_ref()
+    }
+
+    /// Moves on to the next puzzle, when solving puzzles, or to the next lesson of the tutorial.
+    pub fn next_puzzle(&mut self) {
+        if let Some(puzzles) = self.puzzles.as_mut() {
+            puzzles.next(&mut self.game);
+            self.selected = None;
+            self.drop = None;
+        }
+        if let Some(tutorial) = self.tutorial.as_mut() {
+            tutorial.next(&mut self.game);
+            self.selected = None;
+        }
+    }
+
+    pub fn tutorial_session(&self) -> Option<&Tutorial> {
+        self.tutorial.as_ref()
+    }
+
+    /// Goes back to the previous lesson, when following the tutorial.
+    pub fn previous_lesson(&mut self) {
+        if let Some(tutorial) = self.tutorial.as_mut() {
+            tutorial.previous(&mut self.game);
+            self.selected = None;
+        }
+    }
+
+    pub fn drop_choice(&self) -> Option<PieceType> {
+        self.drop
+    }
+
+    /// The distinct pieces in the pocket of the side to move, ordered as shown in the pocket.
+    pub fn pocket(&self) -> Vec<PieceType> {
+        let board = self.game.board();
+        if board.variant() != Variant::Crazyhouse || self.is_opponent_turn() {
+            return Vec::new();
+        }
+        let pieces = self.game.player(board.turn()).taken_pieces();
+        PieceType::DROPS
+            .into_iter()
+            .filter(|variant| pieces.iter().any(|piece| piece.variant() == *variant))
+            .collect()
+    }
+
+    /// Picks the next piece from the pocket to be dropped, going back to moving pieces after the last one.
+    pub fn cycle_drop(&mut self) {
+        let pocket = self.pocket();
+        let next = match self.drop {
+            Some(variant) => pocket.iter().skip_while(|other| **other != variant).nth(1),
+            None => pocket.first(),
+        };
+        self.selected = None;
+        self.drop = next.copied();
+    }
+
+    /// Picks the piece at the given index of the pocket to be dropped.
+    pub fn pick_drop(&mut self, index: usize) {
+        if let Some(variant) = self.pocket().get(index) {
+            self.selected = None;
+            self.drop = Some(*variant);
+        }
+    }
+
+    pub fn promotion(&self) -> Option<&Promotion> {
+        self.promotion.as_ref()
+    }
+
+    pub fn previous_promotion(&mut self) {
+        if let Some(promotion) = self.promotion.as_mut() {
+            promotion.previous();
+        }
+    }
+
+    pub fn next_promotion(&mut self) {
+        if let Some(promotion) = self.promotion.as_mut() {
+            promotion.next();
+        }
+    }
+
+    /// Plays the pending promotion with the highlighted piece.
+    pub fn confirm_promotion(&mut self) {
+        if let Some(promotion) = self.promotion.take() {
+            self.commit(promotion.mv.with_promotion(promotion.choice()));
+        }
+    }
+
+    /// Highlights the promotion choice at the given index and plays it.
+    pub fn pick_promotion(&mut self, index: usize) {
+        if let Some(promotion) = self.promotion.as_mut() {
+            if index < PieceType::PROMOTIONS.len() {
+                promotion.index = index;
+                self.confirm_promotion();
+            }
+        }
+    }
+}
+
+/// The legal move written in algebraic notation, including the drops of Crazyhouse, which are written as in `N@f3`.
+fn typed_move(game: &Game, text: &str) -> Option<Move> {
+    let board = game.board();
+    // castling is easier to type in lowercase, and no other move has an o in it
+    let text = &text.replace('o', "O");
+    parse_san(board, text).or_else(|| {
+        let text = text.trim_end_matches(['+', '#']);
+        game.legal_drops()
+            .into_iter()
+            .find(|mv| san(board, mv) == text)
+    })
 }
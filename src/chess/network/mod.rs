@@ -0,0 +1,693 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use self::protocol::{Message, PROTOCOL_VERSION};
+use super::{game::piece::PieceColor, save::SavedGame};
+
+pub mod protocol;
+
+/// The port games are hosted on, unless the host picks another one.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// How long joining waits for the host to answer.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long each attempt of the guest to reconnect waits for the host, short enough not to hold up the screen.
+const RECONNECT_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// How often the guest tries to reconnect once the connection was lost.
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often a side with nothing to say lets the other side know it is still there.
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long the other side may stay silent before the connection is taken for lost.
+const SILENCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a write may stall before the other side is taken for gone and hung up on.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a lost opponent has to come back before the game is abandoned.
+pub const RECONNECT_WINDOW: Duration = Duration::from_secs(60);
+
+/// A connection to the other side, sending and receiving a message per line, each on a background thread
+/// so that a side which stops reading never holds up the screen.
+struct Connection {
+    /// The lines waiting to be written; the channel is closed once writing failed.
+    outgoing: Sender<String>,
+    /// The messages read so far; the channel is closed once the other side is gone.
+    incoming: Receiver<Message>,
+    last_heard: Instant,
+    last_sent: Instant,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(false)?;
+        stream.set_nodelay(true)?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else {
+                    return;
+                };
+                // lines which are not understood are skipped, in case the other side knows more kinds of messages
+                if let Ok(message) = Message::decode(&line) {
+                    if sender.send(message).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        let mut writer = stream;
+        let (outgoing, lines) = mpsc::channel::<String>();
+        thread::spawn(move || {
+            for line in lines {
+                if writeln!(writer, "{line}").is_err() {
+                    break;
+                }
+            }
+            // the lines are all written once the connection is dropped, or the other side stalled or left;
+            // shutting the socket down ends the reading thread as well
+            let _ = writer.shutdown(Shutdown::Both);
+        });
+        Ok(Self {
+            outgoing,
+            incoming,
+            last_heard: Instant::now(),
+            last_sent: Instant::now(),
+        })
+    }
+
+    /// Queues the message to be written, failing once the connection is lost.
+    fn send(&mut self, message: &Message) -> io::Result<()> {
+        self.last_sent = Instant::now();
+        self.outgoing
+            .send(message.encode())
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the connection was lost"))
+    }
+
+    /// The messages received since the last call, or nothing once the connection is lost.
+    fn receive(&mut self) -> Option<Vec<Message>> {
+        let mut messages = Vec::new();
+        loop {
+            match self.incoming.try_recv() {
+                Ok(message) => {
+                    self.last_heard = Instant::now();
+                    messages.push(message);
+                }
+                Err(TryRecvError::Empty) => break,
+                // the last messages before the other side hung up, such as a rejection, are still passed on,
+                // and the loss shows up on the next call
+                Err(TryRecvError::Disconnected) if !messages.is_empty() => break,
+                Err(TryRecvError::Disconnected) => return None,
+            }
+        }
+        if self.last_heard.elapsed() > SILENCE_TIMEOUT {
+            return None;
+        }
+        if self.last_sent.elapsed() > PING_INTERVAL && self.send(&Message::Ping).is_err() {
+            return None;
+        }
+        Some(messages)
+    }
+}
+
+/// Which end of the connection this side is.
+enum Role {
+    /// Waits for the guest and spectators on the listener, which stays open so that the guest can come back
+    /// after a disconnect, and more spectators can join at any time.
+    Host(TcpListener),
+    /// Joined the host at the address, which is dialed again after a disconnect.
+    Guest(SocketAddr),
+    /// Watches the game hosted at the address, which is dialed again after a disconnect.
+    Spectator(SocketAddr),
+}
+
+/// Something that happened on the network, for the game to act on.
+pub enum NetworkEvent {
+    /// The guest joined the host, for the first time or again after a disconnect.
+    Joined,
+    /// The host welcomed the guest or a spectator with the game so far, for the first time or again after a disconnect.
+    Welcomed(SavedGame),
+    /// A spectator started watching the game of the host.
+    SpectatorJoined,
+    /// A message about the game from the other side.
+    Received(Message),
+    /// The connection was lost, and the other side has the reconnect window to come back.
+    Lost,
+    /// The other side did not come back within the reconnect window.
+    Abandoned,
+    /// The host turned the guest away.
+    Rejected(String),
+}
+
+/// One side of a game played over the network, keeping the connection to the other side alive.
+pub struct NetworkSession {
+    role: Role,
+    /// The side played here, which the guest learns from the welcome of the host.
+    color: PieceColor,
+    /// The connection to the other player, or to the host for a spectator.
+    connection: Option<Connection>,
+    /// The connections the host accepted which have yet to say whether they play or watch.
+    pending: Vec<Connection>,
+    /// The connections of the spectators watching the game of the host.
+    spectators: Vec<Connection>,
+    /// The token the host welcomed the guest with, which the guest has to show to take its seat back
+    /// after a disconnect.
+    token: Option<u64>,
+    /// Whether the handshake on the current connection is done, so that messages about the game may flow.
+    is_ready: bool,
+    /// When the connection was lost, while waiting for the other side to come back.
+    lost_since: Option<Instant>,
+    /// When the guest last tried to reach the host.
+    last_attempt: Instant,
+    /// Whether the session is over for good, after being abandoned or rejected.
+    is_closed: bool,
+}
+
+impl NetworkSession {
+    /// Starts hosting a game on the port of every network interface, playing a random side.
+    pub fn host(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        let color = match fastrand::bool() {
+            true => PieceColor::White,
+            false => PieceColor::Black,
+        };
+        Ok(Self::new(Role::Host(listener), color, None))
+    }
+
+    /// Joins the game hosted at the address, adding the default port if none is given.
+    pub fn join(address: &str) -> io::Result<Self> {
+        let address = resolve(address)?;
+        let connection = dial(
+            &address,
+            CONNECT_TIMEOUT,
+            Role::Guest(address).greeting(None),
+        )?;
+        Ok(Self::new(
+            Role::Guest(address),
+            PieceColor::White,
+            Some(connection),
+        ))
+    }
+
+    /// Watches the game hosted at the address, adding the default port if none is given.
+    pub fn watch(address: &str) -> io::Result<Self> {
+        let address = resolve(address)?;
+        let connection = dial(
+            &address,
+            CONNECT_TIMEOUT,
+            Role::Spectator(address).greeting(None),
+        )?;
+        Ok(Self::new(
+            Role::Spectator(address),
+            PieceColor::White,
+            Some(connection),
+        ))
+    }
+
+    fn new(role: Role, color: PieceColor, connection: Option<Connection>) -> Self {
+        Self {
+            role,
+            color,
+            connection,
+            pending: Vec::new(),
+            spectators: Vec::new(),
+            token: None,
+            is_ready: false,
+            lost_since: None,
+            last_attempt: Instant::now(),
+            is_closed: false,
+        }
+    }
+
+    /// The side played here, which means nothing for a spectator.
+    pub fn color(&self) -> PieceColor {
+        self.color
+    }
+
+    pub fn is_host(&self) -> bool {
+        matches!(self.role, Role::Host(_))
+    }
+
+    pub fn is_spectator(&self) -> bool {
+        matches!(self.role, Role::Spectator(_))
+    }
+
+    /// The number of spectators watching the game of the host.
+    pub fn spectator_count(&self) -> usize {
+        self.spectators.len()
+    }
+
+    /// The port the host listens on, which is the one asked for unless that was 0.
+    pub fn port(&self) -> Option<u16> {
+        match &self.role {
+            Role::Host(listener) => listener.local_addr().ok().map(|address| address.port()),
+            Role::Guest(_) | Role::Spectator(_) => None,
+        }
+    }
+
+    /// The port the host listens on, or the address the guest joined.
+    pub fn address(&self) -> String {
+        match &self.role {
+            Role::Host(listener) => listener
+                .local_addr()
+                .map(|address| format!("port {}", address.port()))
+                .unwrap_or_default(),
+            Role::Guest(address) | Role::Spectator(address) => address.to_string(),
+        }
+    }
+
+    /// Whether the other side is there to play, having finished the handshake.
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some() && self.is_ready
+    }
+
+    /// How much of the reconnect window is left, while the connection is lost.
+    pub fn reconnect_time_left(&self) -> Option<Duration> {
+        self.lost_since
+            .map(|since| RECONNECT_WINDOW.saturating_sub(since.elapsed()))
+    }
+
+    /// Sends a message about the game, which is dropped if the other side is not there to hear it.
+    pub fn send(&mut self, message: Message) {
+        if !self.is_ready || self.is_spectator() {
+            return;
+        }
+        if let Some(connection) = self.connection.as_mut() {
+            // a failed write shows up as a lost connection on the next poll
+            let _ = connection.send(&message);
+        }
+    }
+
+    /// Passes a message about the game on to every spectator of the host.
+    pub fn broadcast(&mut self, message: Message) {
+        for spectator in &mut self.spectators {
+            // a failed write shows up as a lost spectator on the next poll
+            let _ = spectator.send(&message);
+        }
+    }
+
+    /// Keeps the connections going: lets the guest and spectators in or dials the host again, does the handshakes
+    /// and collects what the other side sent. The host welcomes newcomers with the game as given by `state`.
+    pub fn poll(&mut self, state: impl Fn() -> SavedGame) -> Vec<NetworkEvent> {
+        let mut events = Vec::new();
+        if self.is_closed {
+            return events;
+        }
+        self.reach_other_side();
+        if self.is_host() {
+            self.greet_newcomers(&state, &mut events);
+            // spectators have nothing to say, but are dropped once they are gone
+            self.spectators
+                .retain_mut(|spectator| spectator.receive().is_some());
+        }
+
+        let received = self.connection.as_mut().map(Connection::receive);
+        match received {
+            Some(Some(messages)) => {
+                for message in messages {
+                    self.handle(message, &mut events);
+                }
+            }
+            Some(None) => {
+                self.connection = None;
+                if self.is_ready {
+                    self.is_ready = false;
+                    self.lost_since = Some(Instant::now());
+                    events.push(NetworkEvent::Lost);
+                } else if !self.is_host() && self.lost_since.is_none() {
+                    // the host hung up before ever welcoming this side, so there is nothing to come back to
+                    self.close();
+                    events.push(NetworkEvent::Rejected(String::from(
+                        "the host closed the connection",
+                    )));
+                }
+            }
+            None => {}
+        }
+
+        if self.reconnect_time_left() == Some(Duration::ZERO) {
+            self.close();
+            events.push(NetworkEvent::Abandoned);
+        }
+        events
+    }
+
+    /// Lets anyone in as a newcomer to the host, or dials the host again once lost.
+    fn reach_other_side(&mut self) {
+        match &self.role {
+            Role::Host(listener) => {
+                while let Ok((stream, _)) = listener.accept() {
+                    if let Ok(connection) = Connection::new(stream) {
+                        self.pending.push(connection);
+                    }
+                }
+            }
+            Role::Guest(address) | Role::Spectator(address) => {
+                let is_waiting = self.connection.is_none() && self.lost_since.is_some();
+                if !is_waiting || self.last_attempt.elapsed() < RETRY_INTERVAL {
+                    return;
+                }
+                self.last_attempt = Instant::now();
+                let greeting = self.role.greeting(self.token);
+                self.connection = dial(address, RECONNECT_TIMEOUT, greeting).ok();
+            }
+        }
+    }
+
+    /// Welcomes the newcomers of the host as the guest, while it has none, or as spectators,
+    /// turning away those who cannot be either.
+    fn greet_newcomers(&mut self, state: &impl Fn() -> SavedGame, events: &mut Vec<NetworkEvent>) {
+        for mut newcomer in std::mem::take(&mut self.pending) {
+            let Some(messages) = newcomer.receive() else {
+                continue;
+            };
+            let greeting = messages
+                .into_iter()
+                .find(|message| matches!(message, Message::Hello { .. } | Message::Watch { .. }));
+            let reason = match greeting {
+                None => {
+                    self.pending.push(newcomer);
+                    continue;
+                }
+                Some(Message::Hello { version, .. } | Message::Watch { version })
+                    if version != PROTOCOL_VERSION =>
+                {
+                    format!(
+                        "the host speaks version {PROTOCOL_VERSION} of the protocol, not {version}"
+                    )
+                }
+                // once a guest has joined, its seat is kept for it alone, which also lets it take the seat back
+                // before the host noticed the old connection was lost
+                Some(Message::Hello { token, .. })
+                    if self.token.is_some() && token != self.token =>
+                {
+                    match self.connection.is_some() {
+                        true => String::from("the game already has two players"),
+                        false => String::from("the seat is kept for the player who left"),
+                    }
+                }
+                Some(Message::Hello { .. }) => {
+                    let token = *self.token.get_or_insert_with(|| fastrand::u64(..));
+                    let _ = newcomer.send(&Message::Welcome {
+                        version: PROTOCOL_VERSION,
+                        color: self.color.opposite(),
+                        token,
+                        game: state(),
+                    });
+                    self.connection = Some(newcomer);
+                    self.is_ready = true;
+                    self.lost_since = None;
+                    events.push(NetworkEvent::Joined);
+                    continue;
+                }
+                Some(_) => {
+                    let _ = newcomer.send(&Message::Spectate {
+                        version: PROTOCOL_VERSION,
+                        game: state(),
+                    });
+                    self.spectators.push(newcomer);
+                    events.push(NetworkEvent::SpectatorJoined);
+                    continue;
+                }
+            };
+            let _ = newcomer.send(&Message::Reject { reason });
+        }
+    }
+
+    /// Acts on a message on the connection, which for the host only ever comes from the guest.
+    fn handle(&mut self, message: Message, events: &mut Vec<NetworkEvent>) {
+        match message {
+            Message::Welcome {
+                version,
+                color,
+                token,
+                game,
+            } if matches!(self.role, Role::Guest(_)) => {
+                if !self.accepts(version, events) {
+                    return;
+                }
+                self.color = color;
+                self.token = Some(token);
+                events.push(NetworkEvent::Welcomed(game));
+            }
+            Message::Spectate { version, game } if self.is_spectator() => {
+                if !self.accepts(version, events) {
+                    return;
+                }
+                events.push(NetworkEvent::Welcomed(game));
+            }
+            Message::Reject { reason } if !self.is_host() => {
+                self.close();
+                events.push(NetworkEvent::Rejected(reason));
+            }
+            Message::Ping => {}
+            message if self.is_ready => events.push(NetworkEvent::Received(message)),
+            // anything else before the handshake is done is out of place, and ignored
+            _ => {}
+        }
+    }
+
+    /// Finishes the handshake with the host if it speaks the same version of the protocol,
+    /// and closes the session otherwise.
+    fn accepts(&mut self, version: u32, events: &mut Vec<NetworkEvent>) -> bool {
+        if version != PROTOCOL_VERSION {
+            self.close();
+            events.push(NetworkEvent::Rejected(format!(
+                "the host speaks version {version} of the protocol, not {PROTOCOL_VERSION}"
+            )));
+            return false;
+        }
+        self.is_ready = true;
+        self.lost_since = None;
+        true
+    }
+
+    /// Ends the session for good, hanging up on the other side.
+    fn close(&mut self) {
+        self.connection = None;
+        self.pending.clear();
+        self.spectators.clear();
+        self.is_ready = false;
+        self.lost_since = None;
+        self.is_closed = true;
+    }
+}
+
+impl Role {
+    /// The first message on a connection to the host, telling it whether this side plays or watches,
+    /// and showing the token of a guest coming back.
+    fn greeting(&self, token: Option<u64>) -> Message {
+        match self {
+            Role::Spectator(_) => Message::Watch {
+                version: PROTOCOL_VERSION,
+            },
+            _ => Message::Hello {
+                version: PROTOCOL_VERSION,
+                token,
+            },
+        }
+    }
+}
+
+/// Finds the host at the address, adding the default port if none is given.
+fn resolve(address: &str) -> io::Result<SocketAddr> {
+    let address = match address.contains(':') {
+        true => address.to_owned(),
+        false => format!("{address}:{DEFAULT_PORT}"),
+    };
+    address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the address could not be resolved"))
+}
+
+/// Connects to the host and greets it.
+fn dial(address: &SocketAddr, timeout: Duration, greeting: Message) -> io::Result<Connection> {
+    let mut connection = Connection::new(TcpStream::connect_timeout(address, timeout)?)?;
+    connection.send(&greeting)?;
+    Ok(connection)
+}
+
+/// The addresses of the network interfaces of this machine which other machines may reach it at,
+/// IPv4 first, leaving out the loopback and IPv6 link-local addresses.
+pub fn local_addresses() -> Vec<IpAddr> {
+    let mut addresses: Vec<IpAddr> = if_addrs::get_if_addrs()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|interface| interface.ip())
+        .filter(|address| match address {
+            IpAddr::V4(address) => !address.is_loopback(),
+            IpAddr::V6(address) => {
+                !address.is_loopback() && address.segments()[0] & 0xffc0 != 0xfe80
+            }
+        })
+        .collect();
+    addresses.sort_by_key(IpAddr::is_ipv6);
+    addresses.dedup();
+    addresses
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::{protocol::Message, NetworkEvent, NetworkSession, RECONNECT_WINDOW};
+    use crate::chess::{
+        game::{piece::PieceSet, Game},
+        save::SavedGame,
+        setup::GameOptions,
+    };
+
+    /// How long a test waits for the other side before failing.
+    const DEADLINE: Duration = Duration::from_secs(5);
+
+    fn state() -> SavedGame {
+        SavedGame::new(
+            GameOptions::default(),
+            &Game::new(),
+            None,
+            PieceSet::default(),
+            &[],
+        )
+    }
+
+    /// Polls both sides until the side being waited on has an event matching the predicate,
+    /// returning the events the other side had meanwhile.
+    fn wait_for(
+        waiting: &mut NetworkSession,
+        other: &mut NetworkSession,
+        predicate: impl Fn(&NetworkEvent) -> bool,
+    ) -> Vec<NetworkEvent> {
+        let start = Instant::now();
+        let mut others = Vec::new();
+        loop {
+            others.extend(other.poll(state));
+            if waiting.poll(state).iter().any(&predicate) {
+                return others;
+            }
+            assert!(start.elapsed() < DEADLINE, "the other side never answered");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Hosts a game on a free port and has a guest join it, returning the host and the guest.
+    fn connect() -> (NetworkSession, NetworkSession) {
+        let mut host = NetworkSession::host(0).unwrap();
+        let port = host.port().unwrap();
+        let mut guest = NetworkSession::join(&format!("127.0.0.1:{port}")).unwrap();
+        wait_for(&mut host, &mut guest, |event| {
+            matches!(event, NetworkEvent::Joined)
+        });
+        wait_for(&mut guest, &mut host, |event| {
+            matches!(event, NetworkEvent::Welcomed(_))
+        });
+        (host, guest)
+    }
+
+    /// Sends the message from one side and waits for the other side to receive it.
+    fn exchange(from: &mut NetworkSession, to: &mut NetworkSession, message: Message) {
+        let expected = message.encode();
+        from.send(message);
+        wait_for(to, from, |event| match event {
+            NetworkEvent::Received(message) => message.encode() == expected,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn joins_a_game_hosted_on_a_free_port() {
+        let (host, guest) = connect();
+        assert_ne!(host.port(), Some(0));
+        assert!(host.is_connected() && guest.is_connected());
+        assert_eq!(guest.color(), host.color().opposite());
+    }
+
+    #[test]
+    fn exchanges_moves_draws_resignations_and_flags() {
+        let (mut host, mut guest) = connect();
+        let move_with_times = Message::Move {
+            uci: String::from("e2e4"),
+            times: Some((Duration::from_secs(180), Duration::from_millis(181_250))),
+        };
+        exchange(&mut host, &mut guest, move_with_times);
+        let plain_move = Message::Move {
+            uci: String::from("e7e5"),
+            times: None,
+        };
+        exchange(&mut guest, &mut host, plain_move);
+        exchange(&mut host, &mut guest, Message::OfferDraw);
+        exchange(&mut guest, &mut host, Message::DeclineDraw);
+        exchange(&mut guest, &mut host, Message::OfferDraw);
+        exchange(&mut host, &mut guest, Message::AcceptDraw);
+        exchange(&mut guest, &mut host, Message::Resign);
+        exchange(&mut host, &mut guest, Message::Flag);
+    }
+
+    #[test]
+    fn rejects_another_protocol_version() {
+        let mut host = NetworkSession::host(0).unwrap();
+        let mut stream = TcpStream::connect(("127.0.0.1", host.port().unwrap())).unwrap();
+        writeln!(stream, "hello 1").unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+
+        let start = Instant::now();
+        let mut line = String::new();
+        while !line.ends_with('\n') {
+            assert!(start.elapsed() < DEADLINE, "the host never answered");
+            host.poll(state);
+            let _ = reader.read_line(&mut line);
+        }
+        assert!(line.starts_with("reject "), "{line}");
+        assert!(line.contains("version"), "{line}");
+        assert!(!host.is_connected());
+    }
+
+    #[test]
+    fn reconnects_within_the_window_and_keeps_the_seat() {
+        let (mut host, mut guest) = connect();
+        // hanging up on the guest is what a dropped connection looks like from its side
+        host.connection = None;
+        wait_for(&mut guest, &mut host, |event| {
+            matches!(event, NetworkEvent::Lost)
+        });
+        let time_left = guest.reconnect_time_left().unwrap();
+        assert!(time_left > Duration::ZERO && time_left <= RECONNECT_WINDOW);
+
+        // the vacated seat is not given to someone without the session token
+        let port = host.port().unwrap();
+        let mut stranger = NetworkSession::join(&format!("127.0.0.1:{port}")).unwrap();
+        wait_for(
+            &mut stranger,
+            &mut host,
+            |event| matches!(event, NetworkEvent::Rejected(reason) if reason.contains("seat")),
+        );
+
+        let events = wait_for(&mut guest, &mut host, |event| {
+            matches!(event, NetworkEvent::Welcomed(_))
+        });
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, NetworkEvent::Joined)));
+        assert!(host.is_connected() && guest.is_connected());
+        assert_eq!(guest.reconnect_time_left(), None);
+        exchange(&mut guest, &mut host, Message::Resign);
+    }
+}
@@ -0,0 +1,341 @@
+use std::time::Duration;
+
+use crate::chess::{
+    game::{piece::PieceColor, GameStatus},
+    save::SavedGame,
+};
+
+/// The version of the protocol, which both sides have to speak to play each other.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// A message between the two sides of a network game, sent as a single line of text starting with its kind.
+///
+/// The guest opens with `hello`, and the host answers with `welcome` carrying a session token and the whole
+/// game so far. A guest picks the game up again after reconnecting by saying `hello` with the token, and the
+/// seat of a guest who left is given to no one else:
+///
+/// ```text
+/// hello 2
+/// welcome 2 black 5f0e2a9c41d7b386 position standard;variant Standard;opponent human;clock 180 2 180000 180000;moves e2e4
+/// move e7e5 180000 181250
+/// hello 2 5f0e2a9c41d7b386
+/// ```
+///
+/// A spectator opens with `watch` instead, and is answered with `spectate` carrying the game so far.
+/// From then on the host passes on every move, the clock whenever it stops or starts again,
+/// and how the game ended when that does not follow from the moves.
+pub enum Message {
+    /// The guest asking to play, with the token of its session when coming back after a disconnect.
+    Hello {
+        version: u32,
+        token: Option<u64>,
+    },
+    /// The game as the host has it, the side the guest plays and the token the guest comes back with.
+    Welcome {
+        version: u32,
+        color: PieceColor,
+        token: u64,
+        game: SavedGame,
+    },
+    /// A spectator asking to watch the game.
+    Watch {
+        version: u32,
+    },
+    /// The game as the host has it, for a spectator to watch.
+    Spectate {
+        version: u32,
+        game: SavedGame,
+    },
+    /// The host turned the guest away, such as for speaking another version of the protocol.
+    Reject {
+        reason: String,
+    },
+    /// A move in UCI notation, with the time both players had left once it was played.
+    Move {
+        uci: String,
+        times: Option<(Duration, Duration)>,
+    },
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    Resign,
+    /// The sender ran out of time.
+    Flag,
+    /// How the game ended, for spectators; only resignations, agreed draws, time forfeits and
+    /// abandoned games are sent, as the other endings follow from the moves.
+    Ended {
+        status: GameStatus,
+    },
+    /// The time both players have left, and whether the clock is running, for spectators.
+    Clock {
+        white: Duration,
+        black: Duration,
+        is_running: bool,
+    },
+    /// Keeps the connection alive while neither side has anything to say.
+    Ping,
+}
+
+impl Message {
+    /// Writes the message as a line of text, without the line break.
+    pub fn encode(&self) -> String {
+        match self {
+            Message::Hello { version, token } => match token {
+                Some(token) => format!("hello {version} {token:016x}"),
+                None => format!("hello {version}"),
+            },
+            Message::Welcome {
+                version,
+                color,
+                token,
+                game,
+            } => {
+                let game = single_line(game);
+                format!(
+                    "welcome {version} {} {token:016x} {game}",
+                    color_name(*color)
+                )
+            }
+            Message::Watch { version } => format!("watch {version}"),
+            Message::Spectate { version, game } => {
+                format!("spectate {version} {}", single_line(game))
+            }
+            Message::Reject { reason } => format!("reject {reason}"),
+            Message::Move { uci, times } => match times {
+                Some((white, black)) => {
+                    format!("move {uci} {} {}", white.as_millis(), black.as_millis())
+                }
+                None => format!("move {uci}"),
+            },
+            Message::OfferDraw => String::from("draw offer"),
+            Message::AcceptDraw => String::from("draw accept"),
+            Message::DeclineDraw => String::from("draw decline"),
+            Message::Resign => String::from("resign"),
+            Message::Ended { status } => match status {
+                GameStatus::Resignation { winner } => {
+                    format!("end resignation {}", color_name(*winner))
+                }
+                GameStatus::OutOfTime { winner } => format!("end time {}", color_name(*winner)),
+                GameStatus::DrawAgreed => String::from("end agreement"),
+                GameStatus::Abandoned => String::from("end abandoned"),
+                // the other endings are not sent, and would not be understood on the other side
+                _ => String::from("end"),
+            },
+            Message::Clock {
+                white,
+                black,
+                is_running,
+            } => {
+                let state = match is_running {
+                    true => "running",
+                    false => "stopped",
+                };
+                format!("clock {} {} {state}", white.as_millis(), black.as_millis())
+            }
+            Message::Flag => String::from("flag"),
+            Message::Ping => String::from("ping"),
+        }
+    }
+
+    /// Reads a message from a line of text.
+    pub fn decode(line: &str) -> Result<Self, String> {
+        let line = line.trim_end();
+        let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        let version = |text: &str| {
+            text.parse::<u32>()
+                .map_err(|_| format!("'{text}' is not a protocol version"))
+        };
+        let token = |text: &str| {
+            u64::from_str_radix(text, 16).map_err(|_| format!("'{text}' is not a session token"))
+        };
+        let millis = |text: &str| {
+            text.parse::<u64>()
+                .map(Duration::from_millis)
+                .map_err(|_| format!("'{text}' is not a time"))
+        };
+
+        match (kind, fields.as_slice()) {
+            ("hello", [number]) => Ok(Message::Hello {
+                version: version(number)?,
+                token: None,
+            }),
+            ("hello", [number, session]) => Ok(Message::Hello {
+                version: version(number)?,
+                token: Some(token(session)?),
+            }),
+            ("welcome", [number, color, session, ..]) => {
+                let game = rest
+                    .splitn(4, ' ')
+                    .nth(3)
+                    .unwrap_or_default()
+                    .replace(';', "\n");
+                Ok(Message::Welcome {
+                    version: version(number)?,
+                    color: parse_color(color)?,
+                    token: token(session)?,
+                    game: SavedGame::parse(&game)?,
+                })
+            }
+            ("watch", [number]) => Ok(Message::Watch {
+                version: version(number)?,
+            }),
+            ("spectate", [number, ..]) => {
+                let game = rest
+                    .split_once(' ')
+                    .unwrap_or_default()
+                    .1
+                    .replace(';', "\n");
+                Ok(Message::Spectate {
+                    version: version(number)?,
+                    game: SavedGame::parse(&game)?,
+                })
+            }
+            ("reject", _) => Ok(Message::Reject {
+                reason: rest.to_owned(),
+            }),
+            ("move", [uci]) => Ok(Message::Move {
+                uci: uci.to_string(),
+                times: None,
+            }),
+            ("move", [uci, white, black]) => Ok(Message::Move {
+                uci: uci.to_string(),
+                times: Some((millis(white)?, millis(black)?)),
+            }),
+            ("draw", ["offer"]) => Ok(Message::OfferDraw),
+            ("draw", ["accept"]) => Ok(Message::AcceptDraw),
+            ("draw", ["decline"]) => Ok(Message::DeclineDraw),
+            ("resign", []) => Ok(Message::Resign),
+            ("end", ["resignation", color]) => Ok(Message::Ended {
+                status: GameStatus::Resignation {
+                    winner: parse_color(color)?,
+                },
+            }),
+            ("end", ["time", color]) => Ok(Message::Ended {
+                status: GameStatus::OutOfTime {
+                    winner: parse_color(color)?,
+                },
+            }),
+            ("end", ["agreement"]) => Ok(Message::Ended {
+                status: GameStatus::DrawAgreed,
+            }),
+            ("end", ["abandoned"]) => Ok(Message::Ended {
+                status: GameStatus::Abandoned,
+            }),
+            ("clock", [white, black, state @ ("running" | "stopped")]) => Ok(Message::Clock {
+                white: millis(white)?,
+                black: millis(black)?,
+                is_running: *state == "running",
+            }),
+            ("flag", []) => Ok(Message::Flag),
+            ("ping", []) => Ok(Message::Ping),
+            _ => Err(format!("'{line}' is not a message")),
+        }
+    }
+}
+
+/// Writes the game on a single line, which works as none of its lines hold a semicolon.
+fn single_line(game: &SavedGame) -> String {
+    game.to_string().trim_end().replace('\n', ";")
+}
+
+fn color_name(color: PieceColor) -> &'static str {
+    match color {
+        PieceColor::White => "white",
+        PieceColor::Black => "black",
+    }
+}
+
+fn parse_color(text: &str) -> Result<PieceColor, String> {
+    match text {
+        "white" => Ok(PieceColor::White),
+        "black" => Ok(PieceColor::Black),
+        other => Err(format!("'{other}' is not a side")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads the line as a message and writes it out again, which should give back the same line.
+    fn round_trip(line: &str) {
+        let message = Message::decode(line).unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!(message.encode(), line);
+    }
+
+    #[test]
+    fn reads_back_the_messages_it_writes() {
+        for line in [
+            "hello 2",
+            "hello 2 5f0e2a9c41d7b386",
+            "hello 2 00000000000000ff",
+            "watch 2",
+            "reject the host speaks version 2 of the protocol",
+            "move e2e4",
+            "move e7e8q 180000 181250",
+            "move N@f3 0 5",
+            "draw offer",
+            "draw accept",
+            "draw decline",
+            "resign",
+            "flag",
+            "end resignation white",
+            "end time black",
+            "end agreement",
+            "end abandoned",
+            "clock 59000 61500 running",
+            "clock 0 1000 stopped",
+            "ping",
+        ] {
+            round_trip(line);
+        }
+    }
+
+    #[test]
+    fn carries_the_game_on_one_line() {
+        let line = "welcome 2 black 5f0e2a9c41d7b386 position chess960 518;variant Standard;\
+            opponent human;player white Alice;clock 180 2 171500 169250;pieces Ascii;hints white;\
+            moves e2e4 e7e5 g1f3";
+        round_trip(line);
+        let Ok(Message::Welcome {
+            version,
+            color,
+            token,
+            game,
+        }) = Message::decode(line)
+        else {
+            panic!("'{line}' is not a welcome");
+        };
+        assert_eq!(
+            (version, color, token),
+            (2, PieceColor::Black, 0x5f0e2a9c41d7b386)
+        );
+        assert_eq!(game.players.get(PieceColor::White), Some("Alice"));
+        assert_eq!(game.game().history().len(), 3);
+
+        round_trip("spectate 2 position standard;variant Crazyhouse;opponent human;pieces UnicodeFilled;hints ;moves d2d4");
+    }
+
+    #[test]
+    fn refuses_what_is_not_a_message() {
+        for line in [
+            "",
+            "hello",
+            "hello two",
+            "hello 2 not-a-token",
+            "welcome 2 red 00000000000000ff position standard",
+            "move e2e4 180000",
+            "move e2e4 soon later",
+            "draw",
+            "end checkmate white",
+            "clock 1000 1000 paused",
+            "ping pong",
+        ] {
+            assert!(
+                Message::decode(line).is_err(),
+                "'{line}' was read as a message"
+            );
+        }
+    }
+}
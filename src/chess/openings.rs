@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use super::game::{board::Board, parse_san, Game};
+
+/// The table of openings bundled with the game, in the tab separated format of the Lichess opening names:
+/// the ECO code, the name and the moves leading to the opening.
+///
+/// The table is partial: it holds 141 of the best known openings and their main variations, covering
+/// 108 of the 500 ECO codes. Positions reached by none of them are left without a name, and games are archived
+/// without an ECO tag. Replacing the file with the full Lichess table (`a.tsv` to `e.tsv` joined, with one
+/// header line) names every opening, as the format is the same.
+const OPENINGS: &str = include_str!("openings.tsv");
+
+pub struct Opening {
+    /// The code of the opening in the Encyclopaedia of Chess Openings, e.g. `C50`.
+    pub eco: String,
+    pub name: String,
+}
+
+/// The openings of the bundled table, looked up by the position they lead to so that transpositions are recognized.
+pub struct Openings {
+    by_position: HashMap<u64, Opening>,
+}
+
+impl Openings {
+    /// Plays through the moves of every opening in the bundled table.
+    pub fn load() -> Self {
+        let mut by_position = HashMap::new();
+        // the first line holds the names of the columns
+        for line in OPENINGS.lines().skip(1) {
+            let mut columns = line.split('\t');
+            let (Some(eco), Some(name), Some(pgn)) =
+                (columns.next(), columns.next(), columns.next())
+            else {
+                continue;
+            };
+
+            let mut board = Board::new();
+            for san in pgn.split_whitespace().filter(|token| !token.ends_with('.')) {
+                let mv = parse_san(&board, san).expect("bundled openings should be legal");
+                board.apply(&mv);
+            }
+            // an earlier entry reaching the same position is kept, as the table lists the main lines first
+            by_position
+                .entry(board.polyglot_key())
+                .or_insert_with(|| Opening {
+                    eco: eco.to_owned(),
+                    name: name.to_owned(),
+                });
+        }
+        Self { by_position }
+    }
+
+    /// The number of positions the table names.
+    pub fn len(&self) -> usize {
+        self.by_position.len()
+    }
+
+    /// The opening of the game, being the most recent position of the game found in the table.
+    pub fn find(&self, game: &Game) -> Option<&Opening> {
+        std::iter::once(game.board())
+            .chain(game.history().iter().rev().map(|ply| &ply.board))
+            .find_map(|board| self.by_position.get(&board.polyglot_key()))
+    }
+}
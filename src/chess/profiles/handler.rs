@@ -0,0 +1,38 @@
+use crate::app::AppResult;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::prelude::Backend;
+
+use super::Profiles;
+
+pub fn handle_key_events<B: Backend>(
+    key_event: KeyEvent,
+    profiles: &mut Profiles<'_, B>,
+) -> AppResult<()> {
+    if key_event.code == KeyCode::Char('c') && key_event.modifiers == KeyModifiers::CONTROL {
+        profiles.quit();
+        return Ok(());
+    }
+    if profiles.input().is_some() {
+        match key_event.code {
+            KeyCode::Esc => profiles.cancel_input(),
+            KeyCode::Enter => profiles.confirm_input(),
+            KeyCode::Backspace => profiles.erase_symbol(),
+            KeyCode::Char(symbol) => profiles.type_symbol(symbol),
+            _ => {}
+        }
+        return Ok(());
+    }
+    if profiles.is_deleting() && key_event.code != KeyCode::Char('d') {
+        profiles.keep();
+        return Ok(());
+    }
+    match key_event.code {
+        KeyCode::Esc | KeyCode::Char('q') => profiles.quit(),
+        KeyCode::Up => profiles.move_up(),
+        KeyCode::Down => profiles.move_down(),
+        KeyCode::Char('n') => profiles.start_creating(),
+        KeyCode::Char('d') => profiles.delete(),
+        _ => {}
+    }
+    Ok(())
+}
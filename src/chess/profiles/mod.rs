@@ -0,0 +1,533 @@
+use std::{fs, io};
+
+use crate::{app::AppResult, event::Event, tui::Tui};
+use tui::prelude::Backend;
+
+use self::handler::handle_key_events;
+use super::{
+    clock::TimeControl,
+    computer_limits,
+    game::{pgn::today, piece::PieceColor},
+    storage,
+};
+
+mod handler;
+mod ui;
+
+/// The name of the file the profiles and their rated games are kept in, in the data directory.
+const PROFILES_FILE: &str = "profiles.tsv";
+
+/// The rating of a profile that has not played a rated game yet.
+const INITIAL_RATING: i32 = 1500;
+
+/// How much a single game can move a rating.
+const RATING_FACTOR: f64 = 32.0;
+
+/// The longest name a profile can have, so that it fits on the setup screen along with its rating.
+const MAX_NAME_LENGTH: usize = 12;
+
+/// The name the computer is recorded under as the opponent of a rated game, which no profile may take.
+const COMPUTER: &str = "Computer";
+
+/// How a rated game went for one of its players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 3] = [Outcome::Win, Outcome::Draw, Outcome::Loss];
+
+    /// The outcome for the side, given the winner of the game, or none for a draw.
+    fn of(color: PieceColor, winner: Option<PieceColor>) -> Self {
+        match winner {
+            Some(winner) if winner == color => Outcome::Win,
+            Some(_) => Outcome::Loss,
+            None => Outcome::Draw,
+        }
+    }
+
+    /// The points the outcome is worth, as counted by the Elo formula.
+    fn score(self) -> f64 {
+        match self {
+            Outcome::Win => 1.0,
+            Outcome::Draw => 0.5,
+            Outcome::Loss => 0.0,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Outcome::Win => "win",
+            Outcome::Draw => "draw",
+            Outcome::Loss => "loss",
+        }
+    }
+}
+
+/// A rated game as it went for one of its players.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RatedGame {
+    /// The day the game was played, in the `YYYY.MM.DD` form of PGN.
+    pub date: String,
+    pub opponent: String,
+    /// The rating of the opponent going into the game.
+    pub opponent_rating: i32,
+    pub outcome: Outcome,
+    /// The rating of the player after the game.
+    pub rating: i32,
+    /// How much the game changed the rating of the player.
+    pub change: i32,
+}
+
+/// A named player kept across sessions, with the rated games they have played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    /// The rated games of the player, the oldest first.
+    pub history: Vec<RatedGame>,
+}
+
+impl Profile {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            history: Vec::new(),
+        }
+    }
+
+    /// The rating after the last rated game, or the initial rating before the first one.
+    pub fn rating(&self) -> i32 {
+        self.history
+            .last()
+            .map(|game| game.rating)
+            .unwrap_or(INITIAL_RATING)
+    }
+
+    /// How much the last rated game changed the rating.
+    pub fn change(&self) -> Option<i32> {
+        self.history.last().map(|game| game.change)
+    }
+
+    /// The number of rated games that went the given way.
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.history
+            .iter()
+            .filter(|game| game.outcome == outcome)
+            .count()
+    }
+}
+
+/// The profiles playing each side of a game, by name, if any.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Players {
+    pub white: Option<String>,
+    pub black: Option<String>,
+}
+
+impl Players {
+    pub fn get(&self, color: PieceColor) -> Option<&str> {
+        match color {
+            PieceColor::White => self.white.as_deref(),
+            PieceColor::Black => self.black.as_deref(),
+        }
+    }
+
+    pub fn set(&mut self, color: PieceColor, name: Option<String>) {
+        match color {
+            PieceColor::White => self.white = name,
+            PieceColor::Black => self.black = name,
+        }
+    }
+
+    /// Whether a game between the players is rated, as it is when every side not played by the computer
+    /// is played by a profile, and the two sides are not played by the same one.
+    pub fn is_rated(&self, computer: Option<PieceColor>) -> bool {
+        match (computer, &self.white, &self.black) {
+            (Some(color), _, _) => self.get(color.opposite()).is_some(),
+            (None, Some(white), Some(black)) => white != black,
+            (None, _, _) => false,
+        }
+    }
+}
+
+/// The change to a rating after a game against an opponent of the given rating, scoring 1 for a win,
+/// 0.5 for a draw and 0 for a loss, by the Elo formula.
+pub fn rating_change(rating: i32, opponent_rating: i32, score: f64) -> i32 {
+    let expected = 1.0 / (1.0 + 10f64.powf((opponent_rating - rating) as f64 / 400.0));
+    (RATING_FACTOR * (score - expected)).round() as i32
+}
+
+/// Reads every profile from the data directory, in the order they were created.
+///
+/// The profiles file has a line for each profile, then a line for each rated game of one,
+/// with the fields separated by tabs:
+///
+/// ```text
+/// profile Alice
+/// game    Alice   2024.05.17  Computer    1600    win 1521    21
+/// ```
+pub fn load() -> Vec<Profile> {
+    let Some(text) =
+        storage::data_dir().and_then(|dir| fs::read_to_string(dir.join(PROFILES_FILE)).ok())
+    else {
+        return Vec::new();
+    };
+    let mut profiles: Vec<Profile> = Vec::new();
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        match fields.as_slice() {
+            ["profile", name] => profiles.push(Profile::new(name)),
+            ["game", name, date, opponent, opponent_rating, outcome, rating, change] => {
+                let (Some(profile), Ok(opponent_rating), Some(outcome), Ok(rating), Ok(change)) = (
+                    profiles.iter_mut().find(|profile| profile.name == *name),
+                    opponent_rating.parse(),
+                    Outcome::ALL
+                        .into_iter()
+                        .find(|other| other.name() == *outcome),
+                    rating.parse(),
+                    change.parse(),
+                ) else {
+                    continue;
+                };
+                profile.history.push(RatedGame {
+                    date: date.to_string(),
+                    opponent: opponent.to_string(),
+                    opponent_rating,
+                    outcome,
+                    rating,
+                    change,
+                });
+            }
+            // lines that are not understood, as from a file edited by hand, are left out
+            _ => {}
+        }
+    }
+    profiles
+}
+
+fn store(profiles: &[Profile]) -> io::Result<()> {
+    let dir = storage::data_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    fs::create_dir_all(&dir)?;
+    let mut text = String::new();
+    for profile in profiles {
+        text.push_str(&format!("profile\t{}\n", profile.name));
+    }
+    for profile in profiles {
+        for game in &profile.history {
+            text.push_str(&format!(
+                "game\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                profile.name,
+                game.date,
+                game.opponent,
+                game.opponent_rating,
+                game.outcome.name(),
+                game.rating,
+                game.change
+            ));
+        }
+    }
+    fs::write(dir.join(PROFILES_FILE), text)
+}
+
+/// Adds a profile with the name, which must be new and short enough to show.
+pub fn create(name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(String::from("the profile needs a name"));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("names are at most {MAX_NAME_LENGTH} letters long"));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(String::from(
+            "names can only be made of letters and symbols",
+        ));
+    }
+    if name.eq_ignore_ascii_case(COMPUTER) {
+        return Err(format!("the name {COMPUTER} is kept for the computer"));
+    }
+    let mut profiles = load();
+    if profiles
+        .iter()
+        .any(|profile| profile.name.eq_ignore_ascii_case(name))
+    {
+        return Err(format!("there already is a profile named {name}"));
+    }
+    profiles.push(Profile::new(name));
+    store(&profiles).map_err(|error| error.to_string())
+}
+
+/// Removes the profile with the name, along with its rated games.
+pub fn delete(name: &str) -> io::Result<()> {
+    let mut profiles = load();
+    profiles.retain(|profile| profile.name != name);
+    store(&profiles)
+}
+
+/// The rating the computer is taken to play at in a game with the time control, as estimated from the limits
+/// it searches within at the start of the game.
+pub fn computer_rating(time_control: Option<TimeControl>) -> i32 {
+    let clock = time_control.map(|time_control| (time_control, time_control.initial));
+    computer_limits(clock).estimated_rating()
+}
+
+/// Rates a finished game, changing the rating of every side played by a profile, going by the ratings
+/// both sides had going into the game. The computer keeps its rating whatever the result.
+pub fn rate(
+    players: &Players,
+    computer: Option<PieceColor>,
+    time_control: Option<TimeControl>,
+    winner: Option<PieceColor>,
+) -> io::Result<()> {
+    let mut profiles = load();
+    let rating_of = |profiles: &[Profile], color: PieceColor| {
+        if computer == Some(color) {
+            return Some((COMPUTER.to_owned(), computer_rating(time_control)));
+        }
+        let name = players.get(color)?;
+        let profile = profiles.iter().find(|profile| profile.name == name)?;
+        Some((profile.name.clone(), profile.rating()))
+    };
+    let white = rating_of(&profiles, PieceColor::White);
+    let black = rating_of(&profiles, PieceColor::Black);
+    let (Some(white), Some(black)) = (white, black) else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "a profile of the game is missing",
+        ));
+    };
+    let date = today();
+    for (color, (_, rating), (opponent, opponent_rating)) in [
+        (PieceColor::White, &white, &black),
+        (PieceColor::Black, &black, &white),
+    ] {
+        let Some(profile) = players
+            .get(color)
+            .filter(|_| computer != Some(color))
+            .and_then(|name| profiles.iter_mut().find(|profile| profile.name == name))
+        else {
+            continue;
+        };
+        let outcome = Outcome::of(color, winner);
+        let change = rating_change(*rating, *opponent_rating, outcome.score());
+        profile.history.push(RatedGame {
+            date: date.clone(),
+            opponent: opponent.clone(),
+            opponent_rating: *opponent_rating,
+            outcome,
+            rating: rating + change,
+            change,
+        });
+    }
+    store(&profiles)
+}
+
+/// The screen listing the profiles with their ratings, where they are created and deleted,
+/// and the rating history of the highlighted one is shown.
+pub struct Profiles<'a, B: Backend> {
+    running: bool,
+    tui: Option<&'a mut Tui<B>>,
+    profiles: Vec<Profile>,
+    /// The index of the highlighted profile.
+    selected: usize,
+    /// The name being typed for a new profile.
+    input: Option<String>,
+    /// Signifies whether the player was asked to confirm deleting the highlighted profile.
+    is_deleting: bool,
+    /// A message for the player about the last thing they did.
+    notice: Option<String>,
+}
+
+impl<'a, B: Backend> Profiles<'a, B> {
+    pub fn new(tui: &'a mut Tui<B>) -> Self {
+        Self {
+            running: true,
+            tui: Some(tui),
+            profiles: load(),
+            selected: 0,
+            input: None,
+            is_deleting: false,
+            notice: None,
+        }
+    }
+
+    pub fn launch_profiles(&mut self) -> AppResult<()> {
+        while self.running {
+            let tui = self.tui.take().expect("should always contain a tui");
+            tui.terminal.draw(|frame| ui::render(self, frame))?;
+            match tui.events.next()? {
+                Event::Tick => {}
+                Event::Key(key_event) => handle_key_events(key_event, self)?,
+                Event::Mouse(_) => {}
+                Event::Resize(_, _) => {}
+            }
+            self.tui = Some(tui);
+        }
+        Ok(())
+    }
+
+    pub fn quit(&mut self) {
+        self.running = false;
+    }
+
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_profile(&self) -> Option<&Profile> {
+        self.profiles.get(self.selected)
+    }
+
+    pub fn input(&self) -> Option<&str> {
+        self.input.as_deref()
+    }
+
+    pub fn is_deleting(&self) -> bool {
+        self.is_deleting
+    }
+
+    pub fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    /// Forgets about the last notice and a pending deletion, as the player went on to something else.
+    fn settle(&mut self) {
+        self.notice = None;
+        self.is_deleting = false;
+    }
+
+    /// Highlights the profile directly above the highlighted one - without wrapping around.
+    pub fn move_up(&mut self) {
+        self.settle();
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Highlights the profile directly below the highlighted one - without wrapping around.
+    pub fn move_down(&mut self) {
+        self.settle();
+        if self.selected + 1 < self.profiles.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Asks for the name of a new profile.
+    pub fn start_creating(&mut self) {
+        self.settle();
+        self.input = Some(String::new());
+    }
+
+    pub fn type_symbol(&mut self, symbol: char) {
+        if let Some(name) = self.input.as_mut() {
+            if name.chars().count() < MAX_NAME_LENGTH && !symbol.is_control() {
+                name.push(symbol);
+            }
+        }
+    }
+
+    pub fn erase_symbol(&mut self) {
+        if let Some(name) = self.input.as_mut() {
+            name.pop();
+        }
+    }
+
+    pub fn cancel_input(&mut self) {
+        self.input = None;
+    }
+
+    /// Creates the profile with the typed name, and highlights it.
+    pub fn confirm_input(&mut self) {
+        let Some(name) = self.input.take() else {
+            return;
+        };
+        match create(&name) {
+            Ok(()) => {
+                self.profiles = load();
+                self.selected = self.profiles.len().saturating_sub(1);
+                self.notice = Some(format!("Profile {} created", name.trim()));
+            }
+            Err(error) => {
+                self.notice = Some(format!("Could not create the profile: {error}"));
+                self.input = Some(name);
+            }
+        }
+    }
+
+    /// Keeps the highlighted profile after all.
+    pub fn keep(&mut self) {
+        self.is_deleting = false;
+    }
+
+    /// Asks the player to confirm deleting the highlighted profile, and deletes it along with its rating history
+    /// once they have.
+    pub fn delete(&mut self) {
+        let Some(profile) = self.profiles.get(self.selected) else {
+            return;
+        };
+        if !self.is_deleting {
+            self.notice = None;
+            self.is_deleting = true;
+            return;
+        }
+        self.is_deleting = false;
+        self.notice = Some(match delete(&profile.name) {
+            Ok(()) => format!("Profile {} deleted", profile.name),
+            Err(error) => format!("Could not delete the profile: {error}"),
+        });
+        self.profiles = load();
+        self.selected = self.selected.min(self.profiles.len().saturating_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn changes_the_rating_by_the_elo_formula() {
+        assert_eq!(rating_change(1500, 1500, 1.0), 16);
+        assert_eq!(rating_change(1500, 1500, 0.5), 0);
+        assert_eq!(rating_change(1500, 1500, 0.0), -16);
+        // a player rated 400 points higher is expected to score ten games out of eleven
+        assert_eq!(rating_change(1900, 1500, 1.0), 3);
+        assert_eq!(rating_change(1900, 1500, 0.5), -13);
+        assert_eq!(rating_change(1900, 1500, 0.0), -29);
+        assert_eq!(rating_change(1500, 1900, 1.0), 29);
+        assert_eq!(rating_change(1500, 1900, 0.0), -3);
+    }
+
+    #[test]
+    fn rates_games_between_two_profiles_or_against_the_computer() {
+        let players = |white: Option<&str>, black: Option<&str>| Players {
+            white: white.map(String::from),
+            black: black.map(String::from),
+        };
+        assert!(players(Some("Alice"), Some("Bob")).is_rated(None));
+        assert!(!players(Some("Alice"), Some("Alice")).is_rated(None));
+        assert!(!players(Some("Alice"), None).is_rated(None));
+        assert!(players(Some("Alice"), None).is_rated(Some(PieceColor::Black)));
+        assert!(!players(Some("Alice"), None).is_rated(Some(PieceColor::White)));
+        assert!(!players(None, None).is_rated(Some(PieceColor::Black)));
+    }
+
+    #[test]
+    fn rates_the_computer_higher_with_more_time() {
+        let rating = |minutes: u64| {
+            computer_rating(Some(TimeControl {
+                initial: Duration::from_secs(minutes * 60),
+                increment: Duration::ZERO,
+            }))
+        };
+        assert!(rating(1) <= rating(5));
+        assert!(rating(5) <= rating(30));
+    }
+}
@@ -0,0 +1,215 @@
+use super::{Outcome, Profile, Profiles, INITIAL_RATING};
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    prelude::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Paragraph, Row, Sparkline, Table, TableState},
+    Frame,
+};
+
+/// The widths of the columns of the list of profiles.
+const PROFILE_COLUMN_WIDTHS: [Constraint; 4] = [
+    Constraint::Length(12),
+    Constraint::Length(6),
+    Constraint::Length(5),
+    Constraint::Length(11),
+];
+
+/// The width taken up by the list of profiles, the rating history taking up whatever is left.
+const PROFILES_WIDTH: u16 = 42;
+
+/// The widths of the columns of the rated games, the opponent fitting the longest name along with its rating.
+const GAME_COLUMN_WIDTHS: [Constraint; 5] = [
+    Constraint::Length(10),
+    Constraint::Length(19),
+    Constraint::Length(6),
+    Constraint::Length(6),
+    Constraint::Length(6),
+];
+
+/// The height of the chart of the rating over time, including its border.
+const CHART_HEIGHT: u16 = 8;
+
+/// Renders the user interface widgets.
+pub fn render<B: Backend>(profiles: &mut Profiles<B>, frame: &mut Frame<'_, B>) {
+    outer_block(frame);
+    status(profiles, frame);
+    let area = Rect::new(
+        2,
+        3,
+        frame.size().width.saturating_sub(4),
+        frame.size().height.saturating_sub(6),
+    );
+    if profiles.profiles().is_empty() {
+        let empty = Paragraph::new("Create a profile with n to have your games rated")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(empty, area);
+    } else {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(PROFILES_WIDTH), Constraint::Min(0)])
+            .split(area);
+        profile_list(profiles, frame, columns[0]);
+        if let Some(profile) = profiles.selected_profile() {
+            history(profile, frame, columns[1]);
+        }
+    }
+    key_hints(profiles, frame);
+}
+
+#[inline(always)]
+fn outer_block<B: Backend>(frame: &mut Frame<'_, B>) {
+    let outer_block = Block::default()
+        .title("Chess Profiles")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    frame.render_widget(outer_block, frame.size());
+}
+
+/// The name being typed, or the last notice.
+#[inline(always)]
+fn status<B: Backend>(profiles: &mut Profiles<B>, frame: &mut Frame<'_, B>) {
+    let text = match (profiles.input(), profiles.notice()) {
+        (Some(name), Some(notice)) => format!("{notice} · Name: {name}_"),
+        (Some(name), None) => format!("Name of the new profile: {name}_"),
+        (None, _) if profiles.is_deleting() => String::from(
+            "Press d again to delete the profile and its rating history, or any other key to keep it",
+        ),
+        (None, Some(notice)) => notice.to_owned(),
+        (None, None) => String::from(
+            "Pick profiles for the players when setting up a game to have it rated",
+        ),
+    };
+    let style = match profiles.is_deleting() {
+        true => Style::default().fg(Color::Red),
+        false => Style::default().fg(Color::White),
+    };
+    let area = Rect::new(2, 1, frame.size().width.saturating_sub(4), 1);
+    frame.render_widget(Paragraph::new(text).style(style), area);
+}
+
+#[inline(always)]
+fn profile_list<B: Backend>(profiles: &mut Profiles<B>, frame: &mut Frame<'_, B>, area: Rect) {
+    let header = Row::new(["Name", "Rating", "Games", "W/D/L"]).style(
+        Style::default()
+            .add_modifier(Modifier::BOLD)
+            .fg(Color::White),
+    );
+    let rows: Vec<Row> = profiles
+        .profiles()
+        .iter()
+        .map(|profile| {
+            let record: Vec<String> = Outcome::ALL
+                .into_iter()
+                .map(|outcome| profile.count(outcome).to_string())
+                .collect();
+            Row::new([
+                profile.name.clone(),
+                profile.rating().to_string(),
+                profile.history.len().to_string(),
+                record.join("/"),
+            ])
+        })
+        .collect();
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&PROFILE_COLUMN_WIDTHS)
+        .column_spacing(2)
+        .style(Style::default().fg(Color::DarkGray))
+        .highlight_style(Style::default().fg(Color::Cyan));
+
+    let mut state = TableState::default();
+    state.select(Some(profiles.selected()));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+/// The rating of the profile over its rated games, and the latest of those games.
+#[inline(always)]
+fn history<B: Backend>(profile: &Profile, frame: &mut Frame<'_, B>, area: Rect) {
+    if profile.history.is_empty() {
+        let empty = Paragraph::new(format!("{} has not played a rated game yet", profile.name))
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(CHART_HEIGHT), Constraint::Min(0)])
+        .split(area);
+
+    // the chart shows as many of the latest ratings as it has columns, from just below the lowest of them,
+    // starting out from the initial rating
+    let ratings: Vec<i32> = std::iter::once(INITIAL_RATING)
+        .chain(profile.history.iter().map(|game| game.rating))
+        .collect();
+    let shown = &ratings[ratings
+        .len()
+        .saturating_sub(rows[0].width.saturating_sub(2) as usize)..];
+    let lowest = shown.iter().min().copied().unwrap_or_default();
+    let highest = shown.iter().max().copied().unwrap_or_default();
+    let data: Vec<u64> = shown
+        .iter()
+        .map(|rating| (rating - lowest) as u64 + 1)
+        .collect();
+    let chart = Sparkline::default()
+        .block(
+            Block::default()
+                .title(format!("Rating {lowest}–{highest}"))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .data(&data)
+        .style(Style::default().fg(Color::Cyan));
+    frame.render_widget(chart, rows[0]);
+
+    let header = Row::new(["Date", "Opponent", "Result", "Rating", "Change"]).style(
+        Style::default()
+            .add_modifier(Modifier::BOLD)
+            .fg(Color::White),
+    );
+    let games: Vec<Row> = profile
+        .history
+        .iter()
+        .rev()
+        .map(|game| {
+            Row::new([
+                game.date.clone(),
+                format!("{} ({})", game.opponent, game.opponent_rating),
+                game.outcome.name().to_owned(),
+                game.rating.to_string(),
+                format!("{:+}", game.change),
+            ])
+        })
+        .collect();
+    let table = Table::new(games)
+        .header(header)
+        .widths(&GAME_COLUMN_WIDTHS)
+        .column_spacing(1)
+        .style(Style::default().fg(Color::DarkGray));
+    // a row is left between the chart and the games
+    let area = Rect {
+        y: rows[1].y + 1,
+        height: rows[1].height.saturating_sub(1),
+        ..rows[1]
+    };
+    frame.render_widget(table, area);
+}
+
+#[inline(always)]
+fn key_hints<B: Backend>(profiles: &mut Profiles<B>, frame: &mut Frame<'_, B>) {
+    let text = match profiles.input() {
+        Some(_) => "type the name · Enter create · Esc cancel",
+        None => "n new profile · d delete · Esc back",
+    };
+    let y_axis = frame.size().height.saturating_sub(2);
+    let area = Rect::new(1, y_axis, frame.size().width.saturating_sub(2), 1);
+    let hints = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(hints, area);
+}
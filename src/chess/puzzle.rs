@@ -0,0 +1,360 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use super::{
+    game::{board::Board, chess_move::Move, piece::PieceColor, Game},
+    profiles::rating_change,
+    storage,
+};
+
+/// The name of the puzzle file in the data directory, in the format of the Lichess puzzle database.
+const PUZZLE_FILE: &str = "puzzles.csv";
+
+/// The name of the file the puzzle rating is kept in, in the data directory.
+const RATING_FILE: &str = "puzzle_rating";
+
+/// The rating of a player who has not solved any puzzles yet.
+const INITIAL_RATING: i32 = 1500;
+
+/// How far from the rating of the player puzzles are picked, when there are any that close.
+const RATING_WINDOW: i32 = 150;
+
+/// How many puzzles are read from random places in the puzzle file to pick one from.
+const SAMPLES: usize = 200;
+
+/// A tactics puzzle, as found in the Lichess puzzle database.
+pub struct Puzzle {
+    pub id: String,
+    board: Board,
+    /// The moves of the solution in UCI notation, starting with the opposing move leading into the puzzle.
+    moves: Vec<String>,
+    pub rating: i32,
+    pub themes: Vec<String>,
+}
+
+impl Puzzle {
+    /// Parses a line of the Lichess puzzle database, of which only the first four columns and the themes are used:
+    /// `PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags`.
+    pub fn from_csv(line: &str) -> Result<Self, String> {
+        let columns: Vec<&str> = line.split(',').collect();
+        let [id, fen, moves, rating, ..] = columns.as_slice() else {
+            return Err(format!("'{line}' has too few columns for a puzzle"));
+        };
+        let moves: Vec<String> = moves.split_whitespace().map(String::from).collect();
+        if moves.len() < 2 {
+            return Err(format!("puzzle {id} has no moves to solve"));
+        }
+
+        Ok(Self {
+            id: id.to_string(),
+            board: Board::from_fen(fen)?,
+            moves,
+            rating: rating
+                .parse()
+                .map_err(|_| format!("'{rating}' is not a puzzle rating"))?,
+            themes: columns
+                .get(7)
+                .map(|themes| themes.split_whitespace().map(String::from).collect())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// The path of the puzzle file, if the platform has a data directory.
+pub fn puzzle_file() -> Option<PathBuf> {
+    storage::data_dir().map(|dir| dir.join(PUZZLE_FILE))
+}
+
+/// Picks a random puzzle close to the given rating, leaving out the puzzles already seen.
+///
+/// The puzzle database holds millions of puzzles, far too many to read through for every puzzle, so a few hundred
+/// are read from random places in the file instead. A random one of them within the rating window is picked,
+/// or the closest one if there are none.
+fn pick_puzzle(path: &Path, rating: i32, seen: &HashSet<String>) -> io::Result<Option<Puzzle>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let length = reader.get_ref().metadata()?.len();
+    let mut closest: Option<Puzzle> = None;
+    let mut picked: Option<Puzzle> = None;
+    let mut candidates = 0;
+    let mut line = String::new();
+
+    for _ in 0..SAMPLES {
+        if length == 0 {
+            break;
+        }
+        // the line the random place falls in is read from partway through, so the next one is taken,
+        // going round to the first line past the last
+        reader.seek(SeekFrom::Start(fastrand::u64(0..length)))?;
+        reader.read_line(&mut line)?;
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            reader.rewind()?;
+            reader.read_line(&mut line)?;
+        }
+        // the header and any malformed lines are skipped
+        let puzzle = Puzzle::from_csv(line.trim_end());
+        line.clear();
+        let Ok(puzzle) = puzzle else {
+            continue;
+        };
+        if seen.contains(&puzzle.id) {
+            continue;
+        }
+
+        let distance = (puzzle.rating - rating).abs();
+        if distance <= RATING_WINDOW {
+            candidates += 1;
+            if fastrand::usize(0..candidates) == 0 {
+                picked = Some(puzzle);
+            }
+        } else if picked.is_none()
+            && closest
+                .as_ref()
+                .map(|closest| distance < (closest.rating - rating).abs())
+                .unwrap_or(true)
+        {
+            closest = Some(puzzle);
+        }
+    }
+    Ok(picked.or(closest))
+}
+
+/// The puzzle rating of the player, kept across sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PuzzleRating {
+    pub rating: i32,
+    /// How much the rating changed with the last rated puzzle.
+    pub change: i32,
+}
+
+impl PuzzleRating {
+    /// Loads the rating of the player, starting out at the initial rating if none was saved.
+    pub fn load() -> Self {
+        let rating = storage::data_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(RATING_FILE)).ok())
+            .and_then(|text| text.trim().parse().ok())
+            .unwrap_or(INITIAL_RATING);
+        Self { rating, change: 0 }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let dir = storage::data_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(RATING_FILE), self.rating.to_string())
+    }
+
+    /// Rates the outcome of a puzzle like a game against the puzzle, using the Elo formula.
+    fn update(&mut self, puzzle_rating: i32, solved: bool) {
+        let score = if solved { 1.0 } else { 0.0 };
+        self.change = rating_change(self.rating, puzzle_rating, score);
+        self.rating += self.change;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PuzzleState {
+    /// The player is looking for the next move of the solution.
+    Solving,
+    /// The last move of the player was correct, and the opponent has replied.
+    Correct,
+    /// The last move of the player was wrong and taken back, so the player may try again.
+    Wrong,
+    Solved,
+    /// There is no puzzle to solve, as the puzzle file is missing or empty.
+    Unavailable,
+}
+
+/// A series of puzzles picked around the rating of the player.
+pub struct PuzzleSession {
+    puzzle: Option<Puzzle>,
+    /// The index into the moves of the solution of the next move to play.
+    progress: usize,
+    state: PuzzleState,
+    /// Whether the player has made a wrong move in the current puzzle, and so already lost rating for it.
+    failed: bool,
+    rating: PuzzleRating,
+    /// The puzzles played in this session, which are not picked again.
+    seen: HashSet<String>,
+}
+
+impl PuzzleSession {
+    /// Starts a session with the first puzzle, setting up the game in its position.
+    pub fn new(game: &mut Game) -> Self {
+        let mut session = Self {
+            puzzle: None,
+            progress: 0,
+            state: PuzzleState::Unavailable,
+            failed: false,
+            rating: PuzzleRating::load(),
+            seen: HashSet::new(),
+        };
+        session.next(game);
+        session
+    }
+
+    pub fn puzzle(&self) -> Option<&Puzzle> {
+        self.puzzle.as_ref()
+    }
+
+    pub fn state(&self) -> PuzzleState {
+        self.state
+    }
+
+    pub fn rating(&self) -> PuzzleRating {
+        self.rating
+    }
+
+    /// The side the player solves the puzzle for.
+    pub fn color(&self) -> Option<PieceColor> {
+        self.puzzle
+            .as_ref()
+            .map(|puzzle| puzzle.board.turn().opposite())
+    }
+
+    /// Moves on to a new puzzle, giving up the current one if it was not finished.
+    ///
+    /// A puzzle whose solution does not fit its position was never the player's to solve, so it costs nothing.
+    pub fn next(&mut self, game: &mut Game) {
+        if self.puzzle.is_some()
+            && !self.failed
+            && self.state != PuzzleState::Solved
+            && self.state != PuzzleState::Unavailable
+        {
+            self.finish(false);
+        }
+
+        let puzzle = puzzle_file().and_then(|path| {
+            pick_puzzle(&path, self.rating.rating, &self.seen)
+                .ok()
+                .flatten()
+        });
+        let Some(puzzle) = puzzle else {
+            self.puzzle = None;
+            self.state = PuzzleState::Unavailable;
+            return;
+        };
+
+        *game = Game::with_board(puzzle.board);
+        self.seen.insert(puzzle.id.clone());
+        self.puzzle = Some(puzzle);
+        self.progress = 0;
+        self.failed = false;
+        self.state = PuzzleState::Solving;
+        // the first move is the opponent's, leading into the position to solve
+        self.play_reply(game);
+    }
+
+    /// Plays the move of the player if it follows the solution, and the reply of the opponent after it.
+    ///
+    /// Any move delivering checkmate is accepted as the last move, as puzzles ending in mate may have several.
+    pub fn play(&mut self, game: &mut Game, mv: Move) {
+        let Some(puzzle) = self.puzzle.as_ref() else {
+            return;
+        };
+        if self.state == PuzzleState::Solved {
+            return;
+        }
+
+        let is_last = self.progress + 1 == puzzle.moves.len();
+        let mut after = *game.board();
+        after.apply(&mv);
+        let is_mate = after.in_check() && after.legal_moves().is_empty();
+        if mv.uci() != puzzle.moves[self.progress] && !(is_last && is_mate) {
+            self.state = PuzzleState::Wrong;
+            if !self.failed {
+                self.failed = true;
+                self.finish(false);
+            }
+            return;
+        }
+
+        game.make_move(mv);
+        self.progress += 1;
+        if self.progress >= puzzle.moves.len() {
+            self.state = PuzzleState::Solved;
+            if !self.failed {
+                self.finish(true);
+            }
+            return;
+        }
+        self.state = PuzzleState::Correct;
+        self.play_reply(game);
+    }
+
+    fn play_reply(&mut self, game: &mut Game) {
+        let Some(puzzle) = self.puzzle.as_ref() else {
+            return;
+        };
+        let reply = game
+            .board()
+            .legal_moves()
+            .into_iter()
+            .find(|mv| mv.uci() == puzzle.moves[self.progress]);
+        match reply {
+            Some(reply) => {
+                game.make_move(reply);
+                self.progress += 1;
+            }
+            // a solution which does not fit the position cannot be solved, so the puzzle is skipped
+            None => self.state = PuzzleState::Unavailable,
+        }
+    }
+
+    /// Rates the current puzzle, saving the new rating right away so that it is kept even if the game is closed.
+    fn finish(&mut self, solved: bool) {
+        let Some(puzzle) = self.puzzle.as_ref() else {
+            return;
+        };
+        self.rating.update(puzzle.rating, solved);
+        // failing to save leaves the rating as it was on the next session, which is not worth interrupting the puzzles for
+        let _ = self.rating.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str =
+        "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags";
+
+    fn row(id: &str, rating: i32) -> String {
+        format!("{id},4k3/8/8/8/8/8/4P3/4K3 w - - 0 1,e2e4 e8e7,{rating},75,90,100,endgame short,,")
+    }
+
+    #[test]
+    fn parses_a_row_of_the_lichess_database() {
+        let puzzle = Puzzle::from_csv(&row("00008", 1913)).unwrap();
+        assert_eq!(puzzle.id, "00008");
+        assert_eq!(puzzle.rating, 1913);
+        assert_eq!(puzzle.themes, ["endgame", "short"]);
+        assert!(Puzzle::from_csv(HEADER).is_err());
+    }
+
+    #[test]
+    fn picks_an_unseen_puzzle_near_the_rating() {
+        let path =
+            std::env::temp_dir().join(format!("terminal-games-puzzles-{}.csv", std::process::id()));
+        let rows = [
+            row("far", 2600),
+            row("seen", 1500),
+            row("near", 1550),
+            row("low", 900),
+        ];
+        fs::write(&path, format!("{HEADER}\n{}\n", rows.join("\n"))).unwrap();
+        let seen = HashSet::from([String::from("seen")]);
+        for _ in 0..20 {
+            let puzzle = pick_puzzle(&path, 1500, &seen).unwrap().unwrap();
+            assert_eq!(puzzle.id, "near");
+        }
+        // with no puzzle within the window, the closest one is picked
+        let puzzle = pick_puzzle(&path, 2400, &seen).unwrap().unwrap();
+        assert_eq!(puzzle.id, "far");
+        fs::remove_file(&path).unwrap();
+    }
+}
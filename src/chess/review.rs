@@ -0,0 +1,256 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    engine::{
+        history_keys,
+        search::{search, Score, SearchLimits},
+    },
+    game::{board::Board, chess_move::Move, piece::PieceColor, san_line, Game},
+    storage,
+};
+
+/// How long the engine looks at each position of the game.
+const REVIEW_LIMITS: SearchLimits = SearchLimits {
+    depth: 8,
+    time: Some(Duration::from_millis(200)),
+    nodes: None,
+};
+
+/// The name of the directory in the data directory reviewed games are exported to.
+const EXPORT_DIRECTORY: &str = "reviews";
+
+/// How bad a move was, by how much it lowered the chances of the player winning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Annotation {
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Annotation {
+    /// Classifies a move by the winning chances it gave away, in percentage points.
+    fn from_loss(loss: f64) -> Option<Self> {
+        match loss {
+            loss if loss >= 30.0 => Some(Annotation::Blunder),
+            loss if loss >= 20.0 => Some(Annotation::Mistake),
+            loss if loss >= 10.0 => Some(Annotation::Inaccuracy),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Annotation::Inaccuracy => "?!",
+            Annotation::Mistake => "?",
+            Annotation::Blunder => "??",
+        }
+    }
+}
+
+/// What the engine found for a position of the game.
+#[derive(Debug, Clone)]
+struct Evaluation {
+    score: Score,
+    /// The move the engine would have played, if the game was not over.
+    best_move: Option<Move>,
+}
+
+/// A review of a finished game, evaluating every position on a background thread.
+pub struct Review {
+    /// The moves of the reviewed game.
+    moves: Vec<Move>,
+    /// The boards the moves were played from.
+    boards: Vec<Board>,
+    /// The evaluations of the positions before every move and after the last, filled in as the review goes on.
+    evaluations: Arc<Mutex<Vec<Evaluation>>>,
+    stop: Arc<AtomicBool>,
+    /// Whether the engine plays the variant of the game, without which nothing is evaluated.
+    is_available: bool,
+}
+
+impl Review {
+    /// Starts reviewing the game, unless the engine does not play its variant.
+    pub fn start(game: &Game) -> Self {
+        let moves: Vec<Move> = game.history().iter().map(|ply| ply.mv).collect();
+        let boards: Vec<Board> = game.history().iter().map(|ply| ply.board).collect();
+        let mut positions = boards.clone();
+        positions.push(*game.board());
+        let keys = history_keys(game);
+        // the final position has already been decided, even when the board alone cannot tell a draw
+        let is_draw = game.result() == "1/2-1/2";
+
+        let evaluations = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let is_available = game.board().variant().has_engine_support();
+        let (thread_evaluations, thread_stop) = (Arc::clone(&evaluations), Arc::clone(&stop));
+        // there is nothing to evaluate in the variants the engine does not play
+        if is_available {
+            thread::spawn(move || {
+                for (index, board) in positions.iter().enumerate() {
+                    let is_last = index == positions.len() - 1;
+                    let mut latest = None;
+                    let info = search(board, &keys[..index], REVIEW_LIMITS, &thread_stop, |info| {
+                        latest = Some(info.score)
+                    });
+                    if thread_stop.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let evaluation = Evaluation {
+                        score: match (is_last && is_draw, latest) {
+                            (false, Some(score)) => score,
+                            _ => Score::Centipawns(0),
+                        },
+                        best_move: info.and_then(|info| info.pv.first().copied()),
+                    };
+                    match thread_evaluations.lock() {
+                        Ok(mut evaluations) => evaluations.push(evaluation),
+                        Err(_) => return,
+                    }
+                }
+            });
+        }
+
+        Self {
+            moves,
+            boards,
+            evaluations,
+            stop,
+            is_available,
+        }
+    }
+
+    /// Whether the moves are evaluated at all, which they are not in the variants the engine does not play.
+    pub fn is_available(&self) -> bool {
+        self.is_available
+    }
+
+    fn evaluations(&self) -> Vec<Evaluation> {
+        self.evaluations
+            .lock()
+            .map(|evaluations| evaluations.clone())
+            .unwrap_or_default()
+    }
+
+    /// The number of positions evaluated so far, and the number there are to evaluate.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.evaluations().len(), self.moves.len() + 1)
+    }
+
+    pub fn is_done(&self) -> bool {
+        let (evaluated, total) = self.progress();
+        evaluated == total
+    }
+
+    /// The winning chances each reviewed move gave away, in percentage points, as far as the review has got.
+    fn losses(&self) -> Vec<f64> {
+        let evaluations = self.evaluations();
+        evaluations
+            .windows(2)
+            .zip(&self.boards)
+            .map(|(pair, board)| {
+                let chances = |score: Score| match board.turn() {
+                    PieceColor::White => score.white_share() * 100.0,
+                    PieceColor::Black => (1.0 - score.white_share()) * 100.0,
+                };
+                (chances(pair[0].score) - chances(pair[1].score)).max(0.0)
+            })
+            .collect()
+    }
+
+    /// The annotation of the move at the given index of the game, if it was reviewed and found lacking.
+    ///
+    /// Nothing is returned for a different move at that index, as played when exploring another line.
+    pub fn annotation(&self, index: usize, mv: &Move) -> Option<Annotation> {
+        if self.moves.get(index) != Some(mv) {
+            return None;
+        }
+        self.losses()
+            .get(index)
+            .and_then(|loss| Annotation::from_loss(*loss))
+    }
+
+    /// The indices of the moves which were annotated, in the order they were played.
+    pub fn critical_moments(&self) -> Vec<usize> {
+        self.losses()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, loss)| Annotation::from_loss(*loss).is_some())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// The accuracy of the player over their reviewed moves as a percentage, using the formula of Lichess.
+    pub fn accuracy(&self, color: PieceColor) -> Option<f64> {
+        let accuracies: Vec<f64> = self
+            .losses()
+            .into_iter()
+            .zip(&self.boards)
+            .filter(|(_, board)| board.turn() == color)
+            .map(|(loss, _)| (103.1668 * (-0.04354 * loss).exp() - 3.1669).clamp(0.0, 100.0))
+            .collect();
+        (!accuracies.is_empty()).then(|| accuracies.iter().sum::<f64>() / accuracies.len() as f64)
+    }
+
+    /// Writes the game to the data directory in PGN, annotated with the review and the better moves found.
+    pub fn export(&self, game: &Game, tags: &[(&str, String)]) -> io::Result<PathBuf> {
+        let evaluations = self.evaluations();
+        let notes: Vec<String> = self
+            .moves
+            .iter()
+            .enumerate()
+            .map(|(index, mv)| {
+                let Some(annotation) = self.annotation(index, mv) else {
+                    return String::new();
+                };
+                let best = evaluations[index]
+                    .best_move
+                    .map(|best| san_line(&self.boards[index], &[best]))
+                    .map(|best| format!(" {{ {best} was best }}"))
+                    .unwrap_or_default();
+                format!("{}{best}", annotation.symbol())
+            })
+            .collect();
+
+        let dir = storage::data_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?
+            .join(EXPORT_DIRECTORY);
+        fs::create_dir_all(&dir)?;
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let pgn = game.to_pgn(tags, &notes);
+        // a review exported within the same second as another one is numbered rather than overwriting it
+        let mut number = 1;
+        loop {
+            let path = match number {
+                1 => dir.join(format!("review-{seconds}.pgn")),
+                number => dir.join(format!("review-{seconds}-{number}.pgn")),
+            };
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(pgn.as_bytes())?;
+                    return Ok(path);
+                }
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => number += 1,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+impl Drop for Review {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
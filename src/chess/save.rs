@@ -0,0 +1,382 @@
+use std::{fs, io, path::PathBuf, time::Duration};
+
+use super::{
+    clock::{Clock, TimeControl},
+    game::{
+        board::Board,
+        piece::{PieceColor, PieceSet},
+        variant::Variant,
+        Game,
+    },
+    profiles::Players,
+    setup::{Blindfold, GameOptions, Opponent, StartingPosition},
+    storage,
+};
+
+/// The name of the file the unfinished game is kept in, in the data directory.
+const SAVE_FILE: &str = "saved_game";
+
+/// An unfinished game as written to the save file, one `key value` line per field:
+///
+/// ```text
+/// position chess960 518
+/// variant Crazyhouse
+/// opponent computer black
+/// player white Alice
+/// clock 180 2 171500 169250
+/// blindfold hidden
+/// pieces Ascii
+/// hints white white
+/// moves e2e4 e7e5 g1f3
+/// ```
+pub struct SavedGame {
+    pub options: GameOptions,
+    /// The profiles playing the game, by name.
+    pub players: Players,
+    /// The moves played from the starting position, in UCI notation.
+    moves: Vec<String>,
+    /// The time white and black had left.
+    times: Option<(Duration, Duration)>,
+    pub piece_set: PieceSet,
+    /// The side each hint was given to, in the order they were asked for.
+    pub hints_used: Vec<PieceColor>,
+}
+
+impl SavedGame {
+    /// Captures the game along with everything needed to pick it up again.
+    pub fn new(
+        options: GameOptions,
+        game: &Game,
+        clock: Option<&Clock>,
+        piece_set: PieceSet,
+        hints_used: &[PieceColor],
+    ) -> Self {
+        Self {
+            options,
+            players: Players::default(),
+            moves: game.history().iter().map(|ply| ply.mv.uci()).collect(),
+            times: clock.map(|clock| {
+                (
+                    clock.remaining(PieceColor::White),
+                    clock.remaining(PieceColor::Black),
+                )
+            }),
+            piece_set,
+            hints_used: hints_used.to_vec(),
+        }
+    }
+
+    /// Reads the saved game from the data directory, if there is one.
+    pub fn load() -> Option<Self> {
+        let text = fs::read_to_string(save_file()?).ok()?;
+        Self::parse(&text).ok()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = save_file()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_string())
+    }
+
+    /// Removes the saved game, as it has been finished.
+    pub fn delete() -> io::Result<()> {
+        match save_file() {
+            Some(path) if path.exists() => fs::remove_file(path),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether there is a saved game to resume.
+    pub fn exists() -> bool {
+        save_file().map(|path| path.exists()).unwrap_or(false)
+    }
+
+    /// Replays the saved moves, stopping at the first one that is not legal, as from a file edited by hand.
+    pub fn game(&self) -> Game {
+        let mut game = Game::with_board(self.options.board());
+        for uci in &self.moves {
+            let mv = game
+                .board()
+                .legal_moves()
+                .into_iter()
+                .chain(game.legal_drops())
+                .find(|mv| mv.uci() == *uci);
+            match mv {
+                Some(mv) => game.make_move(mv),
+                None => break,
+            }
+        }
+        game
+    }
+
+    /// The clock as it stood, running for the side to move once the game is under way.
+    pub fn clock(&self, game: &Game) -> Option<Clock> {
+        let time_control = self.options.time_control?;
+        let (white, black) = self
+            .times
+            .unwrap_or((time_control.initial, time_control.initial));
+        Some(Clock::restore(
+            time_control,
+            white,
+            black,
+            game.board().turn(),
+            !game.history().is_empty(),
+        ))
+    }
+
+    /// Reads a game written as by [`SavedGame::to_string`], as in the save file.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut saved = Self {
+            options: GameOptions::default(),
+            players: Players::default(),
+            moves: Vec::new(),
+            times: None,
+            piece_set: PieceSet::default(),
+            hints_used: Vec::new(),
+        };
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            let Some(key) = fields.next() else {
+                continue;
+            };
+            let values: Vec<&str> = fields.collect();
+            match (key, values.as_slice()) {
+                ("position", ["standard"]) => saved.options.start = StartingPosition::Standard,
+                ("position", ["chess960", index]) => {
+                    let index = index
+                        .parse()
+                        .map_err(|_| format!("'{index}' is not a Chess960 position"))?;
+                    saved.options.start = StartingPosition::Chess960(Some(index));
+                }
+                ("position", ["fen", fen @ ..]) => {
+                    let board = Board::from_fen(&fen.join(" "))?;
+                    saved.options.start = StartingPosition::Custom(board);
+                }
+                ("variant", name) => {
+                    let name = name.join(" ");
+                    saved.options.variant = Variant::ALL
+                        .into_iter()
+                        .find(|variant| variant.name() == name)
+                        .ok_or_else(|| format!("'{name}' is not a variant"))?;
+                }
+                ("opponent", ["human"]) => saved.options.opponent = Opponent::Human,
+                ("opponent", ["computer", "white"]) => {
+                    saved.options.opponent = Opponent::Computer(PieceColor::White)
+                }
+                ("opponent", ["computer", "black"]) => {
+                    saved.options.opponent = Opponent::Computer(PieceColor::Black)
+                }
+                ("player", [side, name @ ..]) if !name.is_empty() => {
+                    let color = match *side {
+                        "white" => PieceColor::White,
+                        "black" => PieceColor::Black,
+                        other => return Err(format!("'{other}' is not a side")),
+                    };
+                    saved.players.set(color, Some(name.join(" ")));
+                }
+                ("clock", [initial, increment, white, black]) => {
+                    let number = |text: &str| {
+                        text.parse::<u64>()
+                            .map_err(|_| format!("'{text}' is not a number"))
+                    };
+                    saved.options.time_control = Some(TimeControl {
+                        initial: Duration::from_secs(number(initial)?),
+                        increment: Duration::from_secs(number(increment)?),
+                    });
+                    saved.times = Some((
+                        Duration::from_millis(number(white)?),
+                        Duration::from_millis(number(black)?),
+                    ));
+                }
+                ("blindfold", ["hidden"]) => saved.options.blindfold = Blindfold::HiddenPieces,
+                ("blindfold", ["blank"]) => saved.options.blindfold = Blindfold::BlankBoard,
+                ("pieces", [name]) => {
+                    saved.piece_set = PieceSet::ALL
+                        .into_iter()
+                        .find(|piece_set| format!("{piece_set:?}") == *name)
+                        .unwrap_or_default();
+                }
+                ("hints", sides) => {
+                    saved.hints_used = sides
+                        .iter()
+                        .map(|side| match *side {
+                            "white" => Ok(PieceColor::White),
+                            "black" => Ok(PieceColor::Black),
+                            other => Err(format!("'{other}' is not a side")),
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                ("moves", moves) => saved.moves = moves.iter().map(|mv| mv.to_string()).collect(),
+                _ => return Err(format!("'{line}' is not part of a saved game")),
+            }
+        }
+        Ok(saved)
+    }
+}
+
+impl std::fmt::Display for SavedGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.options.start {
+            StartingPosition::Chess960(Some(index)) => writeln!(f, "position chess960 {index}")?,
+            StartingPosition::Custom(board) => writeln!(f, "position fen {}", board.to_fen())?,
+            _ => writeln!(f, "position standard")?,
+        }
+        writeln!(f, "variant {}", self.options.variant.name())?;
+        match self.options.opponent {
+            Opponent::Human => writeln!(f, "opponent human")?,
+            Opponent::Computer(PieceColor::White) => writeln!(f, "opponent computer white")?,
+            Opponent::Computer(PieceColor::Black) => writeln!(f, "opponent computer black")?,
+        }
+        for (side, color) in [("white", PieceColor::White), ("black", PieceColor::Black)] {
+            if let Some(name) = self.players.get(color) {
+                writeln!(f, "player {side} {name}")?;
+            }
+        }
+        if let (Some(time_control), Some((white, black))) = (self.options.time_control, self.times)
+        {
+            writeln!(
+                f,
+                "clock {} {} {} {}",
+                time_control.initial.as_secs(),
+                time_control.increment.as_secs(),
+                white.as_millis(),
+                black.as_millis()
+            )?;
+        }
+        // the line is left out when the board is shown, as it is for the games sent over the network
+        match self.options.blindfold {
+            Blindfold::Off => {}
+            Blindfold::HiddenPieces => writeln!(f, "blindfold hidden")?,
+            Blindfold::BlankBoard => writeln!(f, "blindfold blank")?,
+        }
+        writeln!(f, "pieces {:?}", self.piece_set)?;
+        let hints: Vec<&str> = self
+            .hints_used
+            .iter()
+            .map(|side| match side {
+                PieceColor::White => "white",
+                PieceColor::Black => "black",
+            })
+            .collect();
+        writeln!(f, "hints {}", hints.join(" "))?;
+        writeln!(f, "moves {}", self.moves.join(" "))
+    }
+}
+
+fn save_file() -> Option<PathBuf> {
+    storage::data_dir().map(|dir| dir.join(SAVE_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::game::parse_san;
+
+    #[test]
+    fn reads_back_the_games_it_writes() {
+        let options = GameOptions {
+            start: StartingPosition::Chess960(Some(518)),
+            variant: Variant::Crazyhouse,
+            opponent: Opponent::Computer(PieceColor::Black),
+            time_control: Some(TimeControl {
+                initial: Duration::from_secs(180),
+                increment: Duration::from_secs(2),
+            }),
+            blindfold: Blindfold::HiddenPieces,
+        };
+        let mut game = Game::with_board(options.board());
+        for san in ["e4", "e5", "Nf3"] {
+            game.make_move(parse_san(game.board(), san).unwrap());
+        }
+        let clock = Clock::restore(
+            options.time_control.unwrap(),
+            Duration::from_millis(171_500),
+            Duration::from_millis(169_250),
+            PieceColor::Black,
+            false,
+        );
+        let mut saved = SavedGame::new(
+            options,
+            &game,
+            Some(&clock),
+            PieceSet::Ascii,
+            &[PieceColor::White, PieceColor::White],
+        );
+        saved
+            .players
+            .set(PieceColor::White, Some(String::from("Alice")));
+
+        let text = saved.to_string();
+        assert_eq!(
+            text,
+            "position chess960 518\n\
+            variant Crazyhouse\n\
+            opponent computer black\n\
+            player white Alice\n\
+            clock 180 2 171500 169250\n\
+            blindfold hidden\n\
+            pieces Ascii\n\
+            hints white white\n\
+            moves e2e4 e7e5 g1f3\n"
+        );
+
+        let read = SavedGame::parse(&text).unwrap();
+        assert_eq!(read.to_string(), text);
+        assert_eq!(read.options, options);
+        assert_eq!(read.players.get(PieceColor::White), Some("Alice"));
+        assert_eq!(read.players.get(PieceColor::Black), None);
+        assert_eq!(read.piece_set, PieceSet::Ascii);
+        assert_eq!(read.hints_used, [PieceColor::White, PieceColor::White]);
+
+        let replayed = read.game();
+        assert_eq!(replayed.board().to_fen(), game.board().to_fen());
+        let clock = read.clock(&replayed).unwrap();
+        assert_eq!(
+            clock.remaining(PieceColor::White),
+            Duration::from_millis(171_500)
+        );
+        assert!(clock.is_running());
+    }
+
+    #[test]
+    fn replays_a_set_up_position_up_to_the_first_illegal_move() {
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1";
+        let text = format!(
+            "position fen {fen}\nvariant Standard\nopponent human\nmoves e2e4 e8d7 e4e6 e1d2\n"
+        );
+        let saved = SavedGame::parse(&text).unwrap();
+        assert_eq!(
+            saved.options.start,
+            StartingPosition::Custom(Board::from_fen(fen).unwrap())
+        );
+        assert_eq!(saved.options.time_control, None);
+        assert!(saved.clock(&saved.game()).is_none());
+        // the pawn cannot jump to e6, so the game stops before it
+        assert_eq!(
+            saved.game().board().to_fen(),
+            "8/3k4/8/8/4P3/8/8/4K3 w - - 1 2"
+        );
+    }
+
+    #[test]
+    fn refuses_what_is_not_a_saved_game() {
+        for text in [
+            "position chess960 many",
+            "position fen not a position",
+            "variant Bughouse",
+            "opponent computer red",
+            "player green Alice",
+            "clock 180 2 soon",
+            "hints white grey",
+            "score 1-0",
+        ] {
+            assert!(
+                SavedGame::parse(text).is_err(),
+                "'{text}' was read as a saved game"
+            );
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate::app::AppResult;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::prelude::Backend;
+
+use super::Setup;
+
+pub fn handle_key_events<B: Backend>(
+    key_event: KeyEvent,
+    setup: &mut Setup<'_, B>,
+) -> AppResult<()> {
+    match key_event.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            setup.quit();
+        }
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            setup.quit();
+        }
+        KeyCode::Up => setup.move_up(),
+        KeyCode::Down => setup.move_down(),
+        KeyCode::Left => setup.previous_value(),
+        KeyCode::Right => setup.next_value(),
+        KeyCode::Char(digit @ '0'..='9') => setup.type_digit(digit as u16 - '0' as u16),
+        KeyCode::Backspace => setup.erase_digit(),
+        KeyCode::Char('r') => setup.randomize(),
+        KeyCode::Enter => setup.confirm(),
+        _ => {}
+    }
+    Ok(())
+}
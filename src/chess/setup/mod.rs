@@ -0,0 +1,459 @@
+use crate::{app::AppResult, event::Event, tui::Tui};
+use tui::prelude::Backend;
+
+use self::handler::handle_key_events;
+use super::{
+    clock::TimeControl,
+    game::{
+        board::{Board, CHESS960_POSITIONS},
+        piece::PieceColor,
+        variant::Variant,
+        Game,
+    },
+    profiles::{self, Players, Profile},
+};
+
+mod handler;
+mod ui;
+
+/// The number of options presented on the setup screen, the last two of which pick the profiles of the players.
+const OPTION_COUNT: u8 = 8;
+
+/// The number of options picking the profiles of the players.
+const PLAYER_OPTION_COUNT: u8 = 2;
+
+/// The screen shown before a chess game, where the player picks how the game is played.
+pub struct Setup<'a, B: Backend> {
+    running: bool,
+    tui: Option<&'a mut Tui<B>>,
+    /// The options the game will be started with.
+    pub options: GameOptions,
+    /// The profiles playing the game, which rate it if every side not played by the computer has one.
+    pub players: Players,
+    /// The profiles to pick the players from.
+    profiles: Vec<Profile>,
+    /// Signifies whether the players are picked, which they are not for games played with someone elsewhere.
+    picks_players: bool,
+    /// The index of the currently selected option.
+    state: u8,
+    /// Signifies whether the player chose to start the game, rather than going back.
+    confirmed: bool,
+}
+
+/// The choices made on the setup screen, which decide how a chess game is played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GameOptions {
+    pub start: StartingPosition,
+    pub variant: Variant,
+    pub opponent: Opponent,
+    /// The time each player has for the game, or none for an untimed game.
+    pub time_control: Option<TimeControl>,
+    /// How much of the board is hidden from the players, who then type their moves.
+    pub blindfold: Blindfold,
+}
+
+impl GameOptions {
+    /// Settles on a random Chess960 position, if one was asked for, and on a human opponent
+    /// in a variant the engine cannot play.
+    pub fn resolve(self) -> Self {
+        let opponent = match self.variant.has_engine_support() {
+            true => self.opponent,
+            false => Opponent::Human,
+        };
+        Self {
+            start: self.start.resolve(),
+            opponent,
+            ..self
+        }
+    }
+
+    /// The board the game starts from, played by the rules of the chosen variant.
+    pub fn board(self) -> Board {
+        let mut board = self.start.board();
+        board.set_variant(self.variant);
+        board
+    }
+
+    /// The options a game was started with, as far as can be told from the game itself.
+    pub fn of_game(game: &Game) -> Self {
+        let start = game
+            .history()
+            .first()
+            .map(|ply| ply.board)
+            .unwrap_or(*game.board());
+        let mut standard = start;
+        standard.set_variant(Variant::Standard);
+        // the starting position is only known by its FEN, so the Chess960 position is looked up to name the game
+        let chess960 = (0..CHESS960_POSITIONS).find(|index| Board::chess960(*index) == standard);
+        Self {
+            start: match chess960 {
+                _ if standard == Board::new() => StartingPosition::Standard,
+                Some(index) => StartingPosition::Chess960(Some(index)),
+                None => StartingPosition::Custom(standard),
+            },
+            variant: start.variant(),
+            ..Self::default()
+        }
+    }
+
+    /// The `Variant` tag of the game, for games not played by the standard rules from the standard position.
+    pub fn variant_tags(self) -> Vec<(&'static str, String)> {
+        match (self.variant, self.start) {
+            // the position itself is told by the FEN tag
+            (Variant::Standard, StartingPosition::Standard | StartingPosition::Custom(_)) => {
+                Vec::new()
+            }
+            (Variant::Standard, _) => vec![("Variant", String::from("Chess960"))],
+            (variant, _) => vec![("Variant", variant.name().to_owned())],
+        }
+    }
+
+    /// The name of the game, made up of the variant and the starting position.
+    pub fn name(self) -> String {
+        match (self.variant, self.start) {
+            (Variant::Standard, start) => start.name(),
+            (variant, StartingPosition::Standard) => variant.name().to_owned(),
+            (variant, start) => format!("{} {}", variant.name(), start.name()),
+        }
+    }
+}
+
+/// Who the game is played against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Opponent {
+    /// Both sides are played by people sharing the terminal.
+    #[default]
+    Human,
+    /// The engine plays the given side.
+    Computer(PieceColor),
+}
+
+impl Opponent {
+    pub const ALL: [Opponent; 3] = [
+        Opponent::Human,
+        Opponent::Computer(PieceColor::Black),
+        Opponent::Computer(PieceColor::White),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Opponent::Human => "Human",
+            Opponent::Computer(PieceColor::Black) => "Computer (black)",
+            Opponent::Computer(PieceColor::White) => "Computer (white)",
+        }
+    }
+
+    /// The side played by the computer, if any.
+    pub fn computer(self) -> Option<PieceColor> {
+        match self {
+            Opponent::Human => None,
+            Opponent::Computer(color) => Some(color),
+        }
+    }
+
+    /// The opponent after this one, wrapping around to the first.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|opponent| *opponent == self);
+        Self::ALL[(index.unwrap_or(0) + 1) % Self::ALL.len()]
+    }
+
+    /// The opponent before this one, wrapping around to the last.
+    pub fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|opponent| *opponent == self);
+        Self::ALL[(index.unwrap_or(0) + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// How much of the board is hidden in a blindfold game, where the moves are typed in algebraic notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Blindfold {
+    /// The board is shown as usual.
+    #[default]
+    Off,
+    /// The squares are shown, but not the pieces on them.
+    HiddenPieces,
+    /// Nothing of the board is shown, only the moves played.
+    BlankBoard,
+}
+
+impl Blindfold {
+    pub const ALL: [Blindfold; 3] = [
+        Blindfold::Off,
+        Blindfold::HiddenPieces,
+        Blindfold::BlankBoard,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Blindfold::Off => "Off",
+            Blindfold::HiddenPieces => "Hidden pieces",
+            Blindfold::BlankBoard => "Blank board",
+        }
+    }
+
+    /// The blindfold after this one, wrapping around to the first.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|blindfold| *blindfold == self);
+        Self::ALL[(index.unwrap_or(0) + 1) % Self::ALL.len()]
+    }
+
+    /// The blindfold before this one, wrapping around to the last.
+    pub fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|blindfold| *blindfold == self);
+        Self::ALL[(index.unwrap_or(0) + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StartingPosition {
+    #[default]
+    Standard,
+    /// A Chess960 position by its Scharnagl number, or a random one if none is given.
+    Chess960(Option<u16>),
+    /// A position set up on the board editor.
+    Custom(Board),
+}
+
+impl StartingPosition {
+    /// Settles on a random Chess960 position, if one was asked for.
+    pub fn resolve(self) -> Self {
+        match self {
+            StartingPosition::Chess960(None) => {
+                StartingPosition::Chess960(Some(fastrand::u16(0..CHESS960_POSITIONS)))
+            }
+            other => other,
+        }
+    }
+
+    /// The board of the starting position; a random Chess960 position is picked anew on every call.
+    pub fn board(self) -> Board {
+        match self.resolve() {
+            StartingPosition::Chess960(Some(index)) => Board::chess960(index),
+            StartingPosition::Custom(board) => board,
+            _ => Board::new(),
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            StartingPosition::Standard => String::from("Chess"),
+            StartingPosition::Chess960(Some(index)) => format!("Chess960 #{index}"),
+            StartingPosition::Chess960(None) => String::from("Chess960"),
+            StartingPosition::Custom(_) => String::from("Custom position"),
+        }
+    }
+}
+
+impl<'a, B: Backend> Setup<'a, B> {
+    pub fn new(tui: &'a mut Tui<B>) -> Self {
+        Self {
+            running: true,
+            tui: Some(tui),
+            options: GameOptions::default(),
+            players: Players::default(),
+            profiles: profiles::load(),
+            picks_players: true,
+            state: 0,
+            confirmed: false,
+        }
+    }
+
+    /// Constructs a setup screen for a game from the position, which cannot be changed here.
+    /// Such a game is not rated, as the position may favor either side.
+    pub fn with_position(tui: &'a mut Tui<B>, board: Board) -> Self {
+        let mut setup = Self::without_players(tui);
+        setup.options.start = StartingPosition::Custom(board);
+        // the mode and the position are already settled
+        setup.state = 2;
+        setup
+    }
+
+    /// Constructs a setup screen for a game played with someone elsewhere, which is not rated.
+    pub fn without_players(tui: &'a mut Tui<B>) -> Self {
+        Self {
+            picks_players: false,
+            ..Self::new(tui)
+        }
+    }
+
+    /// Runs the setup screen, returning the chosen options, or nothing if the player went back.
+    pub fn launch_setup(&mut self) -> AppResult<Option<GameOptions>> {
+        while self.running {
+            let tui = self.tui.take().expect("should always contain a tui");
+            tui.terminal.draw(|frame| ui::render(self, frame))?;
+            match tui.events.next()? {
+                Event::Tick => {}
+                Event::Key(key_event) => handle_key_events(key_event, self)?,
+                Event::Mouse(_) => {}
+                Event::Resize(_, _) => {}
+            }
+            self.tui = Some(tui);
+        }
+        Ok(self.confirmed.then_some(self.options))
+    }
+
+    pub fn quit(&mut self) {
+        self.running = false;
+    }
+
+    /// Starts the game with the chosen options.
+    pub fn confirm(&mut self) {
+        // the side of the computer is not played by a profile, even if one was picked for it before
+        if let Opponent::Computer(color) = self.options.opponent {
+            self.players.set(color, None);
+        }
+        self.confirmed = true;
+        self.running = false;
+    }
+
+    /// Changes the selected option, to the one directly above it - without wrapping around.
+    pub fn move_up(&mut self) {
+        if self.state > self.first_option() {
+            self.state -= 1;
+        }
+    }
+
+    /// Changes the selected option, to the one directly below it - without wrapping around.
+    pub fn move_down(&mut self) {
+        if self.state < self.option_count() - 1 {
+            self.state += 1;
+        }
+    }
+
+    /// Changes the value of the selected option, to the one before it.
+    pub fn previous_value(&mut self) {
+        let start = &mut self.options.start;
+        match (self.state, *start) {
+            (0, StartingPosition::Standard) => *start = StartingPosition::Chess960(None),
+            (0, StartingPosition::Chess960(_)) => *start = StartingPosition::Standard,
+            // stepping back from the random position wraps around to the last one
+            (1, StartingPosition::Chess960(None)) => {
+                *start = StartingPosition::Chess960(Some(CHESS960_POSITIONS - 1))
+            }
+            (1, StartingPosition::Chess960(Some(index))) => {
+                *start = StartingPosition::Chess960(index.checked_sub(1))
+            }
+            (2, _) => self.change_variant(self.options.variant.previous()),
+            (3, _) => self.change_opponent(self.options.opponent.previous()),
+            (4, _) => self.options.time_control = TimeControl::previous(self.options.time_control),
+            (5, _) => self.options.blindfold = self.options.blindfold.previous(),
+            (6, _) => self.change_player(PieceColor::White, false),
+            (7, _) => self.change_player(PieceColor::Black, false),
+            _ => {}
+        }
+    }
+
+    /// Changes the value of the selected option, to the one after it.
+    pub fn next_value(&mut self) {
+        // the mode only has two values, so going forwards is the same as going backwards
+        if self.state == 0 {
+            return self.previous_value();
+        }
+
+        let start = &mut self.options.start;
+        match (self.state, *start) {
+            (1, StartingPosition::Chess960(None)) => *start = StartingPosition::Chess960(Some(0)),
+            (1, StartingPosition::Chess960(Some(index))) => {
+                let next = Some(index + 1).filter(|next| *next < CHESS960_POSITIONS);
+                *start = StartingPosition::Chess960(next)
+            }
+            (2, _) => self.change_variant(self.options.variant.next()),
+            (3, _) => self.change_opponent(self.options.opponent.next()),
+            (4, _) => self.options.time_control = TimeControl::next(self.options.time_control),
+            (5, _) => self.options.blindfold = self.options.blindfold.next(),
+            (6, _) => self.change_player(PieceColor::White, true),
+            (7, _) => self.change_player(PieceColor::Black, true),
+            _ => {}
+        }
+    }
+
+    /// Changes the variant, going back to a human opponent if the engine cannot play it.
+    fn change_variant(&mut self, variant: Variant) {
+        self.options.variant = variant;
+        if !variant.has_engine_support() {
+            self.options.opponent = Opponent::Human;
+        }
+    }
+
+    /// Changes the opponent, leaving out the computer in a variant the engine cannot play.
+    fn change_opponent(&mut self, opponent: Opponent) {
+        if self.options.variant.has_engine_support() {
+            self.options.opponent = opponent;
+        }
+    }
+
+    /// The first option that can be changed, skipping the mode and the position when playing from a set position.
+    fn first_option(&self) -> u8 {
+        match self.options.start {
+            StartingPosition::Custom(_) => 2,
+            _ => 0,
+        }
+    }
+
+    fn option_count(&self) -> u8 {
+        match self.picks_players {
+            true => OPTION_COUNT,
+            false => OPTION_COUNT - PLAYER_OPTION_COUNT,
+        }
+    }
+
+    /// Changes the profile playing the side to the one after or before it, going through playing without one
+    /// and leaving out the profile playing the other side. The side of the computer is not played by a profile.
+    fn change_player(&mut self, color: PieceColor, forwards: bool) {
+        if self.options.opponent == Opponent::Computer(color) {
+            return;
+        }
+        let other = self.players.get(color.opposite());
+        let choices: Vec<Option<&str>> = std::iter::once(None)
+            .chain(
+                self.profiles
+                    .iter()
+                    .map(|profile| Some(profile.name.as_str()))
+                    .filter(|name| *name != other),
+            )
+            .collect();
+        let index = choices
+            .iter()
+            .position(|name| *name == self.players.get(color))
+            .unwrap_or(0);
+        let index = match forwards {
+            true => (index + 1) % choices.len(),
+            false => (index + choices.len() - 1) % choices.len(),
+        };
+        let name = choices[index].map(String::from);
+        self.players.set(color, name);
+    }
+
+    /// The rating of the profile with the name, if there is one.
+    fn rating(&self, name: &str) -> Option<i32> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .map(|profile| profile.rating())
+    }
+
+    /// Appends a digit to the number of the Chess960 position, as long as it stays a valid position.
+    pub fn type_digit(&mut self, digit: u16) {
+        if let (1, StartingPosition::Chess960(index)) = (self.state, self.options.start) {
+            let typed = index.unwrap_or(0) * 10 + digit;
+            if typed < CHESS960_POSITIONS {
+                self.options.start = StartingPosition::Chess960(Some(typed));
+            }
+        }
+    }
+
+    /// Removes the last digit of the number of the Chess960 position, going back to a random position once empty.
+    pub fn erase_digit(&mut self) {
+        if let (1, StartingPosition::Chess960(Some(index))) = (self.state, self.options.start) {
+            let erased = Some(index / 10).filter(|_| index >= 10);
+            self.options.start = StartingPosition::Chess960(erased);
+        }
+    }
+
+    /// Goes back to a random Chess960 position.
+    pub fn randomize(&mut self) {
+        if let StartingPosition::Chess960(_) = self.options.start {
+            self.options.start = StartingPosition::Chess960(None);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use super::{Opponent, Setup, StartingPosition};
+use crate::chess::{game::piece::PieceColor, profiles::computer_rating};
+use tui::{
+    backend::Backend,
+    layout::Alignment,
+    prelude::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Paragraph},
+    Frame,
+};
+
+/// The width of the option labels, so that the values line up.
+const LABEL_WIDTH: usize = 10;
+
+/// The width of the option values, fitting the longest profile name along with its rating.
+const VALUE_WIDTH: usize = 19;
+
+/// Renders the user interface widgets.
+pub fn render<B: Backend>(setup: &mut Setup<B>, frame: &mut Frame<'_, B>) {
+    outer_block(frame);
+    titles(frame);
+    options(setup, frame);
+    key_hints(frame);
+}
+
+#[inline(always)]
+fn outer_block<B: Backend>(frame: &mut Frame<'_, B>) {
+    let outer_block = Block::default()
+        .title("New Game")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    frame.render_widget(outer_block, frame.size());
+}
+
+#[inline(always)]
+fn titles<B: Backend>(frame: &mut Frame<'_, B>) {
+    let y_axis = (frame.size().height / 2).saturating_sub(3);
+
+    let title_style = Style::default()
+        .add_modifier(Modifier::BOLD)
+        .fg(Color::White);
+    let title = Paragraph::new("Chess")
+        .style(title_style)
+        .alignment(Alignment::Center);
+    frame.render_widget(title, Rect::new(0, y_axis, frame.size().width, 1));
+
+    let sub_title_style = Style::default()
+        .add_modifier(Modifier::ITALIC)
+        .fg(Color::White);
+    let sub_title = Paragraph::new("Choose how the game is played")
+        .style(sub_title_style)
+        .alignment(Alignment::Center);
+    frame.render_widget(sub_title, Rect::new(0, y_axis + 1, frame.size().width, 1));
+}
+
+#[inline(always)]
+fn options<B: Backend>(setup: &mut Setup<B>, frame: &mut Frame<'_, B>) {
+    let mode = match setup.options.start {
+        StartingPosition::Standard => "Standard",
+        StartingPosition::Chess960(_) => "Chess960",
+        StartingPosition::Custom(_) => "Custom",
+    };
+    let position = match setup.options.start {
+        StartingPosition::Standard => String::from("Standard"),
+        StartingPosition::Chess960(None) => String::from("Random"),
+        StartingPosition::Chess960(Some(index)) => format!("#{index}"),
+        StartingPosition::Custom(_) => String::from("Edited"),
+    };
+    let mut rows = vec![
+        ("Mode", mode.to_owned()),
+        ("Position", position),
+        ("Variant", setup.options.variant.name().to_owned()),
+        (
+            "Opponent",
+            match setup.options.variant.has_engine_support() {
+                true => setup.options.opponent.name().to_owned(),
+                false => String::from("Human (no engine for this variant)"),
+            },
+        ),
+        (
+            "Clock",
+            setup
+                .options
+                .time_control
+                .map(|time_control| time_control.name())
+                .unwrap_or_else(|| String::from("None")),
+        ),
+        ("Blindfold", setup.options.blindfold.name().to_owned()),
+    ];
+    if setup.picks_players {
+        rows.push(("White", player(setup, PieceColor::White)));
+        rows.push(("Black", player(setup, PieceColor::Black)));
+    }
+    let row_count = rows.len() as u16;
+
+    let y_axis = frame.size().height / 2;
+    for (index, (label, value)) in rows.into_iter().enumerate() {
+        let style = if index as u8 == setup.state {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+
+        let text = format!("{label:<LABEL_WIDTH$}◀ {value:^VALUE_WIDTH$} ▶");
+        let area = Rect::new(0, y_axis + index as u16, frame.size().width, 1);
+        let row = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(style);
+        frame.render_widget(row, area);
+    }
+
+    if setup.picks_players {
+        let text = match setup.players.is_rated(setup.options.opponent.computer()) {
+            true => "Rated game",
+            false => "Unrated game · pick a profile for each player to have it rated",
+        };
+        let area = Rect::new(0, y_axis + row_count + 1, frame.size().width, 1);
+        let rating = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(rating, area);
+    }
+}
+
+/// The profile playing the side along with its rating, or the computer when it plays the side.
+fn player<B: Backend>(setup: &Setup<B>, color: PieceColor) -> String {
+    if setup.options.opponent == Opponent::Computer(color) {
+        let rating = computer_rating(setup.options.time_control);
+        return format!("Computer ({rating})");
+    }
+    match setup.players.get(color) {
+        Some(name) => match setup.rating(name) {
+            Some(rating) => format!("{name} ({rating})"),
+            None => name.to_owned(),
+        },
+        None => String::from("Guest"),
+    }
+}
+
+#[inline(always)]
+fn key_hints<B: Backend>(frame: &mut Frame<'_, B>) {
+    let text = "←/→ change · 0-9 position number · r random · Enter start · Esc back";
+    let y_axis = frame.size().height.saturating_sub(2);
+    let area = Rect::new(1, y_axis, frame.size().width.saturating_sub(2), 1);
+    let hints = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(hints, area);
+}
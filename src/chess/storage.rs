@@ -0,0 +1,6 @@
+use std::path::PathBuf;
+
+/// The directory the chess data of the player is kept in, such as the puzzle rating, if the platform has one.
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("terminal-games").join("chess"))
+}
@@ -0,0 +1,220 @@
+use super::game::{
+    board::Board,
+    chess_move::{CastleSide, Move, MoveKind},
+    Game,
+};
+
+/// What the player has to do to finish a lesson.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Goal {
+    /// Play one of the moves, given in UCI notation.
+    Moves(&'static [&'static str]),
+    /// Give check with any move.
+    Check,
+    /// Castle to the side.
+    Castle(CastleSide),
+    /// Capture a pawn en passant.
+    EnPassant,
+    /// Checkmate with any move.
+    Checkmate,
+}
+
+impl Goal {
+    /// Whether playing the move from the position reaches the goal.
+    fn is_reached(self, board: &Board, mv: &Move) -> bool {
+        let mut after = *board;
+        after.apply(mv);
+        match self {
+            Goal::Moves(moves) => moves.contains(&mv.uci().as_str()),
+            Goal::Check => after.in_check(),
+            Goal::Castle(side) => mv.kind == MoveKind::Castle(side),
+            Goal::EnPassant => mv.kind == MoveKind::EnPassant,
+            Goal::Checkmate => after.in_check() && after.legal_moves().is_empty(),
+        }
+    }
+}
+
+/// A single step of the tutorial: a position to play a move in, and what the move should achieve.
+pub struct Lesson {
+    pub title: &'static str,
+    /// What the player is asked to do, short enough to fit below the board.
+    pub instruction: &'static str,
+    fen: &'static str,
+    goal: Goal,
+}
+
+/// The lessons of the tutorial, from how each piece moves to the special moves and the basic mates.
+const LESSONS: [Lesson; 16] = [
+    Lesson {
+        title: "The pawn",
+        instruction: "Pawns move straight ahead, two squares on their first move. Play e4.",
+        fen: "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        goal: Goal::Moves(&["e2e4"]),
+    },
+    Lesson {
+        title: "Pawn captures",
+        instruction: "Pawns capture one square diagonally forward. Capture the knight.",
+        fen: "4k3/8/8/8/3n4/4P3/8/4K3 w - - 0 1",
+        goal: Goal::Moves(&["e3d4"]),
+    },
+    Lesson {
+        title: "The knight",
+        instruction: "Knights jump in an L shape, over any piece in the way. Capture the rook.",
+        fen: "4k3/8/8/5r2/4P3/3PNP2/8/4K3 w - - 0 1",
+        goal: Goal::Moves(&["e3f5"]),
+    },
+    Lesson {
+        title: "The bishop",
+        instruction: "Bishops move any number of squares diagonally. Capture the rook.",
+        fen: "4k3/8/6r1/8/8/8/2B5/4K3 w - - 0 1",
+        goal: Goal::Moves(&["c2g6"]),
+    },
+    Lesson {
+        title: "The rook",
+        instruction: "Rooks move any number of squares along ranks and files. Capture the queen.",
+        fen: "4k3/8/1q6/8/8/8/8/1R2K3 w - - 0 1",
+        goal: Goal::Moves(&["b1b6"]),
+    },
+    Lesson {
+        title: "The queen",
+        instruction: "The queen moves like a rook and a bishop together. Capture the rook.",
+        fen: "4k3/8/8/7r/8/8/8/3QK3 w - - 0 1",
+        goal: Goal::Moves(&["d1h5"]),
+    },
+    Lesson {
+        title: "The king",
+        instruction: "The king moves one square in any direction. Capture the pawn.",
+        fen: "4k3/8/8/8/8/4p3/4K3/8 w - - 0 1",
+        goal: Goal::Moves(&["e2e3"]),
+    },
+    Lesson {
+        title: "Check",
+        instruction: "A piece attacking the king gives check. Give check with the rook.",
+        fen: "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+        goal: Goal::Check,
+    },
+    Lesson {
+        title: "Getting out of check",
+        instruction: "A king in check has to be saved at once. Capture the checking queen.",
+        fen: "4k3/8/8/8/8/8/3q4/2B1K3 w - - 0 1",
+        goal: Goal::Moves(&["c1d2", "e1d2"]),
+    },
+    Lesson {
+        title: "Castling kingside",
+        instruction: "Castle by moving the king two squares towards the rook. Castle kingside.",
+        fen: "4k3/8/8/8/8/8/5PPP/4K2R w K - 0 1",
+        goal: Goal::Castle(CastleSide::King),
+    },
+    Lesson {
+        title: "Castling queenside",
+        instruction: "Castling works on both sides, if neither piece has moved. Castle queenside.",
+        fen: "4k3/8/8/8/8/8/PPP5/R3K3 w Q - 0 1",
+        goal: Goal::Castle(CastleSide::Queen),
+    },
+    Lesson {
+        title: "En passant",
+        instruction: "The d-pawn just moved two squares past yours. Capture it en passant.",
+        fen: "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2",
+        goal: Goal::EnPassant,
+    },
+    Lesson {
+        title: "Promotion",
+        instruction: "A pawn reaching the last rank becomes another piece. Promote to a queen.",
+        fen: "4k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+        goal: Goal::Moves(&["a7a8q"]),
+    },
+    Lesson {
+        title: "Back rank mate",
+        instruction: "Checkmate is a check with no way out. Checkmate with the rook.",
+        fen: "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+        goal: Goal::Checkmate,
+    },
+    Lesson {
+        title: "Mate with the queen",
+        instruction: "The king can help the queen take away the last squares. Checkmate.",
+        fen: "k7/8/1K6/8/8/8/8/7Q w - - 0 1",
+        goal: Goal::Checkmate,
+    },
+    Lesson {
+        title: "Mate with two rooks",
+        instruction: "One rook cuts the king off while the other checks. Checkmate.",
+        fen: "7k/1R6/8/8/8/8/R7/6K1 w - - 0 1",
+        goal: Goal::Checkmate,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LessonState {
+    /// The player has yet to play the move the lesson asks for.
+    Learning,
+    /// The last move of the player was not the one asked for and was taken back, so the player may try again.
+    Wrong,
+    Done,
+}
+
+/// A walk through the lessons of the tutorial, in order.
+pub struct Tutorial {
+    /// The index of the current lesson.
+    index: usize,
+    state: LessonState,
+}
+
+impl Tutorial {
+    /// Starts the tutorial with the first lesson, setting up the game in its position.
+    pub fn new(game: &mut Game) -> Self {
+        let mut tutorial = Self {
+            index: 0,
+            state: LessonState::Learning,
+        };
+        tutorial.set_up(game);
+        tutorial
+    }
+
+    pub fn lesson(&self) -> &Lesson {
+        &LESSONS[self.index]
+    }
+
+    /// The number of the current lesson, counting from one, and the number of lessons.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.index + 1, LESSONS.len())
+    }
+
+    pub fn state(&self) -> LessonState {
+        self.state
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.index + 1 == LESSONS.len()
+    }
+
+    /// Moves on to the next lesson, or starts over after the last one.
+    pub fn next(&mut self, game: &mut Game) {
+        self.index = (self.index + 1) % LESSONS.len();
+        self.set_up(game);
+    }
+
+    /// Goes back to the previous lesson, staying at the first one.
+    pub fn previous(&mut self, game: &mut Game) {
+        self.index = self.index.saturating_sub(1);
+        self.set_up(game);
+    }
+
+    fn set_up(&mut self, game: &mut Game) {
+        let board = Board::from_fen(self.lesson().fen).expect("lessons should be valid positions");
+        *game = Game::with_board(board);
+        self.state = LessonState::Learning;
+    }
+
+    /// Plays the move of the player if it does what the lesson asks for, and takes it back otherwise.
+    pub fn play(&mut self, game: &mut Game, mv: Move) {
+        if self.state == LessonState::Done {
+            return;
+        }
+        if !self.lesson().goal.is_reached(game.board(), &mv) {
+            self.state = LessonState::Wrong;
+            return;
+        }
+        game.make_move(mv);
+        self.state = LessonState::Done;
+    }
+}
@@ -1,31 +1,191 @@
-use super::Chess;
+use super::{
+    clock::format_time,
+    engine::tablebase,
+    game::{
+        board_widget::{BoardScale, BoardWidget},
+        piece::{Piece, PieceColor, PieceSet, PieceType},
+        san_line,
+        variant::{Variant, CHECKS_TO_WIN},
+        GameStatus,
+    },
+    profiles::computer_rating,
+    puzzle::{puzzle_file, PuzzleSession, PuzzleState},
+    review::{Annotation, Review},
+    setup::{Blindfold, Opponent},
+    tutorial::{LessonState, Tutorial},
+    Chess,
+};
 use tui::{
-    prelude::{Alignment, Backend, Rect},
-    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph},
+    prelude::{Alignment, Backend, Margin, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        block::{Position as TitlePosition, Title},
+        Block, BorderType, Borders, Clear, List, ListItem, Paragraph,
+    },
     Frame,
 };
 
-/// The total width of the board in characters; includes the number markings.
-const BOARD_WIDTH: u16 = 36;
-
-/// The total height of the board in characters; includes the letter markings.
-const BOARD_HEIGHT: u16 = 18;
-
 /// The minimum width the terminal requires to render all the components.
-const MINIMUM_TERMINAL_WIDTH: u16 = 62;
+const MINIMUM_TERMINAL_WIDTH: u16 = 24;
 
 // The minimum height the terminal requires to render all the components.
-const MINIMUM_TERMINAL_HEIGHT: u16 = 31;
+const MINIMUM_TERMINAL_HEIGHT: u16 = 19;
+
+/// The width of the move lists beside the board, fitting a piece and the longest algebraic notation.
+const MOVE_LIST_WIDTH: u16 = 9;
+
+/// The number of most recent moves shown in each move list.
+const MOVE_LIST_LENGTH: usize = 10;
+
+/// The fewest moves worth showing when the move lists collapse below the board.
+const MINIMUM_MOVE_LIST_LENGTH: u16 = 3;
+
+/// The space between the move lists and the components next to them.
+const MOVE_LIST_GAP: u16 = 2;
+
+/// The widest the row of taken pieces can become, with every piece of one side taken.
+const TAKEN_PIECES_WIDTH: u16 = 32;
+
+/// The space between the evaluation bar and the board.
+const EVAL_BAR_GAP: u16 = 1;
+
+/// The rows above and below the board: a nameplate, the taken pieces and a gap for each player, as well as the status with gaps around it.
+const ROWS_AROUND_BOARD: u16 = 8;
+
+/// The areas of the components on the chess screen, fitted to the size of the terminal.
+struct ChessLayout {
+    scale: BoardScale,
+    board: Rect,
+    black_nameplate: Rect,
+    black_taken_pieces: Rect,
+    black_moves: Rect,
+    white_nameplate: Rect,
+    white_taken_pieces: Rect,
+    white_moves: Rect,
+    status: Rect,
+    /// The row below the status, showing the analysis of the engine in analysis mode.
+    analysis: Rect,
+    /// The column left of the board, showing the evaluation of the engine in analysis mode.
+    eval_bar: Rect,
+}
+
+impl ChessLayout {
+    /// Picks the largest board that fits within the outer block, preferring the move lists beside the board,
+    /// then below it, and finally leaving them out on the smallest board.
+    fn new(area: Rect) -> Self {
+        let area = area.inner(&Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+
+        BoardScale::LARGEST_FIRST
+            .into_iter()
+            .find_map(|scale| {
+                Self::with_moves_beside(area, scale).or_else(|| Self::with_moves_below(area, scale))
+            })
+            .unwrap_or_else(|| Self::column(area, BoardScale::Compact, 0))
+    }
+
+    fn with_moves_beside(area: Rect, scale: BoardScale) -> Option<Self> {
+        let panel_width = MOVE_LIST_WIDTH + MOVE_LIST_GAP;
+        if scale.width() + 2 * panel_width > area.width
+            || scale.height() + ROWS_AROUND_BOARD > area.height
+        {
+            return None;
+        }
+
+        let mut layout = Self::column(area, scale, 0);
+        let height = (MOVE_LIST_LENGTH as u16).min(scale.height());
+        let y_axis = layout.board.y + (scale.height() - height) / 2;
+        layout.black_moves = Rect::new(
+            layout.board.x - panel_width,
+            y_axis,
+            MOVE_LIST_WIDTH,
+            height,
+        );
+        layout.white_moves = Rect::new(
+            layout.board.right() + MOVE_LIST_GAP,
+            y_axis,
+            MOVE_LIST_WIDTH,
+            height,
+        );
+        Some(layout)
+    }
+
+    fn with_moves_below(area: Rect, scale: BoardScale) -> Option<Self> {
+        let column_height = scale.height() + ROWS_AROUND_BOARD;
+        let width = 2 * MOVE_LIST_WIDTH + MOVE_LIST_GAP;
+        if scale.width().max(width) > area.width
+            || column_height + 1 + MINIMUM_MOVE_LIST_LENGTH > area.height
+        {
+            return None;
+        }
+
+        let height = (MOVE_LIST_LENGTH as u16).min(area.height - column_height - 1);
+        let mut layout = Self::column(area, scale, height + 1);
+        let x_axis = area.x + (area.width - width) / 2;
+        let y_axis = layout.white_nameplate.bottom() + 1;
+        layout.black_moves = Rect::new(x_axis, y_axis, MOVE_LIST_WIDTH, height);
+        layout.white_moves = Rect::new(
+            x_axis + MOVE_LIST_WIDTH + MOVE_LIST_GAP,
+            y_axis,
+            MOVE_LIST_WIDTH,
+            height,
+        );
+        Some(layout)
+    }
+
+    /// Stacks the players around the board, centered in the area with room left for the given number of rows below.
+    fn column(area: Rect, scale: BoardScale, rows_below: u16) -> Self {
+        let total_height = scale.height() + ROWS_AROUND_BOARD + rows_below;
+        let top = area.y + area.height.saturating_sub(total_height) / 2;
+        let row = |y: u16| Rect::new(area.x, y, area.width, 1);
+
+        let board_x = area.x + area.width.saturating_sub(scale.width()) / 2;
+        let board = Rect::new(
+            board_x,
+            top + 3,
+            scale.width().min(area.width),
+            scale.height(),
+        );
+        let taken_pieces_width = TAKEN_PIECES_WIDTH.min(area.width);
+        let taken_pieces_x = area.x + (area.width - taken_pieces_width) / 2;
+        let taken_pieces = |y: u16| Rect::new(taken_pieces_x, y, taken_pieces_width, 1);
+        let eval_bar = match board.x > area.x + EVAL_BAR_GAP {
+            true => Rect::new(board.x - EVAL_BAR_GAP - 1, board.y, 1, board.height),
+            false => Rect::default(),
+        };
+
+        Self {
+            scale,
+            board,
+            black_nameplate: row(top),
+            black_taken_pieces: taken_pieces(top + 1),
+            black_moves: Rect::default(),
+            status: row(board.bottom() + 1),
+            analysis: row(board.bottom() + 2),
+            eval_bar,
+            white_taken_pieces: taken_pieces(board.bottom() + 3),
+            white_nameplate: row(board.bottom() + 4),
+            white_moves: Rect::default(),
+        }
+    }
+}
 
 pub fn render<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>) {
     if increase_terminal_size(frame) {
         return;
     }
 
-    outer_block(frame);
-    board(chess, frame);
-    white_player(chess, frame);
-    black_player(chess, frame);
+    let layout = ChessLayout::new(frame.size());
+    outer_block(chess, frame);
+    board(chess, frame, &layout);
+    white_player(chess, frame, &layout);
+    black_player(chess, frame, &layout);
+    status(chess, frame, &layout);
+    analysis(chess, frame, &layout);
+    promotion_picker(chess, frame, &layout);
 }
 
 fn increase_terminal_size<B: Backend>(frame: &mut Frame<'_, B>) -> bool {
@@ -58,123 +218,671 @@ fn increase_terminal_size<B: Backend>(frame: &mut Frame<'_, B>) -> bool {
 }
 
 #[inline(always)]
-fn outer_block<B: Backend>(frame: &mut Frame<'_, B>) {
-    let outer_block = Block::default()
-        .title("Chess")
+fn outer_block<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>) {
+    let title = match (chess.tutorial_session(), chess.puzzle_session()) {
+        (Some(tutorial), _) => {
+            let (number, count) = tutorial.progress();
+            format!("Lesson {number}/{count} · {}", tutorial.lesson().title)
+        }
+        (None, Some(session)) => match session.puzzle() {
+            Some(puzzle) => format!("Puzzle {} · rated {}", puzzle.id, puzzle.rating),
+            None => String::from("Puzzles"),
+        },
+        (None, None) if chess.analysis().is_some() => {
+            format!("{} · Analysis", chess.options.name())
+        }
+        (None, None) => {
+            let mut title = chess.options.name();
+            if let Some(time_control) = chess.options.time_control {
+                title.push_str(&format!(" · {}", time_control.name()));
+            }
+            match chess.network_session() {
+                Some(network) if network.is_spectator() => title.push_str(" · LAN · watching"),
+                Some(network) => {
+                    title.push_str(" · LAN");
+                    match network.spectator_count() {
+                        0 => {}
+                        1 => title.push_str(" · 1 spectator"),
+                        count => title.push_str(&format!(" · {count} spectators")),
+                    }
+                }
+                None if chess.correspondence_file().is_some() => {
+                    title.push_str(" · Correspondence")
+                }
+                None => {}
+            }
+            title
+        }
+    };
+    let mut outer_block = Block::default()
+        .title(title)
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded);
+    if let Some(opening) = opening(chess) {
+        outer_block = outer_block.title(
+            Title::from(opening)
+                .alignment(Alignment::Center)
+                .position(TitlePosition::Bottom),
+        );
+    }
     frame.render_widget(outer_block, frame.size());
 }
 
+/// The ECO code and name of the opening being played, marked when the position is still covered by the opening book.
+///
+/// The bundled table of openings is partial, so a game it has no name for says so rather than showing nothing.
+fn opening<B: Backend>(chess: &Chess<B>) -> Option<String> {
+    if chess.puzzle_session().is_some()
+        || chess.tutorial_session().is_some()
+        || !chess.is_standard_opening()
+        || chess.game.history().is_empty()
+    {
+        return None;
+    }
+    let Some(opening) = chess.openings.find(&chess.game) else {
+        return Some(format!(
+            " Opening not among the {} named in the bundled table ",
+            chess.openings.len()
+        ));
+    };
+    let in_book = chess
+        .book
+        .as_ref()
+        .map(|book| !book.moves(chess.game.board()).is_empty())
+        .unwrap_or(false);
+    Some(match in_book {
+        true => format!(" {} {} · book ", opening.eco, opening.name),
+        false => format!(" {} {} ", opening.eco, opening.name),
+    })
+}
+
+/// The board, with its pieces hidden or left out altogether in a blindfold game.
 #[inline(always)]
-fn board<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>) {
-    // these centerings do not account for the row numbers and column letters
-    // add 1 to both results to center in relation to the board itself
-    let x_axis = (frame.size().width / 2) - (BOARD_WIDTH / 2);
-    let y_axis = (frame.size().height / 2) - (BOARD_HEIGHT / 2);
-    let board_area = Rect::new(x_axis, y_axis, BOARD_WIDTH, BOARD_HEIGHT);
-    frame.render_widget(chess.game.owned_board(), board_area);
+fn board<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>, layout: &ChessLayout) {
+    chess.board_area = layout.board;
+    chess.board_scale = layout.scale;
+    let blindfold = chess.blindfold();
+    if blindfold == Blindfold::BlankBoard {
+        let area = Rect {
+            y: layout.board.y + layout.board.height / 2,
+            height: 1,
+            ..layout.board
+        };
+        let blank = Paragraph::new("Blindfold")
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(blank, area);
+        return;
+    }
+    let mut board = BoardWidget::new(chess.game.board());
+    // the cursor has nothing to pick when the moves are typed
+    if !chess.is_typing_moves() {
+        board = board.cursor(chess.cursor);
+    }
+    let board = board
+        .selected(chess.selected)
+        .targets(chess.targets())
+        .hint(chess.hint_squares())
+        .last_move(chess.game.last_move().copied())
+        .premove(chess.premove())
+        .piece_set(chess.piece_set)
+        .pieces_hidden(blindfold == Blindfold::HiddenPieces)
+        .scale(layout.scale);
+    frame.render_widget(board, layout.board);
 }
 
 #[inline(always)]
-fn black_player<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>) {
-    black_player_nameplate(frame);
-    black_player_taken_pieces(chess, frame);
-    black_player_moves(chess, frame);
+fn black_player<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>, layout: &ChessLayout) {
+    nameplate(chess, frame, PieceColor::Black, layout.black_nameplate);
+    taken_pieces(chess, frame, PieceColor::Black, layout.black_taken_pieces);
+    moves(chess, frame, PieceColor::Black, layout.black_moves);
 }
 
 #[inline(always)]
-fn black_player_nameplate<B: Backend>(frame: &mut Frame<'_, B>) {
-    let text = "BLACK PLAYER";
+fn white_player<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>, layout: &ChessLayout) {
+    nameplate(chess, frame, PieceColor::White, layout.white_nameplate);
+    taken_pieces(chess, frame, PieceColor::White, layout.white_taken_pieces);
+    moves(chess, frame, PieceColor::White, layout.white_moves);
+}
 
-    let x_axis = (frame.size().width / 2) - (text.len() / 2) as u16;
-    let y_axis = (frame.size().height / 2) - 14;
-    let text_area = Rect::new(x_axis, y_axis, text.len() as u16, 1);
-    let paragraph = Paragraph::new(text);
-    frame.render_widget(paragraph, text_area);
+#[inline(always)]
+fn nameplate<B: Backend>(
+    chess: &mut Chess<B>,
+    frame: &mut Frame<'_, B>,
+    color: PieceColor,
+    area: Rect,
+) {
+    let local = chess.local_color();
+    let profile = chess.profile(color);
+    let name = match (color, chess.options.opponent, profile) {
+        (color, _, _) if local == Some(color) => "YOU",
+        (_, _, _) if local.is_some() => "OPPONENT",
+        (color, Opponent::Computer(computer), _) if color == computer => "COMPUTER",
+        (_, _, Some(profile)) => profile.name.as_str(),
+        (PieceColor::White, _, None) => "WHITE PLAYER",
+        (PieceColor::Black, _, None) => "BLACK PLAYER",
+    };
+    let mut name = name.to_owned();
+    if chess.is_rated_game() {
+        match profile {
+            Some(profile) if chess.is_rated() => {
+                let change = profile.change().unwrap_or_default();
+                name.push_str(&format!(" ({} {change:+})", profile.rating()));
+            }
+            Some(profile) => name.push_str(&format!(" ({})", profile.rating())),
+            None => {
+                let rating = computer_rating(chess.options.time_control);
+                name.push_str(&format!(" ({rating})"));
+            }
+        }
+    }
+    let mut text = match chess.game.board().variant() {
+        Variant::ThreeCheck => {
+            let checks = chess.game.player(color).checks();
+            format!("{name} · {checks}/{CHECKS_TO_WIN} checks")
+        }
+        _ => name,
+    };
+    if let Some(clock) = chess.clock() {
+        text.push_str(&format!(" · {}", format_time(clock.remaining(color))));
+    }
+    if let Some(accuracy) = chess
+        .review()
+        .filter(|review| review.is_done())
+        .and_then(|review| review.accuracy(color))
+    {
+        text.push_str(&format!(" · {accuracy:.0}% accuracy"));
+    }
+    let hints = chess.hints_used(color);
+    if chess.game.status().is_over() && hints > 0 {
+        text.push_str(&match hints {
+            1 => String::from(" · 1 hint"),
+            hints => format!(" · {hints} hints"),
+        });
+    }
+    let paragraph = Paragraph::new(text).alignment(Alignment::Center);
+    frame.render_widget(paragraph, area);
 }
 
 #[inline(always)]
-fn black_player_taken_pieces<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>) {
-    let taken_pieces = " ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜ ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜";
-    let x_axis = (frame.size().width / 2) - (BOARD_WIDTH / 2) + 2;
-    let y_axis = (frame.size().height / 2) - 13;
-    let area = Rect::new(x_axis, y_axis, 32, 1);
+fn taken_pieces<B: Backend>(
+    chess: &mut Chess<B>,
+    frame: &mut Frame<'_, B>,
+    color: PieceColor,
+    area: Rect,
+) {
+    if chess.game.board().variant() == Variant::Crazyhouse {
+        return pocket(chess, frame, color, area);
+    }
+    let player = chess.game.player(color);
+    let taken_pieces = taken_pieces_text(player.taken_pieces(), chess.piece_set);
     let text = Paragraph::new(taken_pieces).alignment(Alignment::Left);
     frame.render_widget(text, area);
 }
 
+/// The width of a single piece in the pocket, fitting the symbol and a two digit count after a space.
+const POCKET_ENTRY_WIDTH: u16 = 4;
+
+/// Shows the taken pieces as the pocket of Crazyhouse, where they change sides and are counted by type.
+#[inline(always)]
+fn pocket<B: Backend>(
+    chess: &mut Chess<B>,
+    frame: &mut Frame<'_, B>,
+    color: PieceColor,
+    area: Rect,
+) {
+    let pieces = chess.game.player(color).taken_pieces();
+    let is_to_move = chess.game.board().turn() == color;
+
+    let mut spans = Vec::new();
+    for variant in PieceType::DROPS {
+        let count = pieces
+            .iter()
+            .filter(|piece| piece.variant() == variant)
+            .count();
+        if count == 0 {
+            continue;
+        }
+        let style = match is_to_move && chess.drop_choice() == Some(variant) {
+            true => Style::default().add_modifier(Modifier::REVERSED),
+            false => Style::default(),
+        };
+        let symbol = chess.piece_set.text_symbol(Piece::new(color, variant));
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(format!("{symbol}{count:<2}"), style));
+    }
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    if is_to_move {
+        chess.pocket_area = area;
+    }
+}
+
+/// Maps a terminal cell to the index of the pocket piece rendered there, if the pocket was rendered in the given area.
+pub fn pocket_choice_at(area: Rect, x: u16, y: u16) -> Option<usize> {
+    if y != area.y || x < area.x || x >= area.right() {
+        return None;
+    }
+    let column = x - area.x;
+    let is_separator = column.is_multiple_of(POCKET_ENTRY_WIDTH);
+    (!is_separator).then_some((column / POCKET_ENTRY_WIDTH) as usize)
+}
+
 #[inline(always)]
-fn black_player_moves<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>) {
-    let v = vec![
-        "♖ E2->E4",
-        "♖ E2->E4",
-        "♖ E2->E4",
-        "♖ E2->E4",
-        "♖ E2->E4",
-        "♖ E2->E4",
-        "♖ E2->E4",
-        "♖ E2->E4",
-        "♖ E2->E4",
-        "♖ E2->E4",
-    ];
-
-    let items: Vec<ListItem> = v.iter().map(|item| ListItem::new(*item)).collect();
-    let x_axis = (frame.size().width / 2) - 30;
-    let y_axis = (frame.size().height / 2) - 5;
-    let area = Rect::new(x_axis, y_axis, 8, items.len() as u16);
+fn moves<B: Backend>(
+    chess: &mut Chess<B>,
+    frame: &mut Frame<'_, B>,
+    color: PieceColor,
+    area: Rect,
+) {
+    let items = move_items(chess, color, area.height as usize);
     let moves = List::new(items);
     frame.render_widget(moves, area);
 }
 
-#[inline(always)]
-fn white_player<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>) {
-    white_player_nameplate(frame);
-    white_player_taken_pieces(chess, frame);
-    white_player_moves(chess, frame);
+fn taken_pieces_text(pieces: &[Piece], piece_set: PieceSet) -> String {
+    pieces
+        .iter()
+        .map(|piece| format!(" {}", piece_set.text_symbol(*piece)))
+        .collect()
+}
+
+/// The most recent moves of the given color, each shown as the moving piece and its algebraic notation,
+/// followed by the annotation of the review if there is one.
+fn move_items<B: Backend>(
+    chess: &Chess<B>,
+    color: PieceColor,
+    length: usize,
+) -> Vec<ListItem<'static>> {
+    let moves: Vec<ListItem> = chess
+        .game
+        .history()
+        .iter()
+        .enumerate()
+        .filter(|(_, ply)| ply.mv.piece.color() == color)
+        .map(|(index, ply)| {
+            let annotation = chess
+                .review()
+                .and_then(|review| review.annotation(index, &ply.mv));
+            let text = format!(
+                "{} {}{}",
+                chess.piece_set.text_symbol(ply.mv.piece),
+                ply.san,
+                annotation.map(Annotation::symbol).unwrap_or_default()
+            );
+            match annotation {
+                Some(annotation) => ListItem::new(text).style(annotation_style(annotation)),
+                None => ListItem::new(text),
+            }
+        })
+        .collect();
+    let skip = moves.len().saturating_sub(length);
+    moves.into_iter().skip(skip).collect()
+}
+
+fn annotation_style(annotation: Annotation) -> Style {
+    match annotation {
+        Annotation::Inaccuracy => Style::default().fg(Color::Yellow),
+        Annotation::Mistake => Style::default().fg(Color::LightRed),
+        Annotation::Blunder => Style::default().fg(Color::Red),
+    }
 }
 
 #[inline(always)]
-fn white_player_nameplate<B: Backend>(frame: &mut Frame<'_, B>) {
-    let text = "WHITE PLAYER";
+fn status<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>, layout: &ChessLayout) {
+    let board = chess.game.board();
+    let side = |color: PieceColor| match color {
+        PieceColor::White => "White",
+        PieceColor::Black => "Black",
+    };
 
-    let x_axis = (frame.size().width / 2) - (text.len() / 2) as u16;
-    let y_axis = (frame.size().height / 2) + 14;
-    let text_area = Rect::new(x_axis, y_axis, text.len() as u16, 1);
-    let paragraph = Paragraph::new(text);
-    frame.render_widget(paragraph, text_area);
+    if let Some(tutorial) = chess.tutorial_session() {
+        let paragraph = Paragraph::new(tutorial.lesson().instruction)
+            .alignment(Alignment::Center)
+            .style(Style::default().add_modifier(Modifier::ITALIC));
+        frame.render_widget(paragraph, layout.status);
+        return;
+    }
+    if let Some(session) = chess.puzzle_session() {
+        let paragraph = Paragraph::new(puzzle_status(session))
+            .alignment(Alignment::Center)
+            .style(Style::default().add_modifier(Modifier::ITALIC));
+        frame.render_widget(paragraph, layout.status);
+        return;
+    }
+
+    let mut text = match chess.game.status() {
+        GameStatus::Ongoing if board.in_check() => {
+            format!("{} to move, check!", side(board.turn()))
+        }
+        GameStatus::Ongoing => format!("{} to move", side(board.turn())),
+        GameStatus::Checkmate { winner } => {
+            format!("Checkmate, {} wins", side(winner).to_lowercase())
+        }
+        GameStatus::Stalemate => String::from("Draw by stalemate"),
+        GameStatus::FiftyMoveRule => String::from("Draw by the fifty-move rule"),
+        GameStatus::ThreefoldRepetition => String::from("Draw by threefold repetition"),
+        GameStatus::InsufficientMaterial => String::from("Draw by insufficient material"),
+        GameStatus::KingOfTheHill { winner } => {
+            format!("King of the hill, {} wins", side(winner).to_lowercase())
+        }
+        GameStatus::ThreeChecks { winner } => {
+            format!("Third check, {} wins", side(winner).to_lowercase())
+        }
+        GameStatus::KingExploded { winner } => {
+            format!("King exploded, {} wins", side(winner).to_lowercase())
+        }
+        GameStatus::Resignation { winner } => {
+            format!(
+                "{} resigned, {} wins",
+                side(winner.opposite()),
+                side(winner).to_lowercase()
+            )
+        }
+        GameStatus::DrawAgreed => String::from("Draw by agreement"),
+        GameStatus::Abandoned => String::from("Game abandoned, the connection was lost"),
+        GameStatus::OutOfTime { winner } => {
+            format!(
+                "{} ran out of time, {} wins",
+                side(winner.opposite()),
+                side(winner).to_lowercase()
+            )
+        }
+    };
+
+    if chess.is_typing_moves() && !chess.is_opponent_turn() {
+        text.push_str(&format!(" · {}_", chess.move_input()));
+    }
+
+    let paragraph = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().add_modifier(Modifier::ITALIC));
+    frame.render_widget(paragraph, layout.status);
 }
 
+/// The eighths of a cell, from the bottom up, used to draw the edge of the evaluation bar.
+const EIGHTHS: [char; 7] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇'];
+
+/// Shows the evaluation bar beside the board, and the depth, score and best line found by the engine below it.
 #[inline(always)]
-fn white_player_taken_pieces<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>) {
-    let taken_pieces = " ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖ ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖";
-    let x_axis = (frame.size().width / 2) - (BOARD_WIDTH / 2) + 2;
-    let y_axis = (frame.size().height / 2) + 13;
-    let area = Rect::new(x_axis, y_axis, 32, 1);
-    let text = Paragraph::new(taken_pieces).alignment(Alignment::Left);
-    frame.render_widget(text, area);
+fn analysis<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>, layout: &ChessLayout) {
+    let hint = Style::default().fg(Color::DarkGray);
+    if let Some(notice) = chess.notice() {
+        let paragraph = Paragraph::new(notice.to_owned())
+            .alignment(Alignment::Center)
+            .style(hint);
+        frame.render_widget(paragraph, layout.analysis);
+        return;
+    }
+    if let Some(text) = play_status(chess) {
+        let paragraph = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(hint);
+        frame.render_widget(paragraph, layout.analysis);
+        return;
+    }
+    if let Some(tutorial) = chess.tutorial_session() {
+        let paragraph = Paragraph::new(lesson_status(tutorial))
+            .alignment(Alignment::Center)
+            .style(hint);
+        frame.render_widget(paragraph, layout.analysis);
+        return;
+    }
+    if let Some(suggestion) = chess.hint() {
+        let text = match suggestion.best_move() {
+            _ if suggestion.is_thinking() => String::from("Thinking of a hint…"),
+            None => String::from("No hint found"),
+            Some(mv) if suggestion.is_revealed() => {
+                format!("Hint: {}", san_line(chess.game.board(), &[mv]))
+            }
+            Some(_) => {
+                String::from("Hint: move the highlighted piece · ? again for the whole move")
+            }
+        };
+        let paragraph = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(hint);
+        frame.render_widget(paragraph, layout.analysis);
+        return;
+    }
+    if chess.is_typing_moves() {
+        let text = match chess.is_peeking() {
+            true => "Peeking at the board · Tab to hide it again",
+            false => {
+                "Type a move such as Nf3 or o-o, then Enter · Tab to peek · Alt+key for commands"
+            }
+        };
+        let paragraph = Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .style(hint);
+        frame.render_widget(paragraph, layout.analysis);
+        return;
+    }
+    let Some(analysis) = chess.analysis() else {
+        if let Some(review) = chess.review() {
+            let paragraph = Paragraph::new(review_status(review, chess.game.board().variant()))
+                .alignment(Alignment::Center)
+                .style(hint);
+            frame.render_widget(paragraph, layout.analysis);
+        }
+        return;
+    };
+    // the tables are read rather than searched, and know better than the engine when they cover the position
+    let (white_share, mut text, line) = match analysis.tablebase() {
+        Some(verdict) => {
+            let share = match verdict.winner {
+                Some(PieceColor::White) => 1.0,
+                Some(PieceColor::Black) => 0.0,
+                None => 0.5,
+            };
+            let text = format!("tablebase · {verdict}");
+            (share, text, vec![verdict.best_move])
+        }
+        None => {
+            let Some(info) = analysis.latest() else {
+                let text = match analysis.has_engine() {
+                    true => String::from("Analyzing…"),
+                    false => format!(
+                        "The engine does not play {}",
+                        chess.game.board().variant().name()
+                    ),
+                };
+                let paragraph = Paragraph::new(text)
+                    .alignment(Alignment::Center)
+                    .style(Style::default().fg(Color::DarkGray));
+                frame.render_widget(paragraph, layout.analysis);
+                return;
+            };
+            let text = format!("depth {} · {}", info.depth, info.score);
+            (info.score.white_share(), text, info.pv)
+        }
+    };
+
+    let height = layout.eval_bar.height as usize;
+    let eighths = (white_share * (height * 8) as f64).round() as usize;
+    let bar_style = Style::default().fg(Color::White).bg(Color::DarkGray);
+    let rows: Vec<Line> = (0..height)
+        .rev()
+        .map(|row| {
+            let symbol = match eighths.saturating_sub(row * 8) {
+                0 => ' ',
+                filled @ 1..=7 => EIGHTHS[filled - 1],
+                _ => '█',
+            };
+            Line::styled(symbol.to_string(), bar_style)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(rows), layout.eval_bar);
+
+    if !line.is_empty() {
+        text.push_str(" · ");
+        text.push_str(&san_line(chess.game.board(), &line));
+    }
+    // a tablebase that was asked for but could not be read is said so, rather than silently left out
+    if let Some(problem) = tablebase::problem() {
+        text.push_str(" · tablebase: ");
+        text.push_str(&problem);
+    }
+    let paragraph = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(paragraph, layout.analysis);
+}
+
+/// Describes what the game is waiting on while it is played: the player confirming their resignation,
+/// the correspondent moving, or, over the network, the connection coming back or an answer to a draw offer.
+fn play_status<B: Backend>(chess: &Chess<B>) -> Option<String> {
+    if chess.is_resigning() {
+        return Some(String::from(
+            "Press r again to resign · any other key to keep playing",
+        ));
+    }
+    if chess.game.status().is_over() {
+        return None;
+    }
+    if let Some(file) = chess.correspondence_file() {
+        return Some(match file.color() == chess.game.board().turn() {
+            true => {
+                String::from("Your move · it is written to the game file as soon as it is played")
+            }
+            false => format!(
+                "Waiting for your opponent to move in {}",
+                file.path().display()
+            ),
+        });
+    }
+    let network = chess.network_session()?;
+    if let Some(left) = network.reconnect_time_left() {
+        let seconds = left.as_secs();
+        return Some(match network.is_host() {
+            true => format!("Connection lost · waiting {seconds}s for the opponent to reconnect"),
+            false => format!("Connection lost · reconnecting to the host, {seconds}s left"),
+        });
+    }
+    match chess.draw_offer() {
+        Some(color) if color == network.color() => Some(String::from(
+            "Draw offered, waiting for your opponent to answer",
+        )),
+        Some(_) => Some(String::from(
+            "Your opponent offers a draw · o to accept · x to decline",
+        )),
+        None => None,
+    }
+}
+
+/// Describes how far the review of the game has got, and what can be done with it once it is done.
+fn review_status(review: &Review, variant: Variant) -> String {
+    if !review.is_available() {
+        return format!(
+            "No review, the engine does not play {} · e to export",
+            variant.name()
+        );
+    }
+    let (evaluated, total) = review.progress();
+    if !review.is_done() {
+        return format!("Reviewing the game… {evaluated}/{total}");
+    }
+    match review.critical_moments().len() {
+        0 => String::from("Review done, no mistakes found · e to export"),
+        count => {
+            format!("Review done, {count} moves annotated · j/k to step through them · e to export")
+        }
+    }
+}
+
+/// Tells the player whether they played the move the lesson asked for, and how to move between the lessons.
+fn lesson_status(tutorial: &Tutorial) -> &'static str {
+    match (tutorial.state(), tutorial.is_last()) {
+        (LessonState::Learning, _) => "n next lesson · b previous lesson",
+        (LessonState::Wrong, _) => "Not quite, try again · n to skip the lesson",
+        (LessonState::Done, false) => "Well done! Press n for the next lesson",
+        (LessonState::Done, true) => "Well done, that was the last lesson! Press n to start over",
+    }
+}
+
+/// Describes how the player is doing on the current puzzle, along with their puzzle rating.
+fn puzzle_status(session: &PuzzleSession) -> String {
+    let rating = session.rating();
+    let side = match session.color() {
+        Some(PieceColor::White) => "white",
+        Some(PieceColor::Black) => "black",
+        None => "",
+    };
+    let progress = match session.state() {
+        PuzzleState::Solving => format!("Find the best move for {side}"),
+        PuzzleState::Correct => String::from("Correct, keep going"),
+        PuzzleState::Wrong => String::from("Not the move, try again"),
+        PuzzleState::Solved => String::from("Solved! Press n for the next puzzle"),
+        PuzzleState::Unavailable => match (session.puzzle(), puzzle_file()) {
+            (Some(_), _) => String::from("The solution does not fit the position, press n to skip"),
+            (None, Some(path)) => format!("No puzzles found in {}", path.display()),
+            (None, None) => String::from("No data directory to find puzzles in"),
+        },
+    };
+    match (session.state(), rating.change) {
+        (PuzzleState::Unavailable, _) => progress,
+        (PuzzleState::Solved | PuzzleState::Wrong, change) if change != 0 => {
+            format!("{progress} · rating {} ({change:+})", rating.rating)
+        }
+        _ => format!("{progress} · rating {}", rating.rating),
+    }
 }
 
+/// The width of a single choice in the promotion picker, including the space separating it from the next.
+const PROMOTION_CHOICE_WIDTH: u16 = 4;
+
 #[inline(always)]
-fn white_player_moves<B: Backend>(chess: &mut Chess<B>, frame: &mut Frame<'_, B>) {
-    let v = vec![
-        "♜ E2->E4",
-        "♜ E2->E4",
-        "♜ E2->E4",
-        "♜ E2->E4",
-        "♜ E2->E4",
-        "♜ E2->E4",
-        "♜ E2->E4",
-        "♜ E2->E4",
-        "♜ E2->E4",
-        "♜ E2->E4",
-    ];
-
-    let items: Vec<ListItem> = v.iter().map(|item| ListItem::new(*item)).collect();
-    let x_axis = (frame.size().width / 2) + 22;
-    let y_axis = (frame.size().height / 2) - 5;
-    let area = Rect::new(x_axis, y_axis, 8, items.len() as u16);
-    let moves = List::new(items);
-    frame.render_widget(moves, area);
+fn promotion_picker<B: Backend>(
+    chess: &mut Chess<B>,
+    frame: &mut Frame<'_, B>,
+    layout: &ChessLayout,
+) {
+    let Some(promotion) = chess.promotion() else {
+        return;
+    };
+    let color = chess.game.board().turn();
+
+    let mut spans = Vec::new();
+    for (index, variant) in PieceType::PROMOTIONS.into_iter().enumerate() {
+        let style = match index == promotion.index() {
+            true => Style::default().add_modifier(Modifier::REVERSED),
+            false => Style::default(),
+        };
+        spans.push(Span::styled(
+            format!(
+                " {} ",
+                chess.piece_set.text_symbol(Piece::new(color, variant))
+            ),
+            style,
+        ));
+        if index < PieceType::PROMOTIONS.len() - 1 {
+            spans.push(Span::raw(" "));
+        }
+    }
+
+    let width = PieceType::PROMOTIONS.len() as u16 * PROMOTION_CHOICE_WIDTH - 1 + 2;
+    let x_axis = layout.board.x + layout.board.width.saturating_sub(width) / 2;
+    let y_axis = layout.board.y + layout.board.height.saturating_sub(3) / 2;
+    let area = Rect::new(x_axis, y_axis, width, 3);
+    let block = Block::default()
+        .title("Promote")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let picker = Paragraph::new(Line::from(spans)).block(block);
+    frame.render_widget(Clear, area);
+    frame.render_widget(picker, area);
+    chess.promotion_area = area;
+}
+
+/// Maps a terminal cell to the index of the promotion choice rendered there, if the picker was rendered in the given area.
+pub fn promotion_choice_at(area: Rect, x: u16, y: u16) -> Option<usize> {
+    if y != area.y + 1 {
+        return None;
+    }
+    let column = x.checked_sub(area.x + 1)?;
+    let index = (column / PROMOTION_CHOICE_WIDTH) as usize;
+    let is_separator = column % PROMOTION_CHOICE_WIDTH == PROMOTION_CHOICE_WIDTH - 1;
+    (index < PieceType::PROMOTIONS.len() && !is_separator).then_some(index)
 }
@@ -1,6 +1,9 @@
 use crate::{
     app::{App, AppResult},
-    chess::Chess,
+    chess::{
+        archive::Archive, coordinates::Coordinates, correspondence::Correspondence, editor::Editor,
+        lobby::Lobby, profiles::Profiles, setup::Setup, Chess,
+    },
     tui::Tui,
 };
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
@@ -16,10 +19,8 @@ pub fn handle_key_events<B: Backend>(
         KeyCode::Esc | KeyCode::Char('q') => {
             app.quit();
         }
-        KeyCode::Char('c') | KeyCode::Char('C') => {
-            if key_event.modifiers == KeyModifiers::CONTROL {
-                app.quit();
-            }
+        KeyCode::Char('c') | KeyCode::Char('C') if key_event.modifiers == KeyModifiers::CONTROL => {
+            app.quit();
         }
         KeyCode::Up => {
             app.move_up();
@@ -27,14 +28,45 @@ pub fn handle_key_events<B: Backend>(
         KeyCode::Down => {
             app.move_down();
         }
-        KeyCode::Enter => match app.state {
-            0 => {
-                let mut chess_game = Chess::new(tui);
-                chess_game.launch_chess()?;
+        KeyCode::Enter => {
+            match app.selected_game() {
+                "Resume Chess" => {
+                    if let Some(mut chess_game) = Chess::resume(tui) {
+                        chess_game.launch_chess()?;
+                    }
+                }
+                "Chess" => {
+                    let mut setup = Setup::new(tui);
+                    let options = setup.launch_setup()?;
+                    let players = setup.players.clone();
+                    if let Some(options) = options {
+                        let mut chess_game = Chess::with_players(tui, options, players);
+                        chess_game.launch_chess()?;
+                    }
+                }
+                "Chess Puzzles" => {
+                    let mut puzzles = Chess::puzzles(tui);
+                    puzzles.launch_chess()?;
+                }
+                "Chess Tutorial" => Chess::tutorial(tui).launch_chess()?,
+                "Board Editor" => Editor::new(tui).launch_editor()?,
+                "Coordinate Training" => Coordinates::new(tui).launch_coordinates()?,
+                "Correspondence Chess" => Correspondence::new(tui).launch_correspondence()?,
+                "Game Archive" => Archive::new(tui).launch_archive()?,
+                "Chess Profiles" => Profiles::new(tui).launch_profiles()?,
+                "Host LAN Game" => {
+                    let options = Setup::without_players(tui).launch_setup()?;
+                    if let Some(options) = options {
+                        Lobby::host(tui, options).launch_lobby()?;
+                    }
+                }
+                "Join LAN Game" => Lobby::join(tui).launch_lobby()?,
+                "Watch LAN Game" => Lobby::watch(tui).launch_lobby()?,
+                _ => {}
             }
-            1 => {}
-            _ => {}
-        },
+            // the game may have been saved or finished meanwhile
+            app.refresh_games();
+        }
         _ => {}
     }
     Ok(())
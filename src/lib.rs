@@ -14,4 +14,4 @@ pub mod tui;
 pub mod handler;
 
 /// Chess.
-pub mod chess;
\ No newline at end of file
+pub mod chess;
@@ -1,5 +1,6 @@
-use std::io;
+use std::{env, io, path::PathBuf, process};
 use terminal_games::app::{App, AppResult};
+use terminal_games::chess::{self, epd};
 use terminal_games::event::{Event, EventHandler};
 use terminal_games::handler::handle_key_events;
 use terminal_games::tui::Tui;
@@ -7,7 +8,31 @@ use terminal_games::ui;
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 
+/// How the app is started, shown when it is given arguments it does not understand.
+const USAGE: &str = "usage: terminal-games [--syzygy <directory>]\n       terminal-games epd <file> [--time <milliseconds>] [--depth <plies>]";
+
 fn main() -> AppResult<()> {
+    // the EPD runner measures the strength of the chess engine from the command line, without the interface
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("epd") {
+        if let Err(error) = epd::run(&args[1..]) {
+            eprintln!("{error}");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    // the endgame tablebase may be kept anywhere, rather than in the data directory
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--syzygy", Some(dir)) => chess::set_tablebase_directory(PathBuf::from(dir)),
+            _ => {
+                eprintln!("{USAGE}");
+                process::exit(1);
+            }
+        }
+    }
+
     let mut app = App::new();
 
     let backend = CrosstermBackend::new(io::stderr());
@@ -45,9 +45,9 @@ fn game_list<B: Backend>(app: &mut App, frame: &mut Frame<B>) {
     let longest_game_name_length = app
         .games
         .iter()
+        .map(|name| name.len())
         .max()
-        .expect("should always have a list of games")
-        .len() as u16;
+        .expect("should always have a list of games") as u16;
 
     let x_axis = (frame.size().width / 2) - (longest_game_name_length / 2);
     let y_axis = (frame.size().height / 2) - (app.games.len() as u16 / 2);